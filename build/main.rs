@@ -6,7 +6,13 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
 	let out_path = std::path::PathBuf::from(std::env::var("OUT_DIR").unwrap());
 
 	std::fs::write(out_path.join("x86.rs"), tier::x86::src())?;
+	std::fs::write(out_path.join("x86_real.rs"), tier::x86::src_real())?;
 	std::fs::write(out_path.join("amd64.rs"), tier::amd64::src())?;
+	std::fs::write(out_path.join("aarch64.rs"), tier::aarch64::src())?;
+	std::fs::write(out_path.join("riscv64.rs"), tier::riscv64::src())?;
+	std::fs::write(out_path.join("arm32_a32.rs"), tier::arm32::src_a32())?;
+	std::fs::write(out_path.join("arm32_t32.rs"), tier::arm32::src_t32())?;
+	std::fs::write(out_path.join("wasm.rs"), tier::wasm::src())?;
 
 	Ok(())
 }
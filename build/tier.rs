@@ -1,2 +1,6 @@
+pub mod aarch64;
 pub mod amd64;
+pub mod arm32;
+pub mod riscv64;
+pub mod wasm;
 pub mod x86;
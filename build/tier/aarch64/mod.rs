@@ -0,0 +1,267 @@
+use super::x86::util::Size;
+
+/// A single 32 bit instruction word, built from `base` with each field OR'd in.
+fn enc(name: &str, params: &str, base: u32, fields: &[&str]) -> String {
+	let fields = fields.iter().map(|f| format!(" | {f}")).collect::<Vec<_>>().concat();
+
+	indoc::formatdoc! {"
+		#[inline]
+		pub const fn {name}({params}) -> [u8; 4] {{
+			(0x{base:08X}_u32{fields}).to_le_bytes()
+		}}
+	"}
+}
+
+fn sf(size: &Size) -> u32 {
+	match size {
+		Size::U64 => 1 << 31,
+		_ => 0
+	}
+}
+
+/// Shifted register form, like `add x0, x1, x2, lsl #3`. Also emits an unshifted shorthand.
+fn shifted(inst: &str, base: u32, size: Size) -> String {
+	let base = base | sf(&size);
+
+	[
+		enc(&format!("{inst}_r{size}_r{size}_r{size}"), "rd: u8, rn: u8, rm: u8", base, &[
+			"reg(rm) << 16",
+			"reg(rn) << 5",
+			"reg(rd)"
+		]),
+		enc(&format!("{inst}_r{size}_r{size}_r{size}_shift"), "rd: u8, rn: u8, rm: u8, shift: u8, amount: u8", base, &[
+			"((shift & 0b11) as u32) << 22",
+			"reg(rm) << 16",
+			"((amount & 0b11_1111) as u32) << 10",
+			"reg(rn) << 5",
+			"reg(rd)"
+		])
+	].join("\n")
+}
+
+/// Three registers with no shift, like `mul` and `udiv`.
+fn rrr(inst: &str, base: u32, size: Size) -> String {
+	enc(&format!("{inst}_r{size}_r{size}_r{size}"), "rd: u8, rn: u8, rm: u8", base | sf(&size), &[
+		"reg(rm) << 16",
+		"reg(rn) << 5",
+		"reg(rd)"
+	])
+}
+
+/// 12 bit unsigned immediate, like `add x0, x1, #4`.
+fn imm12(inst: &str, base: u32, size: Size) -> String {
+	enc(&format!("{inst}_r{size}_r{size}_i12"), "rd: u8, rn: u8, imm: u16", base | sf(&size), &[
+		"((imm & 0xFFF) as u32) << 10",
+		"reg(rn) << 5",
+		"reg(rd)"
+	])
+}
+
+/// Logical immediate, which only exists for values [encode_bitmask] can express.
+fn bitmask(inst: &str, base: u32, size: Size) -> String {
+	let base = base | sf(&size);
+	let bits = size.bytes() as u32 * 8;
+	let imm = match size {
+		Size::U64 => "imm",
+		_ => "imm as u64"
+	};
+
+	indoc::formatdoc! {"
+		#[inline]
+		pub const fn {inst}_r{size}_r{size}_i{size}(rd: u8, rn: u8, imm: u{size}) -> Option<[u8; 4]> {{
+			match encode_bitmask({imm}, {bits}) {{
+				Some(nrs) => Some((0x{base:08X} | nrs << 10 | reg(rn) << 5 | reg(rd)).to_le_bytes()),
+				None => None
+			}}
+		}}
+	"}
+}
+
+/// Move wide immediate, placing 16 bits at `hw * 16`.
+fn wide(inst: &str, base: u32, size: Size) -> String {
+	enc(&format!("{inst}_r{size}_i16"), "rd: u8, imm: u16, hw: u8", base | sf(&size), &[
+		"((hw & 0b11) as u32) << 21",
+		"(imm as u32) << 5",
+		"reg(rd)"
+	])
+}
+
+/// Every addressing mode of a single register load or store.
+///
+/// `scale` is log2 of the access size, `opc` picks between store, load and sign extending loads.
+fn ldst(inst: &str, unscaled: &str, scale: u32, opc: u32, size: Size) -> String {
+	let base = scale << 30 | opc << 22;
+	let offset = match scale {
+		0 => "((offset & 0xFFF) as u32) << 10".to_string(),
+		_ => format!("(((offset >> {scale}) & 0xFFF) as u32) << 10")
+	};
+
+	[
+		// Unsigned offset, scaled by the access size.
+		enc(&format!("{inst}_r{size}_m"), "rt: u8, rn: u8, offset: u16", 0x3900_0000 | base, &[
+			&offset,
+			"reg(rn) << 5",
+			"reg(rt)"
+		]),
+		enc(&format!("{inst}_r{size}_pre"), "rt: u8, rn: u8, offset: i16", 0x3800_0C00 | base, &[
+			"imm(offset as i32, 9) << 12",
+			"reg(rn) << 5",
+			"reg(rt)"
+		]),
+		enc(&format!("{inst}_r{size}_post"), "rt: u8, rn: u8, offset: i16", 0x3800_0400 | base, &[
+			"imm(offset as i32, 9) << 12",
+			"reg(rn) << 5",
+			"reg(rt)"
+		]),
+		enc(&format!("{unscaled}_r{size}_m"), "rt: u8, rn: u8, offset: i16", 0x3800_0000 | base, &[
+			"imm(offset as i32, 9) << 12",
+			"reg(rn) << 5",
+			"reg(rt)"
+		]),
+		// Register offset, optionally extended and shifted by the access size.
+		enc(&format!("{inst}_r{size}_mr"), "rt: u8, rn: u8, rm: u8, extend: u8, shift: bool", 0x3820_0800 | base, &[
+			"reg(rm) << 16",
+			"((extend & 0b111) as u32) << 13",
+			"(shift as u32) << 12",
+			"reg(rn) << 5",
+			"reg(rt)"
+		])
+	].join("\n")
+}
+
+/// Load from a pc relative address.
+fn literal(inst: &str, opc: u32, size: Size) -> String {
+	enc(&format!("{inst}_r{size}_lit"), "rt: u8, offset: i32", 0x1800_0000 | opc << 30, &[
+		"imm(offset >> 2, 19) << 5",
+		"reg(rt)"
+	])
+}
+
+/// Register pair load or store, with a signed offset scaled by the register size.
+fn pair(inst: &str, load: bool, size: Size) -> String {
+	let (opc, scale) = match size {
+		Size::U64 => (0b10, 3),
+		_ => (0b00, 2)
+	};
+
+	let base = 0x2800_0000 | opc << 30 | (load as u32) << 22;
+	let fields = [
+		&format!("imm(offset as i32 >> {scale}, 7) << 15") as &str,
+		"reg(rt2) << 10",
+		"reg(rn) << 5",
+		"reg(rt)"
+	];
+
+	[
+		enc(&format!("{inst}_r{size}_m"), "rt: u8, rt2: u8, rn: u8, offset: i16", base | 0b010 << 23, &fields),
+		enc(&format!("{inst}_r{size}_pre"), "rt: u8, rt2: u8, rn: u8, offset: i16", base | 0b011 << 23, &fields),
+		enc(&format!("{inst}_r{size}_post"), "rt: u8, rt2: u8, rn: u8, offset: i16", base | 0b001 << 23, &fields)
+	].join("\n")
+}
+
+/// Compare and branch, with a pc relative offset in bytes.
+fn cb(inst: &str, base: u32, size: Size) -> String {
+	enc(&format!("{inst}_r{size}_i19"), "rt: u8, offset: i32", base | sf(&size), &[
+		"imm(offset >> 2, 19) << 5",
+		"reg(rt)"
+	])
+}
+
+/// Test bit and branch. Bits above 31 test the 64 bit register.
+fn tb(inst: &str, base: u32) -> String {
+	enc(&format!("{inst}_i14"), "rt: u8, bit: u8, offset: i32", base, &[
+		"((bit as u32 >> 5) & 1) << 31",
+		"((bit & 0b1_1111) as u32) << 19",
+		"imm(offset >> 2, 14) << 5",
+		"reg(rt)"
+	])
+}
+
+/// Branch to a register.
+fn br(inst: &str, base: u32) -> String {
+	enc(&format!("{inst}_r64"), "rn: u8", base, &["reg(rn) << 5"])
+}
+
+fn zo(inst: &str, word: u32) -> String {
+	enc(inst, "", word, &[])
+}
+
+#[rustfmt::skip]
+pub fn src() -> String {
+	let mut out = vec![];
+
+	for size in [Size::U64, Size::U32] {
+		out.extend([
+			shifted("add", 0x0B00_0000, size),
+			shifted("adds", 0x2B00_0000, size),
+			shifted("sub", 0x4B00_0000, size),
+			shifted("subs", 0x6B00_0000, size),
+			shifted("and", 0x0A00_0000, size),
+			shifted("orr", 0x2A00_0000, size),
+			shifted("eor", 0x4A00_0000, size),
+			shifted("ands", 0x6A00_0000, size),
+
+			imm12("add", 0x1100_0000, size),
+			imm12("adds", 0x3100_0000, size),
+			imm12("sub", 0x5100_0000, size),
+			imm12("subs", 0x7100_0000, size),
+
+			bitmask("and", 0x1200_0000, size),
+			bitmask("orr", 0x3200_0000, size),
+			bitmask("eor", 0x5200_0000, size),
+			bitmask("ands", 0x7200_0000, size),
+
+			rrr("mul", 0x1B00_7C00, size),
+			rrr("udiv", 0x1AC0_0800, size),
+			rrr("sdiv", 0x1AC0_0C00, size),
+			rrr("lslv", 0x1AC0_2000, size),
+			rrr("lsrv", 0x1AC0_2400, size),
+			rrr("asrv", 0x1AC0_2800, size),
+
+			wide("movn", 0x1280_0000, size),
+			wide("movz", 0x5280_0000, size),
+			wide("movk", 0x7280_0000, size),
+
+			cb("cbz", 0x3400_0000, size),
+			cb("cbnz", 0x3500_0000, size),
+
+			pair("stp", false, size),
+			pair("ldp", true, size)
+		]);
+	}
+
+	out.extend([
+		ldst("strb", "sturb", 0, 0b00, Size::U32),
+		ldst("ldrb", "ldurb", 0, 0b01, Size::U32),
+		ldst("ldrsb", "ldursb", 0, 0b10, Size::U64),
+		ldst("strh", "sturh", 1, 0b00, Size::U32),
+		ldst("ldrh", "ldurh", 1, 0b01, Size::U32),
+		ldst("ldrsh", "ldursh", 1, 0b10, Size::U64),
+		ldst("str", "stur", 2, 0b00, Size::U32),
+		ldst("ldr", "ldur", 2, 0b01, Size::U32),
+		ldst("ldrsw", "ldursw", 2, 0b10, Size::U64),
+		ldst("str", "stur", 3, 0b00, Size::U64),
+		ldst("ldr", "ldur", 3, 0b01, Size::U64),
+
+		literal("ldr", 0b00, Size::U32),
+		literal("ldr", 0b01, Size::U64),
+		literal("ldrsw", 0b10, Size::U64),
+
+		enc("b_i26", "offset: i32", 0x1400_0000, &["imm(offset >> 2, 26)"]),
+		enc("bl_i26", "offset: i32", 0x9400_0000, &["imm(offset >> 2, 26)"]),
+		enc("bcond_i19", "cond: u8, offset: i32", 0x5400_0000, &["imm(offset >> 2, 19) << 5", "(cond & 0b1111) as u32"]),
+		tb("tbz", 0x3600_0000),
+		tb("tbnz", 0x3700_0000),
+
+		br("br", 0xD61F_0000),
+		br("blr", 0xD63F_0000),
+		br("ret", 0xD65F_0000),
+		zo("ret", 0xD65F_03C0),
+
+		enc("svc_i16", "imm: u16", 0xD400_0001, &["(imm as u32) << 5"]),
+		enc("brk_i16", "imm: u16", 0xD420_0000, &["(imm as u32) << 5"]),
+		zo("nop", 0xD503_201F)
+	]);
+
+	out.join("\n")
+}
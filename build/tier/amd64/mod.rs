@@ -3,35 +3,64 @@ use super::x86::util::*;
 const COMPAT_16: &str = "COMPAT_16";
 const REX_W: &str = "REX_W";
 
+// Register operands reach r8 through r15 through the REX extension bits.
+const REX_W_RM: &str = "REX_W | rex_r(dst) | rex_b(src)";
+const REX_W_M: &str = "REX_W | rex_b(dst)";
+
+// Registers in the opcode always carry a REX prefix for the same reason, as `push` and `pop` default to 64 bits without REX.W.
+const REX_O: &str = "REX | rex_b(dst)";
+
+// Memory operands always carry a REX prefix, so bases r8 through r15 encode without changing the length.
+const REX_B: &str = "REX | rex_b(base)";
+const REX_WB: &str = "REX_W | rex_b(base)";
+const REX_W_LOAD: &str = "REX_W | rex_r(dst) | rex_b(base)";
+const REX_W_STORE: &str = "REX_W | rex_r(src) | rex_b(base)";
+
 pub fn src() -> String {
 	[
 		rm("add", &[COMPAT_16], 0x03, Size::U16, Size::U16),
-		rm("add", &[REX_W], 0x03, Size::U64, Size::U64),
+		rm("add", &[REX_W_RM], 0x03, Size::U64, Size::U64),
 		mi("add", &[COMPAT_16], 0x81, 0, Size::U16, Size::U16),
-		mi("add", &[REX_W], 0x81, 0, Size::U64, Size::U32),
+		mi("add", &[REX_W_M], 0x81, 0, Size::U64, Size::U32),
 		rm("sub", &[COMPAT_16], 0x2B, Size::U16, Size::U16),
-		rm("sub", &[REX_W], 0x2B, Size::U64, Size::U64),
+		rm("sub", &[REX_W_RM], 0x2B, Size::U64, Size::U64),
 		mi("sub", &[COMPAT_16], 0x81, 5, Size::U16, Size::U16),
-		mi("sub", &[REX_W], 0x81, 5, Size::U64, Size::U32),
-		m("mul", &[REX_W], 0xF7, 4, Size::U64),
-		m("div", &[REX_W], 0xF7, 6, Size::U64),
+		mi("sub", &[REX_W_M], 0x81, 5, Size::U64, Size::U32),
+		m("mul", &[REX_W_M], 0xF7, 4, Size::U64),
+		m("div", &[REX_W_M], 0xF7, 6, Size::U64),
 
-		rm("mov", &[REX_W], 0x8B, Size::U64, Size::U64),
-		oi("mov", &[REX_W], 0xB8, Size::U64, Size::U64),
+		rm("mov", &[REX_W_RM], 0x8B, Size::U64, Size::U64),
+		oi("mov", &[REX_W_M], 0xB8, Size::U64, Size::U64),
+		rm_mem("mov", &[REX_W_LOAD], 0x8B, Size::U64),
+		mr_mem("mov", &[REX_W_STORE], 0x89, Size::U64),
+		rm_ops("imul", &[REX_W_RM], &[0x0F, 0xAF], Size::U64, Size::U64),
 
 		i("push", &[COMPAT_16], &[0x68], Size::U16),
-		o("pop", &[COMPAT_16], &[0x58], Size::U16),
-		rm("or", &[REX_W], 0x09, Size::U64, Size::U64),
-		mi("or", &[REX_W], 0x81, 1, Size::U64, Size::U32),
-		rm("xor", &[REX_W], 0x32, Size::U64, Size::U64),
-		mi("xor", &[REX_W], 0x81, 6, Size::U64, Size::U32),
-		m("not", &[REX_W], 0xF7, 2, Size::U64),
-		m("neg", &[REX_W], 0xF7, 3, Size::U64),
-		rm("cmp", &[REX_W], 0x3B, Size::U64, Size::U64),
-		mi("cmp", &[REX_W], 0x81, 7, Size::U64, Size::U32),
-		m("callnai", &[REX_W], 0xFF, 2, Size::U64),
-		o("push", &[], &[0x50], Size::U64),
-		o("pop", &[], &[0x58], Size::U64),
-		zo("syscall", &[], &[0x0F, 0x05])
+		o("pop", &[COMPAT_16, REX_O], &[0x58], Size::U16),
+		rm("or", &[REX_W_RM], 0x09, Size::U64, Size::U64),
+		mi("or", &[REX_W_M], 0x81, 1, Size::U64, Size::U32),
+		rm("xor", &[REX_W_RM], 0x32, Size::U64, Size::U64),
+		mi("xor", &[REX_W_M], 0x81, 6, Size::U64, Size::U32),
+		m("not", &[REX_W_M], 0xF7, 2, Size::U64),
+		m("neg", &[REX_W_M], 0xF7, 3, Size::U64),
+		rm("cmp", &[REX_W_RM], 0x3B, Size::U64, Size::U64),
+		mi("cmp", &[REX_W_M], 0x81, 7, Size::U64, Size::U32),
+		m("callnai", &[REX_W_M], 0xFF, 2, Size::U64),
+		o("push", &[REX_O], &[0x50], Size::U64),
+		o("pop", &[REX_O], &[0x58], Size::U64),
+		zo("syscall", &[], &[0x0F, 0x05]),
+
+		zo("sysret", &[], &[0x0F, 0x07]),
+		zo("sysretq", &[REX_W], &[0x0F, 0x07]),
+		zo("iretq", &[REX_W], &[0xCF]),
+		zo("swapgs", &[], &[0x0F, 0x01, 0xF8]),
+		m_mem("lgdt", &[REX_B], &[0x0F, 0x01], 2),
+		m_mem("lidt", &[REX_B], &[0x0F, 0x01], 3),
+		m_mem("ltr", &[REX_B], &[0x0F, 0x00], 3),
+		m_mem("invlpg", &[REX_B], &[0x0F, 0x01], 7),
+		m_mem("xsave", &[REX_B], &[0x0F, 0xAE], 4),
+		m_mem("xrstor", &[REX_B], &[0x0F, 0xAE], 5),
+		m_mem("xsave64", &[REX_WB], &[0x0F, 0xAE], 4),
+		m_mem("xrstor64", &[REX_WB], &[0x0F, 0xAE], 5)
 	].join("\n")
 }
@@ -0,0 +1,475 @@
+// A32 data processing opcodes. T32 numbers them differently, so its wide forms spell theirs out.
+const AND: u32 = 0b0000;
+const EOR: u32 = 0b0001;
+const SUB: u32 = 0b0010;
+const RSB: u32 = 0b0011;
+const ADD: u32 = 0b0100;
+const ADC: u32 = 0b0101;
+const SBC: u32 = 0b0110;
+const RSC: u32 = 0b0111;
+const TST: u32 = 0b1000;
+const TEQ: u32 = 0b1001;
+const CMP: u32 = 0b1010;
+const CMN: u32 = 0b1011;
+const ORR: u32 = 0b1100;
+const MOV: u32 = 0b1101;
+const BIC: u32 = 0b1110;
+const MVN: u32 = 0b1111;
+
+fn f(s: &str) -> String {
+	s.to_string()
+}
+
+/// A single A32 word, always executed. Conditions are applied afterwards with `cond`.
+fn enc(name: &str, params: &str, base: u32, fields: &[String]) -> String {
+	let fields = fields.iter().map(|f| format!(" | {f}")).collect::<Vec<_>>().concat();
+
+	indoc::formatdoc! {"
+		#[inline]
+		pub const fn {name}({params}) -> [u8; 4] {{
+			(0x{base:08X}_u32{fields}).to_le_bytes()
+		}}
+	"}
+}
+
+/// A 16 bit T32 instruction.
+fn enc16(name: &str, params: &str, base: u16, fields: &[String]) -> String {
+	// A zero base would be flagged as a no-op, so it's left out.
+	let word = (base != 0)
+		.then(|| format!("0x{base:04X}_u32"))
+		.into_iter()
+		.chain(fields.iter().cloned())
+		.collect::<Vec<_>>()
+		.join(" | ");
+
+	indoc::formatdoc! {"
+		#[inline]
+		pub const fn {name}({params}) -> [u8; 2] {{
+			(({word}) as u16).to_le_bytes()
+		}}
+	"}
+}
+
+/// A 32 bit T32 instruction, stored as two halfwords with the first one holding the opcode.
+fn enc32(name: &str, params: &str, base: u32, fields: &[String]) -> String {
+	let fields = fields.iter().map(|f| format!(" | {f}")).collect::<Vec<_>>().concat();
+
+	indoc::formatdoc! {"
+		#[inline]
+		pub const fn {name}({params}) -> [u8; 4] {{
+			wide(0x{base:08X}_u32{fields})
+		}}
+	"}
+}
+
+/// Operands of a data processing instruction, as some have no destination and others no first source.
+#[derive(Clone, Copy, PartialEq)]
+enum Dp {
+	Rd,
+	Rn,
+	RdRn
+}
+
+impl Dp {
+	fn of(opcode: u32) -> Self {
+		match opcode {
+			TST | TEQ | CMP | CMN => Self::Rn,
+			MOV | MVN => Self::Rd,
+			_ => Self::RdRn
+		}
+	}
+
+	fn regs(self) -> (&'static str, &'static str) {
+		match self {
+			Self::Rd => ("_r32", "rd: u8, "),
+			Self::Rn => ("_r32", "rn: u8, "),
+			Self::RdRn => ("_r32_r32", "rd: u8, rn: u8, ")
+		}
+	}
+
+	fn fields(self) -> Vec<String> {
+		match self {
+			Self::Rd => vec![f("reg(rd) << 12")],
+			Self::Rn => vec![f("reg(rn) << 16")],
+			Self::RdRn => vec![f("reg(rn) << 16"), f("reg(rd) << 12")]
+		}
+	}
+}
+
+/// A32 data processing, with a shifted register and a rotated immediate form.
+fn dp(inst: &str, opcode: u32, s: bool) -> String {
+	let ops = Dp::of(opcode);
+	let (name, params) = ops.regs();
+	let base = opcode << 21 | (s as u32) << 20;
+
+	let mut reg = ops.fields();
+	reg.push(f("reg(rm)"));
+
+	let mut shifted = ops.fields();
+	shifted.extend([
+		f("((amount & 0b1_1111) as u32) << 7"),
+		f("((shift & 0b11) as u32) << 5"),
+		f("reg(rm)")
+	]);
+
+	let fields = ops.fields().iter().map(|f| format!(" | {f}")).collect::<Vec<_>>().concat();
+	let imm_base = 0xE200_0000 | base;
+
+	[
+		enc(&format!("{inst}{name}_r32"), &format!("{params}rm: u8"), 0xE000_0000 | base, &reg),
+		enc(&format!("{inst}{name}_r32_shift"), &format!("{params}rm: u8, shift: u8, amount: u8"), 0xE000_0000 | base, &shifted),
+		indoc::formatdoc! {"
+			#[inline]
+			pub const fn {inst}{name}_i32({params}imm: u32) -> Option<[u8; 4]> {{
+				match encode_imm(imm) {{
+					Some(imm) => Some((0x{imm_base:08X}_u32{fields} | imm).to_le_bytes()),
+					None => None
+				}}
+			}}
+		"}
+	]
+	.join("\n")
+}
+
+/// A32 shift by a register, as `mov rd, rm, <shift> rs`.
+fn shift_reg(inst: &str, shift: u32) -> String {
+	enc(&format!("{inst}_r32_r32_r32"), "rd: u8, rm: u8, rs: u8", 0xE1A0_0010 | shift << 5, &[
+		f("reg(rd) << 12"),
+		f("reg(rs) << 8"),
+		f("reg(rm)")
+	])
+}
+
+/// A32 word and byte loads and stores, with a 12 bit offset or a shifted register.
+fn ldst(inst: &str, byte: bool, load: bool) -> String {
+	let base = (byte as u32) << 22 | (load as u32) << 20;
+	let fields = [f("offset12(offset as i32)"), f("reg(rn) << 16"), f("reg(rt) << 12")];
+
+	let mut out = vec![
+		enc(&format!("{inst}_r32_m"), "rt: u8, rn: u8, offset: i16", 0xE500_0000 | base, &fields),
+		enc(&format!("{inst}_r32_pre"), "rt: u8, rn: u8, offset: i16", 0xE520_0000 | base, &fields),
+		enc(&format!("{inst}_r32_post"), "rt: u8, rn: u8, offset: i16", 0xE400_0000 | base, &fields),
+		enc(&format!("{inst}_r32_mr"), "rt: u8, rn: u8, rm: u8, shift: u8, amount: u8", 0xE780_0000 | base, &[
+			f("reg(rn) << 16"),
+			f("reg(rt) << 12"),
+			f("((amount & 0b1_1111) as u32) << 7"),
+			f("((shift & 0b11) as u32) << 5"),
+			f("reg(rm)")
+		])
+	];
+
+	if load {
+		out.push(enc(&format!("{inst}_r32_lit"), "rt: u8, offset: i32", 0xE51F_0000 | base, &[
+			f("offset12(offset - 8)"),
+			f("reg(rt) << 12")
+		]));
+	}
+
+	out.join("\n")
+}
+
+/// A32 halfword and signed byte loads and stores, with an 8 bit offset or a register.
+fn ldst_h(inst: &str, load: bool, sh: u32) -> String {
+	let base = 0x0000_0090 | (load as u32) << 20 | sh << 5;
+	let fields = [f("offset8(offset as i32)"), f("reg(rn) << 16"), f("reg(rt) << 12")];
+
+	[
+		enc(&format!("{inst}_r32_m"), "rt: u8, rn: u8, offset: i16", 0xE140_0000 | base, &fields),
+		enc(&format!("{inst}_r32_pre"), "rt: u8, rn: u8, offset: i16", 0xE160_0000 | base, &fields),
+		enc(&format!("{inst}_r32_post"), "rt: u8, rn: u8, offset: i16", 0xE040_0000 | base, &fields),
+		enc(&format!("{inst}_r32_mr"), "rt: u8, rn: u8, rm: u8", 0xE180_0000 | base, &[
+			f("reg(rn) << 16"),
+			f("reg(rt) << 12"),
+			f("reg(rm)")
+		])
+	]
+	.join("\n")
+}
+
+/// A32 multiply and divide, with `rd` and `rn` in the high fields.
+fn mul(inst: &str, base: u32) -> String {
+	enc(&format!("{inst}_r32_r32_r32"), "rd: u8, rn: u8, rm: u8", base, &[
+		f("reg(rd) << 16"),
+		f("reg(rm) << 8"),
+		f("reg(rn)")
+	])
+}
+
+#[rustfmt::skip]
+pub fn src_a32() -> String {
+	let mut out = vec![];
+
+	for (inst, opcode) in [("and", AND), ("eor", EOR), ("sub", SUB), ("rsb", RSB), ("add", ADD), ("adc", ADC), ("sbc", SBC), ("rsc", RSC), ("orr", ORR), ("mov", MOV), ("bic", BIC), ("mvn", MVN)] {
+		out.push(dp(inst, opcode, false));
+		out.push(dp(&format!("{inst}s"), opcode, true));
+	}
+
+	for (inst, opcode) in [("tst", TST), ("teq", TEQ), ("cmp", CMP), ("cmn", CMN)] {
+		out.push(dp(inst, opcode, true));
+	}
+
+	out.extend([
+		shift_reg("lsl", 0b00),
+		shift_reg("lsr", 0b01),
+		shift_reg("asr", 0b10),
+		shift_reg("ror", 0b11),
+
+		mul("mul", 0xE000_0090),
+		mul("muls", 0xE010_0090),
+		mul("sdiv", 0xE710_F010),
+		mul("udiv", 0xE730_F010),
+
+		enc("movw_r32_i16", "rd: u8, imm: u16", 0xE300_0000, &[f("((imm >> 12) as u32) << 16"), f("reg(rd) << 12"), f("(imm & 0xFFF) as u32")]),
+		enc("movt_r32_i16", "rd: u8, imm: u16", 0xE340_0000, &[f("((imm >> 12) as u32) << 16"), f("reg(rd) << 12"), f("(imm & 0xFFF) as u32")]),
+
+		ldst("str", false, false),
+		ldst("ldr", false, true),
+		ldst("strb", true, false),
+		ldst("ldrb", true, true),
+		ldst_h("strh", false, 0b01),
+		ldst_h("ldrh", true, 0b01),
+		ldst_h("ldrsb", true, 0b10),
+		ldst_h("ldrsh", true, 0b11),
+
+		enc("push", "regs: u16", 0xE92D_0000, &[f("regs as u32")]),
+		enc("pop", "regs: u16", 0xE8BD_0000, &[f("regs as u32")]),
+
+		enc("b_i24", "offset: i32", 0xEA00_0000, &[f("imm((offset - 8) >> 2, 24)")]),
+		enc("bl_i24", "offset: i32", 0xEB00_0000, &[f("imm((offset - 8) >> 2, 24)")]),
+		enc("blx_i24", "offset: i32", 0xFA00_0000, &[f("imm((offset - 8) >> 1, 1) << 24"), f("imm((offset - 8) >> 2, 24)")]),
+		enc("bx_r32", "rm: u8", 0xE12F_FF10, &[f("reg(rm)")]),
+		enc("blx_r32", "rm: u8", 0xE12F_FF30, &[f("reg(rm)")]),
+
+		enc("svc_i24", "imm: u32", 0xEF00_0000, &[f("imm & 0xFF_FFFF")]),
+		enc("bkpt_i16", "imm: u16", 0xE120_0070, &[f("((imm >> 4) as u32) << 8"), f("(imm & 0xF) as u32")]),
+		enc("nop", "", 0xE320_F000, &[])
+	]);
+
+	out.join("\n")
+}
+
+/// T32 16 bit data processing on two low registers, which always sets flags outside an IT block.
+fn dp16(inst: &str, op: u16) -> String {
+	enc16(&format!("{inst}_r32_r32"), "rdn: u8, rm: u8", 0x4000 | op << 6, &[f("lo(rm) << 3"), f("lo(rdn)")])
+}
+
+/// T32 16 bit instruction taking any two registers.
+fn hi16(inst: &str, base: u16) -> String {
+	enc16(&format!("{inst}_r32_r32"), "rdn: u8, rm: u8", base, &[
+		f("(((rdn >> 3) & 1) as u32) << 7"),
+		f("reg(rm) << 3"),
+		f("lo(rdn)")
+	])
+}
+
+/// T32 16 bit load or store with a 5 bit offset, scaled by the access size.
+fn ldst16(inst: &str, base: u16, scale: u32) -> String {
+	let offset = match scale {
+		0 => f("((offset & 0x1F) as u32) << 6"),
+		_ => format!("(((offset >> {scale}) & 0x1F) as u32) << 6")
+	};
+
+	enc16(&format!("{inst}_r32_m"), "rt: u8, rn: u8, offset: u8", base, &[offset, f("lo(rn) << 3"), f("lo(rt)")])
+}
+
+fn ldst16_reg(inst: &str, base: u16) -> String {
+	enc16(&format!("{inst}_r32_mr"), "rt: u8, rn: u8, rm: u8", base, &[f("lo(rm) << 6"), f("lo(rn) << 3"), f("lo(rt)")])
+}
+
+/// T32 wide data processing, with a shifted register and a modified immediate form.
+fn dp32(inst: &str, op: u32, s: bool, ops: Dp) -> String {
+	let (name, params) = ops.regs();
+	let base = op << 21 | (s as u32) << 20;
+
+	// Operands left out are encoded as 0b1111.
+	let regs = match ops {
+		Dp::Rd => vec![f("0b1111 << 16"), f("reg(rd) << 8")],
+		Dp::Rn => vec![f("reg(rn) << 16"), f("0b1111 << 8")],
+		Dp::RdRn => vec![f("reg(rn) << 16"), f("reg(rd) << 8")]
+	};
+
+	let mut reg = regs.clone();
+	reg.push(f("reg(rm)"));
+
+	let mut shifted = regs.clone();
+	shifted.extend([
+		f("(((amount >> 2) & 0b111) as u32) << 12"),
+		f("((amount & 0b11) as u32) << 6"),
+		f("((shift & 0b11) as u32) << 4"),
+		f("reg(rm)")
+	]);
+
+	let fields = regs.iter().map(|f| format!(" | {f}")).collect::<Vec<_>>().concat();
+	let imm_base = 0xF000_0000 | base;
+
+	[
+		enc32(&format!("{inst}_w{name}_r32"), &format!("{params}rm: u8"), 0xEA00_0000 | base, &reg),
+		enc32(&format!("{inst}_w{name}_r32_shift"), &format!("{params}rm: u8, shift: u8, amount: u8"), 0xEA00_0000 | base, &shifted),
+		indoc::formatdoc! {"
+			#[inline]
+			pub const fn {inst}_w{name}_i32({params}imm: u32) -> Option<[u8; 4]> {{
+				match encode_imm(imm) {{
+					Some(imm) => Some(wide(0x{imm_base:08X}_u32{fields} | spread_imm(imm))),
+					None => None
+				}}
+			}}
+		"}
+	]
+	.join("\n")
+}
+
+/// T32 wide shift by a register.
+fn shift32(inst: &str, shift: u32) -> String {
+	enc32(&format!("{inst}_w_r32_r32_r32"), "rd: u8, rn: u8, rm: u8", 0xFA00_F000 | shift << 21, &[
+		f("reg(rn) << 16"),
+		f("reg(rd) << 8"),
+		f("reg(rm)")
+	])
+}
+
+/// T32 wide loads and stores, `base` being the 8 bit offset form.
+fn ldst32(inst: &str, base: u32) -> String {
+	let fields = [f("reg(rn) << 16"), f("reg(rt) << 12")];
+	let indexed = [f("imm8(offset)"), f("reg(rn) << 16"), f("reg(rt) << 12")];
+	let imm12 = base | 0x0080_0000;
+	let imm8 = base | 0x0800 | 0b100 << 8;
+
+	[
+		// Positive offsets use the 12 bit form, negative ones only have 8 bits.
+		indoc::formatdoc! {"
+			#[inline]
+			pub const fn {inst}_w_r32_m(rt: u8, rn: u8, offset: i16) -> [u8; 4] {{
+				if offset >= 0 {{
+					wide(0x{imm12:08X}_u32 | reg(rn) << 16 | reg(rt) << 12 | (offset as u32 & 0xFFF))
+				}} else {{
+					wide(0x{imm8:08X}_u32 | reg(rn) << 16 | reg(rt) << 12 | imm8(offset))
+				}}
+			}}
+		"},
+		enc32(&format!("{inst}_w_r32_pre"), "rt: u8, rn: u8, offset: i16", base | 0x0800 | 0b101 << 8, &indexed),
+		enc32(&format!("{inst}_w_r32_post"), "rt: u8, rn: u8, offset: i16", base | 0x0800 | 0b001 << 8, &indexed),
+		enc32(&format!("{inst}_w_r32_mr"), "rt: u8, rn: u8, rm: u8, shift: u8", base, &[
+			fields[0].clone(),
+			fields[1].clone(),
+			f("((shift & 0b11) as u32) << 4"),
+			f("reg(rm)")
+		])
+	]
+	.join("\n")
+}
+
+#[rustfmt::skip]
+pub fn src_t32() -> String {
+	let mut out = vec![
+		enc16("lsls_r32_r32_i5", "rd: u8, rm: u8, imm: u8", 0x0000, &[f("((imm & 0x1F) as u32) << 6"), f("lo(rm) << 3"), f("lo(rd)")]),
+		enc16("lsrs_r32_r32_i5", "rd: u8, rm: u8, imm: u8", 0x0800, &[f("((imm & 0x1F) as u32) << 6"), f("lo(rm) << 3"), f("lo(rd)")]),
+		enc16("asrs_r32_r32_i5", "rd: u8, rm: u8, imm: u8", 0x1000, &[f("((imm & 0x1F) as u32) << 6"), f("lo(rm) << 3"), f("lo(rd)")]),
+		enc16("adds_r32_r32_r32", "rd: u8, rn: u8, rm: u8", 0x1800, &[f("lo(rm) << 6"), f("lo(rn) << 3"), f("lo(rd)")]),
+		enc16("subs_r32_r32_r32", "rd: u8, rn: u8, rm: u8", 0x1A00, &[f("lo(rm) << 6"), f("lo(rn) << 3"), f("lo(rd)")]),
+		enc16("adds_r32_r32_i3", "rd: u8, rn: u8, imm: u8", 0x1C00, &[f("((imm & 0b111) as u32) << 6"), f("lo(rn) << 3"), f("lo(rd)")]),
+		enc16("subs_r32_r32_i3", "rd: u8, rn: u8, imm: u8", 0x1E00, &[f("((imm & 0b111) as u32) << 6"), f("lo(rn) << 3"), f("lo(rd)")]),
+		enc16("movs_r32_i8", "rd: u8, imm: u8", 0x2000, &[f("lo(rd) << 8"), f("imm as u32")]),
+		enc16("cmp_r32_i8", "rn: u8, imm: u8", 0x2800, &[f("lo(rn) << 8"), f("imm as u32")]),
+		enc16("adds_r32_i8", "rdn: u8, imm: u8", 0x3000, &[f("lo(rdn) << 8"), f("imm as u32")]),
+		enc16("subs_r32_i8", "rdn: u8, imm: u8", 0x3800, &[f("lo(rdn) << 8"), f("imm as u32")])
+	];
+
+	for (inst, op) in [("ands", 0x0), ("eors", 0x1), ("lsls", 0x2), ("lsrs", 0x3), ("asrs", 0x4), ("adcs", 0x5), ("sbcs", 0x6), ("rors", 0x7), ("tst", 0x8), ("negs", 0x9), ("cmn", 0xB), ("orrs", 0xC), ("muls", 0xD), ("bics", 0xE), ("mvns", 0xF)] {
+		out.push(dp16(inst, op));
+	}
+
+	out.extend([
+		hi16("add", 0x4400),
+		hi16("mov", 0x4600),
+		enc16("bx_r32", "rm: u8", 0x4700, &[f("reg(rm) << 3")]),
+		enc16("blx_r32", "rm: u8", 0x4780, &[f("reg(rm) << 3")]),
+
+		ldst16("str", 0x6000, 2),
+		ldst16("ldr", 0x6800, 2),
+		ldst16("strb", 0x7000, 0),
+		ldst16("ldrb", 0x7800, 0),
+		ldst16("strh", 0x8000, 1),
+		ldst16("ldrh", 0x8800, 1),
+
+		ldst16_reg("str", 0x5000),
+		ldst16_reg("strh", 0x5200),
+		ldst16_reg("strb", 0x5400),
+		ldst16_reg("ldrsb", 0x5600),
+		ldst16_reg("ldr", 0x5800),
+		ldst16_reg("ldrh", 0x5A00),
+		ldst16_reg("ldrb", 0x5C00),
+		ldst16_reg("ldrsh", 0x5E00),
+
+		enc16("str_r32_sp", "rt: u8, offset: u16", 0x9000, &[f("lo(rt) << 8"), f("((offset >> 2) & 0xFF) as u32")]),
+		enc16("ldr_r32_sp", "rt: u8, offset: u16", 0x9800, &[f("lo(rt) << 8"), f("((offset >> 2) & 0xFF) as u32")]),
+		enc16("add_sp_i7", "offset: u16", 0xB000, &[f("((offset >> 2) & 0x7F) as u32")]),
+		enc16("sub_sp_i7", "offset: u16", 0xB080, &[f("((offset >> 2) & 0x7F) as u32")]),
+
+		// Only the low registers and `lr` / `pc` fit, anything else needs the wide form.
+		enc16("push", "regs: u16", 0xB400, &[f("(((regs >> 14) & 1) as u32) << 8"), f("(regs & 0xFF) as u32")]),
+		enc16("pop", "regs: u16", 0xBC00, &[f("(((regs >> 15) & 1) as u32) << 8"), f("(regs & 0xFF) as u32")]),
+
+		enc16("b_i11", "offset: i32", 0xE000, &[f("imm((offset - 4) >> 1, 11)")]),
+		enc16("bcond_i8", "cond: u8, offset: i32", 0xD000, &[f("((cond & 0b1111) as u32) << 8"), f("imm((offset - 4) >> 1, 8)")]),
+		enc16("cbz_r32_i6", "rn: u8, offset: i32", 0xB100, &[f("imm((offset - 4) >> 6, 1) << 9"), f("imm((offset - 4) >> 1, 5) << 3"), f("lo(rn)")]),
+		enc16("cbnz_r32_i6", "rn: u8, offset: i32", 0xB900, &[f("imm((offset - 4) >> 6, 1) << 9"), f("imm((offset - 4) >> 1, 5) << 3"), f("lo(rn)")]),
+		enc16("it", "cond: u8, mask: u8", 0xBF00, &[f("((cond & 0b1111) as u32) << 4"), f("(mask & 0b1111) as u32")]),
+
+		enc16("svc_i8", "imm: u8", 0xDF00, &[f("imm as u32")]),
+		enc16("bkpt_i8", "imm: u8", 0xBE00, &[f("imm as u32")]),
+		enc16("nop", "", 0xBF00, &[])
+	]);
+
+	for (inst, op, ops) in [("and", 0b0000, Dp::RdRn), ("bic", 0b0001, Dp::RdRn), ("orr", 0b0010, Dp::RdRn), ("orn", 0b0011, Dp::RdRn), ("eor", 0b0100, Dp::RdRn), ("add", 0b1000, Dp::RdRn), ("adc", 0b1010, Dp::RdRn), ("sbc", 0b1011, Dp::RdRn), ("sub", 0b1101, Dp::RdRn), ("rsb", 0b1110, Dp::RdRn), ("mov", 0b0010, Dp::Rd), ("mvn", 0b0011, Dp::Rd)] {
+		out.push(dp32(inst, op, false, ops));
+		out.push(dp32(&format!("{inst}s"), op, true, ops));
+	}
+
+	for (inst, op) in [("tst", 0b0000), ("teq", 0b0100), ("cmn", 0b1000), ("cmp", 0b1101)] {
+		out.push(dp32(inst, op, true, Dp::Rn));
+	}
+
+	out.extend([
+		shift32("lsl", 0b00),
+		shift32("lsr", 0b01),
+		shift32("asr", 0b10),
+		shift32("ror", 0b11),
+
+		enc32("mul_r32_r32_r32", "rd: u8, rn: u8, rm: u8", 0xFB00_F000, &[f("reg(rn) << 16"), f("reg(rd) << 8"), f("reg(rm)")]),
+		enc32("sdiv_r32_r32_r32", "rd: u8, rn: u8, rm: u8", 0xFB90_F0F0, &[f("reg(rn) << 16"), f("reg(rd) << 8"), f("reg(rm)")]),
+		enc32("udiv_r32_r32_r32", "rd: u8, rn: u8, rm: u8", 0xFBB0_F0F0, &[f("reg(rn) << 16"), f("reg(rd) << 8"), f("reg(rm)")]),
+
+		enc32("movw_r32_i16", "rd: u8, imm: u16", 0xF240_0000, &[
+			f("((imm >> 12) as u32) << 16"),
+			f("(((imm >> 11) & 1) as u32) << 26"),
+			f("(((imm >> 8) & 0b111) as u32) << 12"),
+			f("reg(rd) << 8"),
+			f("(imm & 0xFF) as u32")
+		]),
+		enc32("movt_r32_i16", "rd: u8, imm: u16", 0xF2C0_0000, &[
+			f("((imm >> 12) as u32) << 16"),
+			f("(((imm >> 11) & 1) as u32) << 26"),
+			f("(((imm >> 8) & 0b111) as u32) << 12"),
+			f("reg(rd) << 8"),
+			f("(imm & 0xFF) as u32")
+		]),
+
+		ldst32("str", 0xF840_0000),
+		ldst32("ldr", 0xF850_0000),
+		ldst32("strb", 0xF800_0000),
+		ldst32("ldrb", 0xF810_0000),
+		ldst32("strh", 0xF820_0000),
+		ldst32("ldrh", 0xF830_0000),
+		ldst32("ldrsb", 0xF910_0000),
+		ldst32("ldrsh", 0xF930_0000),
+
+		enc32("push_w", "regs: u16", 0xE92D_0000, &[f("regs as u32")]),
+		enc32("pop_w", "regs: u16", 0xE8BD_0000, &[f("regs as u32")]),
+
+		enc32("b_w_i24", "offset: i32", 0xF000_9000, &[f("branch24(offset)")]),
+		enc32("bl_i24", "offset: i32", 0xF000_D000, &[f("branch24(offset)")]),
+		enc32("bcond_w_i20", "cond: u8, offset: i32", 0xF000_8000, &[f("((cond & 0b1111) as u32) << 22"), f("branch20(offset)")])
+	]);
+
+	out.join("\n")
+}
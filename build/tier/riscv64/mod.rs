@@ -0,0 +1,434 @@
+// Opcodes
+const OP: u32 = 0b011_0011;
+const OP_32: u32 = 0b011_1011;
+const OP_IMM: u32 = 0b001_0011;
+const OP_IMM_32: u32 = 0b001_1011;
+const LOAD: u32 = 0b000_0011;
+const STORE: u32 = 0b010_0011;
+const BRANCH: u32 = 0b110_0011;
+const LOAD_FP: u32 = 0b000_0111;
+const STORE_FP: u32 = 0b010_0111;
+const AMO: u32 = 0b010_1111;
+const OP_FP: u32 = 0b101_0011;
+
+/// Scatters bits `hi..=lo` of `var` to start at `pos`, for the split immediates of most formats.
+fn scatter(var: &str, fields: &[(u32, u32, u32)]) -> Vec<String> {
+	fields
+		.iter()
+		.map(|(hi, lo, pos)| format!("bits({var}, {hi}, {lo}) << {pos}"))
+		.collect()
+}
+
+fn enc(name: &str, params: &str, base: u32, fields: &[String]) -> String {
+	let fields = fields.iter().map(|f| format!(" | {f}")).collect::<Vec<_>>().concat();
+
+	indoc::formatdoc! {"
+		#[inline]
+		pub const fn {name}({params}) -> [u8; 4] {{
+			(0x{base:08X}_u32{fields}).to_le_bytes()
+		}}
+	"}
+}
+
+fn enc16(name: &str, params: &str, base: u16, fields: &[String]) -> String {
+	// A zero base would be flagged as a no-op, so it's left out.
+	let word = (base != 0)
+		.then(|| format!("0x{base:04X}_u32"))
+		.into_iter()
+		.chain(fields.iter().cloned())
+		.collect::<Vec<_>>()
+		.join(" | ");
+
+	indoc::formatdoc! {"
+		#[inline]
+		pub const fn {name}({params}) -> [u8; 2] {{
+			(({word}) as u16).to_le_bytes()
+		}}
+	"}
+}
+
+fn f(s: &str) -> String {
+	s.to_string()
+}
+
+fn r(name: &str, funct7: u32, funct3: u32, opcode: u32) -> String {
+	enc(name, "rd: u8, rs1: u8, rs2: u8", funct7 << 25 | funct3 << 12 | opcode, &[
+		f("reg(rs2) << 20"),
+		f("reg(rs1) << 15"),
+		f("reg(rd) << 7")
+	])
+}
+
+fn i(name: &str, funct3: u32, opcode: u32) -> String {
+	enc(name, "rd: u8, rs1: u8, imm: i32", funct3 << 12 | opcode, &[
+		f("bits(imm, 11, 0) << 20"),
+		f("reg(rs1) << 15"),
+		f("reg(rd) << 7")
+	])
+}
+
+/// Shift by an immediate, with a 6 bit shift amount for 64 bit shifts and 5 bits for the `w` forms.
+fn shift(name: &str, funct6: u32, funct3: u32, opcode: u32, width: u32) -> String {
+	enc(name, "rd: u8, rs1: u8, shamt: u8", funct6 << 26 | funct3 << 12 | opcode, &[
+		format!("((shamt as u32) & 0x{:X}) << 20", (1 << width) - 1),
+		f("reg(rs1) << 15"),
+		f("reg(rd) << 7")
+	])
+}
+
+fn load(name: &str, funct3: u32, opcode: u32) -> String {
+	enc(name, "rd: u8, rs1: u8, offset: i32", funct3 << 12 | opcode, &[
+		f("bits(offset, 11, 0) << 20"),
+		f("reg(rs1) << 15"),
+		f("reg(rd) << 7")
+	])
+}
+
+fn store(name: &str, funct3: u32, opcode: u32) -> String {
+	let mut fields = vec![f("reg(rs2) << 20"), f("reg(rs1) << 15")];
+	fields.extend(scatter("offset", &[(11, 5, 25), (4, 0, 7)]));
+
+	enc(name, "rs2: u8, rs1: u8, offset: i32", funct3 << 12 | opcode, &fields)
+}
+
+fn branch(name: &str, funct3: u32) -> String {
+	let mut fields = vec![f("reg(rs2) << 20"), f("reg(rs1) << 15")];
+	fields.extend(scatter("offset", &[(12, 12, 31), (10, 5, 25), (4, 1, 8), (11, 11, 7)]));
+
+	enc(name, "rs1: u8, rs2: u8, offset: i32", funct3 << 12 | BRANCH, &fields)
+}
+
+fn upper(name: &str, opcode: u32) -> String {
+	enc(name, "rd: u8, imm: u32", opcode, &[f("(imm & 0xF_FFFF) << 12"), f("reg(rd) << 7")])
+}
+
+fn amo(name: &str, funct5: u32, funct3: u32) -> String {
+	enc(name, "rd: u8, rs2: u8, rs1: u8, ordering: u8", funct5 << 27 | funct3 << 12 | AMO, &[
+		f("((ordering & 0b11) as u32) << 25"),
+		f("reg(rs2) << 20"),
+		f("reg(rs1) << 15"),
+		f("reg(rd) << 7")
+	])
+}
+
+fn lr(name: &str, funct3: u32) -> String {
+	enc(name, "rd: u8, rs1: u8, ordering: u8", 0b00010 << 27 | funct3 << 12 | AMO, &[
+		f("((ordering & 0b11) as u32) << 25"),
+		f("reg(rs1) << 15"),
+		f("reg(rd) << 7")
+	])
+}
+
+/// Floating point operation taking a rounding mode.
+fn fr(name: &str, funct7: u32) -> String {
+	enc(name, "rd: u8, rs1: u8, rs2: u8, rm: u8", funct7 << 25 | OP_FP, &[
+		f("reg(rs2) << 20"),
+		f("reg(rs1) << 15"),
+		f("((rm & 0b111) as u32) << 12"),
+		f("reg(rd) << 7")
+	])
+}
+
+/// Single operand floating point operation, where `rs2` selects the operation.
+/// Inexact conversions take a rounding mode, anything else has a fixed `funct3`.
+fn funary(name: &str, funct7: u32, rs2: u32, funct3: Option<u32>) -> String {
+	let base = funct7 << 25 | rs2 << 20 | OP_FP;
+
+	match funct3 {
+		Some(funct3) => enc(name, "rd: u8, rs1: u8", base | funct3 << 12, &[f("reg(rs1) << 15"), f("reg(rd) << 7")]),
+		None => enc(name, "rd: u8, rs1: u8, rm: u8", base, &[
+			f("reg(rs1) << 15"),
+			f("((rm & 0b111) as u32) << 12"),
+			f("reg(rd) << 7")
+		])
+	}
+}
+
+/// Fused multiply add, with a third source register.
+fn r4(name: &str, fmt: u32, opcode: u32) -> String {
+	enc(name, "rd: u8, rs1: u8, rs2: u8, rs3: u8, rm: u8", fmt << 25 | opcode, &[
+		f("reg(rs3) << 27"),
+		f("reg(rs2) << 20"),
+		f("reg(rs1) << 15"),
+		f("((rm & 0b111) as u32) << 12"),
+		f("reg(rd) << 7")
+	])
+}
+
+/// Compressed load or store relative to a register in x8 to x15.
+fn cls(name: &str, base: u16, reg: &str, offset: &[(u32, u32, u32)]) -> String {
+	let mut fields = vec![format!("creg({reg}) << 2"), f("creg(rs1) << 7")];
+	fields.extend(scatter("offset", offset));
+
+	enc16(name, &format!("{reg}: u8, rs1: u8, offset: i32"), base, &fields)
+}
+
+/// Compressed load relative to `sp`.
+fn clsp(name: &str, base: u16, offset: &[(u32, u32, u32)]) -> String {
+	let mut fields = vec![f("reg(rd) << 7")];
+	fields.extend(scatter("offset", offset));
+
+	enc16(name, "rd: u8, offset: i32", base, &fields)
+}
+
+/// Compressed store relative to `sp`.
+fn cssp(name: &str, base: u16, offset: &[(u32, u32, u32)]) -> String {
+	let mut fields = vec![f("reg(rs2) << 2")];
+	fields.extend(scatter("offset", offset));
+
+	enc16(name, "rs2: u8, offset: i32", base, &fields)
+}
+
+/// Compressed operation with a 6 bit immediate on a full register.
+fn ci(name: &str, base: u16) -> String {
+	let mut fields = vec![f("reg(rd) << 7")];
+	fields.extend(scatter("imm", &[(5, 5, 12), (4, 0, 2)]));
+
+	enc16(name, "rd: u8, imm: i32", base, &fields)
+}
+
+/// Compressed operation with a 6 bit immediate on a register in x8 to x15.
+fn cb_imm(name: &str, base: u16) -> String {
+	let mut fields = vec![f("creg(rd) << 7")];
+	fields.extend(scatter("imm", &[(5, 5, 12), (4, 0, 2)]));
+
+	enc16(name, "rd: u8, imm: i32", base, &fields)
+}
+
+/// Compressed operation on two registers in x8 to x15.
+fn ca(name: &str, base: u16) -> String {
+	enc16(name, "rd: u8, rs2: u8", base, &[f("creg(rd) << 7"), f("creg(rs2) << 2")])
+}
+
+fn cbranch(name: &str, base: u16) -> String {
+	let mut fields = vec![f("creg(rs1) << 7")];
+	fields.extend(scatter("offset", &[(8, 8, 12), (4, 3, 10), (7, 6, 5), (2, 1, 3), (5, 5, 2)]));
+
+	enc16(name, "rs1: u8, offset: i32", base, &fields)
+}
+
+const CJ: &[(u32, u32, u32)] = &[(11, 11, 12), (4, 4, 11), (9, 8, 9), (10, 10, 8), (6, 6, 7), (7, 7, 6), (3, 1, 3), (5, 5, 2)];
+
+#[rustfmt::skip]
+pub fn src() -> String {
+	let mut jal = vec![f("reg(rd) << 7")];
+	jal.extend(scatter("offset", &[(20, 20, 31), (10, 1, 21), (11, 11, 20), (19, 12, 12)]));
+
+	let addi16sp = scatter("imm", &[(9, 9, 12), (4, 4, 6), (6, 6, 5), (8, 7, 3), (5, 5, 2)]);
+
+	let mut addi4spn = vec![f("creg(rd) << 2")];
+	addi4spn.extend(scatter("imm", &[(5, 4, 11), (9, 6, 7), (2, 2, 6), (3, 3, 5)]));
+
+	[
+		// RV64I
+		r("add", 0b000_0000, 0b000, OP),
+		r("sub", 0b010_0000, 0b000, OP),
+		r("sll", 0b000_0000, 0b001, OP),
+		r("slt", 0b000_0000, 0b010, OP),
+		r("sltu", 0b000_0000, 0b011, OP),
+		r("xor", 0b000_0000, 0b100, OP),
+		r("srl", 0b000_0000, 0b101, OP),
+		r("sra", 0b010_0000, 0b101, OP),
+		r("or", 0b000_0000, 0b110, OP),
+		r("and", 0b000_0000, 0b111, OP),
+		r("addw", 0b000_0000, 0b000, OP_32),
+		r("subw", 0b010_0000, 0b000, OP_32),
+		r("sllw", 0b000_0000, 0b001, OP_32),
+		r("srlw", 0b000_0000, 0b101, OP_32),
+		r("sraw", 0b010_0000, 0b101, OP_32),
+
+		i("addi", 0b000, OP_IMM),
+		i("slti", 0b010, OP_IMM),
+		i("sltiu", 0b011, OP_IMM),
+		i("xori", 0b100, OP_IMM),
+		i("ori", 0b110, OP_IMM),
+		i("andi", 0b111, OP_IMM),
+		i("addiw", 0b000, OP_IMM_32),
+		shift("slli", 0b00_0000, 0b001, OP_IMM, 6),
+		shift("srli", 0b00_0000, 0b101, OP_IMM, 6),
+		shift("srai", 0b01_0000, 0b101, OP_IMM, 6),
+		shift("slliw", 0b00_0000, 0b001, OP_IMM_32, 5),
+		shift("srliw", 0b00_0000, 0b101, OP_IMM_32, 5),
+		shift("sraiw", 0b01_0000, 0b101, OP_IMM_32, 5),
+
+		load("lb", 0b000, LOAD),
+		load("lh", 0b001, LOAD),
+		load("lw", 0b010, LOAD),
+		load("ld", 0b011, LOAD),
+		load("lbu", 0b100, LOAD),
+		load("lhu", 0b101, LOAD),
+		load("lwu", 0b110, LOAD),
+		store("sb", 0b000, STORE),
+		store("sh", 0b001, STORE),
+		store("sw", 0b010, STORE),
+		store("sd", 0b011, STORE),
+
+		branch("beq", 0b000),
+		branch("bne", 0b001),
+		branch("blt", 0b100),
+		branch("bge", 0b101),
+		branch("bltu", 0b110),
+		branch("bgeu", 0b111),
+
+		upper("lui", 0b011_0111),
+		upper("auipc", 0b001_0111),
+		enc("jal", "rd: u8, offset: i32", 0b110_1111, &jal),
+		load("jalr", 0b000, 0b110_0111),
+
+		enc("ecall", "", 0x0000_0073, &[]),
+		enc("ebreak", "", 0x0010_0073, &[]),
+		enc("fence", "pred: u8, succ: u8", 0x0000_000F, &[f("((pred & 0b1111) as u32) << 24"), f("((succ & 0b1111) as u32) << 20")]),
+		enc("fence_i", "", 0x0000_100F, &[]),
+
+		// M
+		r("mul", 0b000_0001, 0b000, OP),
+		r("mulh", 0b000_0001, 0b001, OP),
+		r("mulhsu", 0b000_0001, 0b010, OP),
+		r("mulhu", 0b000_0001, 0b011, OP),
+		r("div", 0b000_0001, 0b100, OP),
+		r("divu", 0b000_0001, 0b101, OP),
+		r("rem", 0b000_0001, 0b110, OP),
+		r("remu", 0b000_0001, 0b111, OP),
+		r("mulw", 0b000_0001, 0b000, OP_32),
+		r("divw", 0b000_0001, 0b100, OP_32),
+		r("divuw", 0b000_0001, 0b101, OP_32),
+		r("remw", 0b000_0001, 0b110, OP_32),
+		r("remuw", 0b000_0001, 0b111, OP_32),
+
+		// A
+		lr("lr_w", 0b010),
+		lr("lr_d", 0b011),
+		amo("sc_w", 0b00011, 0b010),
+		amo("sc_d", 0b00011, 0b011),
+		amo("amoswap_w", 0b00001, 0b010),
+		amo("amoswap_d", 0b00001, 0b011),
+		amo("amoadd_w", 0b00000, 0b010),
+		amo("amoadd_d", 0b00000, 0b011),
+		amo("amoxor_w", 0b00100, 0b010),
+		amo("amoxor_d", 0b00100, 0b011),
+		amo("amoand_w", 0b01100, 0b010),
+		amo("amoand_d", 0b01100, 0b011),
+		amo("amoor_w", 0b01000, 0b010),
+		amo("amoor_d", 0b01000, 0b011),
+		amo("amomin_w", 0b10000, 0b010),
+		amo("amomin_d", 0b10000, 0b011),
+		amo("amomax_w", 0b10100, 0b010),
+		amo("amomax_d", 0b10100, 0b011),
+		amo("amominu_w", 0b11000, 0b010),
+		amo("amominu_d", 0b11000, 0b011),
+		amo("amomaxu_w", 0b11100, 0b010),
+		amo("amomaxu_d", 0b11100, 0b011),
+
+		// F and D
+		load("flw", 0b010, LOAD_FP),
+		load("fld", 0b011, LOAD_FP),
+		store("fsw", 0b010, STORE_FP),
+		store("fsd", 0b011, STORE_FP),
+
+		fr("fadd_s", 0b000_0000),
+		fr("fsub_s", 0b000_0100),
+		fr("fmul_s", 0b000_1000),
+		fr("fdiv_s", 0b000_1100),
+		fr("fadd_d", 0b000_0001),
+		fr("fsub_d", 0b000_0101),
+		fr("fmul_d", 0b000_1001),
+		fr("fdiv_d", 0b000_1101),
+		funary("fsqrt_s", 0b010_1100, 0, None),
+		funary("fsqrt_d", 0b010_1101, 0, None),
+
+		r("fsgnj_s", 0b001_0000, 0b000, OP_FP),
+		r("fsgnjn_s", 0b001_0000, 0b001, OP_FP),
+		r("fsgnjx_s", 0b001_0000, 0b010, OP_FP),
+		r("fsgnj_d", 0b001_0001, 0b000, OP_FP),
+		r("fsgnjn_d", 0b001_0001, 0b001, OP_FP),
+		r("fsgnjx_d", 0b001_0001, 0b010, OP_FP),
+		r("fmin_s", 0b001_0100, 0b000, OP_FP),
+		r("fmax_s", 0b001_0100, 0b001, OP_FP),
+		r("fmin_d", 0b001_0101, 0b000, OP_FP),
+		r("fmax_d", 0b001_0101, 0b001, OP_FP),
+		r("feq_s", 0b101_0000, 0b010, OP_FP),
+		r("flt_s", 0b101_0000, 0b001, OP_FP),
+		r("fle_s", 0b101_0000, 0b000, OP_FP),
+		r("feq_d", 0b101_0001, 0b010, OP_FP),
+		r("flt_d", 0b101_0001, 0b001, OP_FP),
+		r("fle_d", 0b101_0001, 0b000, OP_FP),
+
+		funary("fcvt_w_s", 0b110_0000, 0, None),
+		funary("fcvt_wu_s", 0b110_0000, 1, None),
+		funary("fcvt_l_s", 0b110_0000, 2, None),
+		funary("fcvt_lu_s", 0b110_0000, 3, None),
+		funary("fcvt_s_w", 0b110_1000, 0, None),
+		funary("fcvt_s_wu", 0b110_1000, 1, None),
+		funary("fcvt_s_l", 0b110_1000, 2, None),
+		funary("fcvt_s_lu", 0b110_1000, 3, None),
+		funary("fcvt_w_d", 0b110_0001, 0, None),
+		funary("fcvt_wu_d", 0b110_0001, 1, None),
+		funary("fcvt_l_d", 0b110_0001, 2, None),
+		funary("fcvt_lu_d", 0b110_0001, 3, None),
+		funary("fcvt_d_w", 0b110_1001, 0, Some(0b000)),
+		funary("fcvt_d_wu", 0b110_1001, 1, Some(0b000)),
+		funary("fcvt_d_l", 0b110_1001, 2, None),
+		funary("fcvt_d_lu", 0b110_1001, 3, None),
+		funary("fcvt_s_d", 0b010_0000, 1, None),
+		funary("fcvt_d_s", 0b010_0001, 0, Some(0b000)),
+
+		funary("fmv_x_w", 0b111_0000, 0, Some(0b000)),
+		funary("fmv_w_x", 0b111_1000, 0, Some(0b000)),
+		funary("fmv_x_d", 0b111_0001, 0, Some(0b000)),
+		funary("fmv_d_x", 0b111_1001, 0, Some(0b000)),
+		funary("fclass_s", 0b111_0000, 0, Some(0b001)),
+		funary("fclass_d", 0b111_0001, 0, Some(0b001)),
+
+		r4("fmadd_s", 0b00, 0b100_0011),
+		r4("fmsub_s", 0b00, 0b100_0111),
+		r4("fnmsub_s", 0b00, 0b100_1011),
+		r4("fnmadd_s", 0b00, 0b100_1111),
+		r4("fmadd_d", 0b01, 0b100_0011),
+		r4("fmsub_d", 0b01, 0b100_0111),
+		r4("fnmsub_d", 0b01, 0b100_1011),
+		r4("fnmadd_d", 0b01, 0b100_1111),
+
+		// C, quadrant 0
+		enc16("c_addi4spn", "rd: u8, imm: i32", 0x0000, &addi4spn),
+		cls("c_fld", 0x2000, "rd", &[(5, 3, 10), (7, 6, 5)]),
+		cls("c_lw", 0x4000, "rd", &[(5, 3, 10), (2, 2, 6), (6, 6, 5)]),
+		cls("c_ld", 0x6000, "rd", &[(5, 3, 10), (7, 6, 5)]),
+		cls("c_fsd", 0xA000, "rs2", &[(5, 3, 10), (7, 6, 5)]),
+		cls("c_sw", 0xC000, "rs2", &[(5, 3, 10), (2, 2, 6), (6, 6, 5)]),
+		cls("c_sd", 0xE000, "rs2", &[(5, 3, 10), (7, 6, 5)]),
+
+		// C, quadrant 1
+		enc16("c_nop", "", 0x0001, &[]),
+		ci("c_addi", 0x0001),
+		ci("c_addiw", 0x2001),
+		ci("c_li", 0x4001),
+		enc16("c_addi16sp", "imm: i32", 0x6101, &addi16sp),
+		ci("c_lui", 0x6001),
+		cb_imm("c_srli", 0x8001),
+		cb_imm("c_srai", 0x8401),
+		cb_imm("c_andi", 0x8801),
+		ca("c_sub", 0x8C01),
+		ca("c_xor", 0x8C21),
+		ca("c_or", 0x8C41),
+		ca("c_and", 0x8C61),
+		ca("c_subw", 0x9C01),
+		ca("c_addw", 0x9C21),
+		enc16("c_j", "offset: i32", 0xA001, &scatter("offset", CJ)),
+		cbranch("c_beqz", 0xC001),
+		cbranch("c_bnez", 0xE001),
+
+		// C, quadrant 2
+		ci("c_slli", 0x0002),
+		clsp("c_fldsp", 0x2002, &[(5, 5, 12), (4, 3, 5), (8, 6, 2)]),
+		clsp("c_lwsp", 0x4002, &[(5, 5, 12), (4, 2, 4), (7, 6, 2)]),
+		clsp("c_ldsp", 0x6002, &[(5, 5, 12), (4, 3, 5), (8, 6, 2)]),
+		enc16("c_jr", "rs1: u8", 0x8002, &[f("reg(rs1) << 7")]),
+		enc16("c_mv", "rd: u8, rs2: u8", 0x8002, &[f("reg(rd) << 7"), f("reg(rs2) << 2")]),
+		enc16("c_ebreak", "", 0x9002, &[]),
+		enc16("c_jalr", "rs1: u8", 0x9002, &[f("reg(rs1) << 7")]),
+		enc16("c_add", "rd: u8, rs2: u8", 0x9002, &[f("reg(rd) << 7"), f("reg(rs2) << 2")]),
+		cssp("c_fsdsp", 0xA002, &[(5, 3, 10), (8, 6, 7)]),
+		cssp("c_swsp", 0xC002, &[(5, 2, 9), (7, 6, 7)]),
+		cssp("c_sdsp", 0xE002, &[(5, 3, 10), (8, 6, 7)])
+	].join("\n")
+}
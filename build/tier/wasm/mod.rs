@@ -0,0 +1,135 @@
+/// Mnemonic to function name, like `i32.trunc_f32_s` to `i32_trunc_f32_s`.
+fn name(mnemonic: &str) -> String {
+	mnemonic.replace('.', "_")
+}
+
+/// Instruction with no immediates.
+fn zo(mnemonic: &str, opcode: u8) -> String {
+	let name = name(mnemonic);
+
+	indoc::formatdoc! {"
+		#[inline]
+		pub const fn {name}() -> [u8; 1] {{
+			[0x{opcode:02X}]
+		}}
+	"}
+}
+
+/// Instruction behind the `0xFC` prefix, with a sub opcode small enough to be a single LEB128 byte.
+fn prefixed(mnemonic: &str, sub: u8) -> String {
+	let name = name(mnemonic);
+
+	indoc::formatdoc! {"
+		#[inline]
+		pub const fn {name}() -> [u8; 2] {{
+			[0xFC, 0x{sub:02X}]
+		}}
+	"}
+}
+
+/// Structured instruction taking a block type.
+fn block(name: &str, opcode: u8) -> String {
+	indoc::formatdoc! {"
+		#[inline]
+		pub const fn {name}(ty: u8) -> [u8; 2] {{
+			[0x{opcode:02X}, ty]
+		}}
+	"}
+}
+
+/// Instruction taking a single index, like a label, function or local.
+fn index(mnemonic: &str, param: &str, opcode: u8) -> String {
+	let name = name(mnemonic);
+
+	indoc::formatdoc! {"
+		#[inline]
+		pub const fn {name}({param}: u32) -> Inst {{
+			Inst::new(0x{opcode:02X}).uleb({param} as u64)
+		}}
+	"}
+}
+
+/// Load or store, with the alignment as a power of two and a constant offset added to the address.
+fn memory(mnemonic: &str, opcode: u8) -> String {
+	let name = name(mnemonic);
+
+	indoc::formatdoc! {"
+		#[inline]
+		pub const fn {name}(align: u32, offset: u32) -> Inst {{
+			Inst::new(0x{opcode:02X}).uleb(align as u64).uleb(offset as u64)
+		}}
+	"}
+}
+
+/// Consecutive opcodes starting at `start`.
+fn run(start: u8, mnemonics: &[&str], gen: fn(&str, u8) -> String) -> Vec<String> {
+	mnemonics
+		.iter()
+		.enumerate()
+		.map(|(i, m)| gen(m, start + i as u8))
+		.collect()
+}
+
+#[rustfmt::skip]
+pub fn src() -> String {
+	let mut out = vec![
+		zo("unreachable", 0x00),
+		zo("nop", 0x01),
+		block("block", 0x02),
+		block("loop_", 0x03),
+		block("if_", 0x04),
+		zo("else_", 0x05),
+		zo("end", 0x0B),
+		index("br", "label", 0x0C),
+		index("br_if", "label", 0x0D),
+		zo("return_", 0x0F),
+		index("call", "func", 0x10),
+		zo("drop", 0x1A),
+		zo("select", 0x1B),
+
+		index("local.get", "local", 0x20),
+		index("local.set", "local", 0x21),
+		index("local.tee", "local", 0x22),
+		index("global.get", "global", 0x23),
+		index("global.set", "global", 0x24)
+	];
+
+	out.extend(run(0x28, &[
+		"i32.load", "i64.load", "f32.load", "f64.load",
+		"i32.load8_s", "i32.load8_u", "i32.load16_s", "i32.load16_u",
+		"i64.load8_s", "i64.load8_u", "i64.load16_s", "i64.load16_u", "i64.load32_s", "i64.load32_u",
+		"i32.store", "i64.store", "f32.store", "f64.store",
+		"i32.store8", "i32.store16", "i64.store8", "i64.store16", "i64.store32"
+	], memory));
+
+	out.extend(run(0x45, &[
+		"i32.eqz", "i32.eq", "i32.ne", "i32.lt_s", "i32.lt_u", "i32.gt_s", "i32.gt_u", "i32.le_s", "i32.le_u", "i32.ge_s", "i32.ge_u",
+		"i64.eqz", "i64.eq", "i64.ne", "i64.lt_s", "i64.lt_u", "i64.gt_s", "i64.gt_u", "i64.le_s", "i64.le_u", "i64.ge_s", "i64.ge_u",
+		"f32.eq", "f32.ne", "f32.lt", "f32.gt", "f32.le", "f32.ge",
+		"f64.eq", "f64.ne", "f64.lt", "f64.gt", "f64.le", "f64.ge",
+
+		"i32.clz", "i32.ctz", "i32.popcnt", "i32.add", "i32.sub", "i32.mul", "i32.div_s", "i32.div_u", "i32.rem_s", "i32.rem_u",
+		"i32.and", "i32.or", "i32.xor", "i32.shl", "i32.shr_s", "i32.shr_u", "i32.rotl", "i32.rotr",
+		"i64.clz", "i64.ctz", "i64.popcnt", "i64.add", "i64.sub", "i64.mul", "i64.div_s", "i64.div_u", "i64.rem_s", "i64.rem_u",
+		"i64.and", "i64.or", "i64.xor", "i64.shl", "i64.shr_s", "i64.shr_u", "i64.rotl", "i64.rotr",
+		"f32.abs", "f32.neg", "f32.ceil", "f32.floor", "f32.trunc", "f32.nearest", "f32.sqrt",
+		"f32.add", "f32.sub", "f32.mul", "f32.div", "f32.min", "f32.max", "f32.copysign",
+		"f64.abs", "f64.neg", "f64.ceil", "f64.floor", "f64.trunc", "f64.nearest", "f64.sqrt",
+		"f64.add", "f64.sub", "f64.mul", "f64.div", "f64.min", "f64.max", "f64.copysign",
+
+		"i32.wrap_i64", "i32.trunc_f32_s", "i32.trunc_f32_u", "i32.trunc_f64_s", "i32.trunc_f64_u",
+		"i64.extend_i32_s", "i64.extend_i32_u", "i64.trunc_f32_s", "i64.trunc_f32_u", "i64.trunc_f64_s", "i64.trunc_f64_u",
+		"f32.convert_i32_s", "f32.convert_i32_u", "f32.convert_i64_s", "f32.convert_i64_u", "f32.demote_f64",
+		"f64.convert_i32_s", "f64.convert_i32_u", "f64.convert_i64_s", "f64.convert_i64_u", "f64.promote_f32",
+		"i32.reinterpret_f32", "i64.reinterpret_f64", "f32.reinterpret_i32", "f64.reinterpret_i64",
+
+		"i32.extend8_s", "i32.extend16_s", "i64.extend8_s", "i64.extend16_s", "i64.extend32_s"
+	], zo));
+
+	out.extend(run(0x00, &[
+		"i32.trunc_sat_f32_s", "i32.trunc_sat_f32_u", "i32.trunc_sat_f64_s", "i32.trunc_sat_f64_u",
+		"i64.trunc_sat_f32_s", "i64.trunc_sat_f32_u", "i64.trunc_sat_f64_s", "i64.trunc_sat_f64_u"
+	], prefixed));
+
+	out.join("\n")
+}
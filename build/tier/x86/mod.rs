@@ -12,6 +12,7 @@ pub fn src_amd64_compatible() -> String {
 		mi("xor", &[], 0x81, 6, Size::U32, Size::U32),
 		zo("nop", &[], &[0x90]),
 		zo("ret", &[], &[0xC3]),
+		i("ret", &[], &[0xC2], Size::U16),
 		zo("leave", &[], &[0xC9]),
 		i("push", &[], &[0x68], Size::U8),
 		i("push", &[], &[0x68], Size::U16),
@@ -44,6 +45,8 @@ pub fn src_amd64_compatible() -> String {
 		mi("cmp", &[], 0x81, 7, Size::U32, Size::U32),
 		d("callnrd", &[], &[0xE8], Size::U16),
 		d("callnrd", &[], &[0xE8], Size::U32),
+		d("jmpnrd", &[], &[0xEB], Size::U8),
+		d("jmpnrd", &[], &[0xE9], Size::U32),
 		m("callnai", &[], 0xFF, 2, Size::U16),
 		m("callnai", &[], 0xFF, 2, Size::U32),
 		rm("mov", &[], 0x8A, Size::U8, Size::U8),
@@ -51,7 +54,19 @@ pub fn src_amd64_compatible() -> String {
 		rm("mov", &[], 0x8B, Size::U32, Size::U32),
 		oi("mov", &[], 0xB0, Size::U8, Size::U8),
 		oi("mov", &[], 0xB8, Size::U16, Size::U16),
-		oi("mov", &[], 0xB8, Size::U32, Size::U32)
+		oi("mov", &[], 0xB8, Size::U32, Size::U32),
+		rm_mem("mov", &[], 0x8B, Size::U32),
+		mr_mem("mov", &[], 0x89, Size::U32),
+		rm_ops("imul", &[], &[0x0F, 0xAF], Size::U32, Size::U32),
+		d_cc("jccnrd", &[], &[0x70], Size::U8),
+		d_cc("jccnrd", &[], &[0x0F, 0x80], Size::U32),
+
+		zo("hlt", &[], &[0xF4]),
+		zo("cli", &[], &[0xFA]),
+		zo("sti", &[], &[0xFB]),
+		zo("rdmsr", &[], &[0x0F, 0x32]),
+		zo("wrmsr", &[], &[0x0F, 0x30]),
+		zo("wbinvd", &[], &[0x0F, 0x09])
 	].join("\n")
 }
 
@@ -59,18 +74,119 @@ pub fn src_amd64_compatible() -> String {
 pub fn src_x86_only() -> String {
 	[
 		zo("into", &[], &[0xCE]),
+		zo("iret", &[], &[0xCF]),
+		m_mem("lgdt", &[], &[0x0F, 0x01], 2),
+		m_mem("lidt", &[], &[0x0F, 0x01], 3),
+		m_mem("ltr", &[], &[0x0F, 0x00], 3),
+		m_mem("invlpg", &[], &[0x0F, 0x01], 7),
+		m_mem("xsave", &[], &[0x0F, 0xAE], 4),
+		m_mem("xrstor", &[], &[0x0F, 0xAE], 5),
 		o("push", &[], &[0x50], Size::U32),
 		o("pop", &[], &[0x58], Size::U32)
 	].join("\n")
 }
 
+const OPSIZE: &str = "OPSIZE";
+
+/// 16 bit real mode, where operands default to 16 bits and 32 bit forms need an operand size prefix.
+pub fn src_real() -> String {
+	[
+		zo("nop", &[], &[0x90]),
+		zo("ret", &[], &[0xC3]),
+		zo("leave", &[], &[0xC9]),
+		zo("retf", &[], &[0xCB]),
+		zo("iret", &[], &[0xCF]),
+		zo("hlt", &[], &[0xF4]),
+		zo("cli", &[], &[0xFA]),
+		zo("sti", &[], &[0xFB]),
+		zo("cld", &[], &[0xFC]),
+		zo("lodsb", &[], &[0xAC]),
+		zo("stosb", &[], &[0xAA]),
+		zo("int3", &[], &[0xCC]),
+		i("int", &[], &[0xCD], Size::U8),
+		i("push", &[], &[0x68], Size::U16),
+		i("push", &[OPSIZE], &[0x68], Size::U32),
+		o("push", &[], &[0x50], Size::U16),
+		o("push", &[OPSIZE], &[0x50], Size::U32),
+		o("pop", &[], &[0x58], Size::U16),
+		o("pop", &[OPSIZE], &[0x58], Size::U32),
+		m("not", &[], 0xF7, 2, Size::U16),
+		m("not", &[OPSIZE], 0xF7, 2, Size::U32),
+		m("neg", &[], 0xF7, 3, Size::U16),
+		m("neg", &[OPSIZE], 0xF7, 3, Size::U32),
+		m("mul", &[], 0xF7, 4, Size::U16),
+		m("mul", &[OPSIZE], 0xF7, 4, Size::U32),
+		m("div", &[], 0xF7, 6, Size::U16),
+		m("div", &[OPSIZE], 0xF7, 6, Size::U32),
+		rm("xor", &[], 0x30, Size::U8, Size::U8),
+		rm("xor", &[], 0x31, Size::U16, Size::U16),
+		rm("xor", &[OPSIZE], 0x31, Size::U32, Size::U32),
+		rm("add", &[], 0x03, Size::U16, Size::U16),
+		rm("add", &[OPSIZE], 0x03, Size::U32, Size::U32),
+		mi("add", &[], 0x81, 0, Size::U16, Size::U16),
+		mi("add", &[OPSIZE], 0x81, 0, Size::U32, Size::U32),
+		rm("sub", &[], 0x2B, Size::U16, Size::U16),
+		rm("sub", &[OPSIZE], 0x2B, Size::U32, Size::U32),
+		mi("sub", &[], 0x81, 5, Size::U16, Size::U16),
+		mi("sub", &[OPSIZE], 0x81, 5, Size::U32, Size::U32),
+		rm("cmp", &[], 0x3B, Size::U16, Size::U16),
+		rm("cmp", &[OPSIZE], 0x3B, Size::U32, Size::U32),
+		mi("cmp", &[], 0x80, 7, Size::U8, Size::U8),
+		mi("cmp", &[], 0x81, 7, Size::U16, Size::U16),
+		mi("cmp", &[OPSIZE], 0x81, 7, Size::U32, Size::U32),
+		d("callnrd", &[], &[0xE8], Size::U16),
+		d("jmpnrd", &[], &[0xEB], Size::U8),
+		d("jmpnrd", &[], &[0xE9], Size::U16),
+		m("callnai", &[], 0xFF, 2, Size::U16),
+		rm("mov", &[], 0x8A, Size::U8, Size::U8),
+		rm("mov", &[], 0x8B, Size::U16, Size::U16),
+		rm("mov", &[OPSIZE], 0x8B, Size::U32, Size::U32),
+		oi("mov", &[], 0xB0, Size::U8, Size::U8),
+		oi("mov", &[], 0xB8, Size::U16, Size::U16),
+		oi("mov", &[OPSIZE], 0xB8, Size::U32, Size::U32),
+
+		rm_mem16("mov", &[], 0x8A, Size::U8),
+		rm_mem16("mov", &[], 0x8B, Size::U16),
+		rm_mem16("mov", &[OPSIZE], 0x8B, Size::U32),
+		mr_mem16("mov", &[], 0x88, Size::U8),
+		mr_mem16("mov", &[], 0x89, Size::U16),
+		mr_mem16("mov", &[OPSIZE], 0x89, Size::U32),
+		rm_mem16("lea", &[], 0x8D, Size::U16),
+		rm_mem16("add", &[], 0x03, Size::U16),
+		rm_mem16("add", &[OPSIZE], 0x03, Size::U32),
+		rm_mem16("sub", &[], 0x2B, Size::U16),
+		rm_mem16("sub", &[OPSIZE], 0x2B, Size::U32),
+		rm_mem16("cmp", &[], 0x3B, Size::U16),
+		rm_mem16("cmp", &[OPSIZE], 0x3B, Size::U32),
+		m_mem16("lgdt", &[], &[0x0F, 0x01], 2),
+		m_mem16("lidt", &[], &[0x0F, 0x01], 3),
+		m_mem16("callnai", &[], &[0xFF], 2),
+		m_mem16("jmpnai", &[], &[0xFF], 4),
+
+		i("in8", &[], &[0xE4], Size::U8),
+		i("in16", &[], &[0xE5], Size::U8),
+		i("in32", &[OPSIZE], &[0xE5], Size::U8),
+		zo("in8_dx", &[], &[0xEC]),
+		zo("in16_dx", &[], &[0xED]),
+		zo("in32_dx", &[OPSIZE], &[0xED]),
+		i("out8", &[], &[0xE6], Size::U8),
+		i("out16", &[], &[0xE7], Size::U8),
+		i("out32", &[OPSIZE], &[0xE7], Size::U8),
+		zo("out8_dx", &[], &[0xEE]),
+		zo("out16_dx", &[], &[0xEF]),
+		zo("out32_dx", &[OPSIZE], &[0xEF])
+	].join("\n")
+}
+
 pub fn src() -> String {
+	let conds = conds();
 	let amd64_compatible = src_amd64_compatible();
 	let x86_only = src_x86_only();
 
 	indoc::formatdoc! {"
 		pub(crate) mod compatible {{
 			pub(crate) use super::prelude::*;
+			{conds}
 			{amd64_compatible}
 		}}
 
@@ -1,3 +1,4 @@
+#[derive(Clone, Copy)]
 pub enum Size {
 	U64,
 	U32,
@@ -43,7 +44,7 @@ pub fn oi(inst: &str, prefixes: &[&str], op: u8, rdst: Size, isrc: Size) -> Stri
 		#[inline]
 		pub const fn {inst}_r{rdst}_i{isrc}(dst: u8, src: u{isrc}) -> [u8; {total_bytes}] {{
 			let b = src.to_le_bytes();
-			[{prefixes}0x{op:02X} + dst, {unpack}]
+			[{prefixes}0x{op:02X} + (dst & 0b111), {unpack}]
 		}}
 	"}
 }
@@ -74,6 +75,20 @@ pub fn rm(inst: &str, prefixes: &[&str], op: u8, rdst: Size, rsrc: Size) -> Stri
 	"}
 }
 
+/// Like [rm], for opcodes longer than a byte such as the `0x0F` escaped ones.
+pub fn rm_ops(inst: &str, prefixes: &[&str], ops: &[u8], rdst: Size, rsrc: Size) -> String {
+	let total_bytes = prefixes.len() + ops.len() + 1;
+	let prefixes = prefixes.iter().map(|s| format!("{s}, ")).collect::<Vec<_>>().concat();
+	let ops = ops.iter().map(|op| format!("0x{op:02X}")).collect::<Vec<_>>().join(", ");
+
+	indoc::formatdoc! {"
+		#[inline]
+		pub const fn {inst}_r{rdst}_r{rsrc}(dst: u8, src: u8) -> [u8; {total_bytes}] {{
+			[{prefixes}{ops}, mod_rm(MODRM_DIRECT, dst, src)]
+		}}
+	"}
+}
+
 pub fn m(inst: &str, prefixes: &[&str], op: u8, code: u8, rdst: Size) -> String {
 	let total_bytes = prefixes.len() + 2;
 	let prefixes = prefixes.iter().map(|s| format!("{s}, ")).collect::<Vec<_>>().concat();
@@ -117,6 +132,49 @@ pub fn i(inst: &str, prefixes: &[&str], ops: &[u8], src: Size) -> String {
 // This encodes the same as an immediate. A separate function purely for distinction.
 pub use i as d;
 
+/// Displacement for a conditional instruction, with a [conds] code added to the last opcode byte.
+pub fn d_cc(inst: &str, prefixes: &[&str], ops: &[u8], src: Size) -> String {
+	let total_bytes = prefixes.len() + ops.len() + src.bytes() as usize;
+	let unpack = unpack(src.bytes());
+	let prefixes = prefixes.iter().map(|s| format!("{s}, ")).collect::<Vec<_>>().concat();
+	let (last, ops) = ops.split_last().unwrap();
+	let ops = ops.iter().map(|op| format!("0x{op:02X}, ")).collect::<Vec<_>>().concat();
+
+	indoc::formatdoc! {"
+		#[inline]
+		pub const fn {inst}_i{src}(cond: u8, src: u{src}) -> [u8; {total_bytes}] {{
+			let b = src.to_le_bytes();
+			[{prefixes}{ops}0x{last:02X} + (cond & 0xF), {unpack}]
+		}}
+	"}
+}
+
+/// Condition codes, as used by [d_cc].
+pub fn conds() -> String {
+	[
+		("O", "Overflow."),
+		("NO", "No overflow."),
+		("B", "Unsigned less than, or carry."),
+		("AE", "Unsigned greater or equal."),
+		("E", "Equal, or zero."),
+		("NE", "Not equal."),
+		("BE", "Unsigned less or equal."),
+		("A", "Unsigned greater than."),
+		("S", "Sign, or negative."),
+		("NS", "No sign."),
+		("P", "Parity even."),
+		("NP", "Parity odd."),
+		("L", "Signed less than."),
+		("GE", "Signed greater or equal."),
+		("LE", "Signed less or equal."),
+		("G", "Signed greater than.")
+	]
+		.iter()
+		.enumerate()
+		.map(|(code, (name, doc))| format!("/// {doc}\npub const CC_{name}: u8 = 0x{code:X};\n"))
+		.collect()
+}
+
 pub fn o(inst: &str, prefixes: &[&str], ops: &[u8], rdst: Size) -> String {
 	let total_bytes = prefixes.len() + ops.len();
 	let prefixes = prefixes.iter().map(|s| format!("{s}, ")).collect::<Vec<_>>().concat();
@@ -125,7 +183,93 @@ pub fn o(inst: &str, prefixes: &[&str], ops: &[u8], rdst: Size) -> String {
 	indoc::formatdoc! {"
 		#[inline]
 		pub const fn {inst}_r{rdst}(dst: u8) -> [u8; {total_bytes}] {{
-			[{prefixes}{ops} + dst]
+			[{prefixes}{ops} + (dst & 0b111)]
+		}}
+	"}
+}
+
+/// Register destination with a 16 bit memory operand, addressed by `base` + `disp`.
+pub fn rm_mem16(inst: &str, prefixes: &[&str], op: u8, size: Size) -> String {
+	let total_bytes = prefixes.len() + 4;
+	let prefixes = prefixes.iter().map(|s| format!("{s}, ")).collect::<Vec<_>>().concat();
+
+	indoc::formatdoc! {"
+		#[inline]
+		pub const fn {inst}_r{size}_m{size}(dst: u8, base: u8, disp: u16) -> [u8; {total_bytes}] {{
+			let [m, d0, d1] = mod_rm16(dst, base, disp);
+			[{prefixes}0x{op:02X}, m, d0, d1]
+		}}
+	"}
+}
+
+/// Memory destination with a 16 bit memory operand, addressed by `base` + `disp`.
+pub fn mr_mem16(inst: &str, prefixes: &[&str], op: u8, size: Size) -> String {
+	let total_bytes = prefixes.len() + 4;
+	let prefixes = prefixes.iter().map(|s| format!("{s}, ")).collect::<Vec<_>>().concat();
+
+	indoc::formatdoc! {"
+		#[inline]
+		pub const fn {inst}_m{size}_r{size}(base: u8, disp: u16, src: u8) -> [u8; {total_bytes}] {{
+			let [m, d0, d1] = mod_rm16(src, base, disp);
+			[{prefixes}0x{op:02X}, m, d0, d1]
+		}}
+	"}
+}
+
+/// Lone memory operand with an opcode extension in the reg field, addressed by `base` + `disp`.
+pub fn m_mem16(inst: &str, prefixes: &[&str], ops: &[u8], code: u8) -> String {
+	let total_bytes = prefixes.len() + ops.len() + 3;
+	let prefixes = prefixes.iter().map(|s| format!("{s}, ")).collect::<Vec<_>>().concat();
+	let ops = ops.iter().map(|op| format!("0x{op:02X}")).collect::<Vec<_>>().join(", ");
+
+	indoc::formatdoc! {"
+		#[inline]
+		pub const fn {inst}_m(base: u8, disp: u16) -> [u8; {total_bytes}] {{
+			let [m, d0, d1] = mod_rm16({code}, base, disp);
+			[{prefixes}{ops}, m, d0, d1]
+		}}
+	"}
+}
+
+/// Lone memory operand with an opcode extension in the reg field, addressed by `base` + `disp` through a SIB byte.
+pub fn m_mem(inst: &str, prefixes: &[&str], ops: &[u8], code: u8) -> String {
+	let total_bytes = prefixes.len() + ops.len() + 6;
+	let prefixes = prefixes.iter().map(|s| format!("{s}, ")).collect::<Vec<_>>().concat();
+	let ops = ops.iter().map(|op| format!("0x{op:02X}")).collect::<Vec<_>>().join(", ");
+
+	indoc::formatdoc! {"
+		#[inline]
+		pub const fn {inst}_m(base: u8, disp: i32) -> [u8; {total_bytes}] {{
+			let [m, s, d0, d1, d2, d3] = mod_rm_sib({code}, base, disp);
+			[{prefixes}{ops}, m, s, d0, d1, d2, d3]
+		}}
+	"}
+}
+
+/// Register destination with a memory operand, addressed by `base` + `disp` through a SIB byte.
+pub fn rm_mem(inst: &str, prefixes: &[&str], op: u8, size: Size) -> String {
+	let total_bytes = prefixes.len() + 7;
+	let prefixes = prefixes.iter().map(|s| format!("{s}, ")).collect::<Vec<_>>().concat();
+
+	indoc::formatdoc! {"
+		#[inline]
+		pub const fn {inst}_r{size}_m{size}(dst: u8, base: u8, disp: i32) -> [u8; {total_bytes}] {{
+			let [m, s, d0, d1, d2, d3] = mod_rm_sib(dst, base, disp);
+			[{prefixes}0x{op:02X}, m, s, d0, d1, d2, d3]
+		}}
+	"}
+}
+
+/// Memory destination, addressed by `base` + `disp` through a SIB byte.
+pub fn mr_mem(inst: &str, prefixes: &[&str], op: u8, size: Size) -> String {
+	let total_bytes = prefixes.len() + 7;
+	let prefixes = prefixes.iter().map(|s| format!("{s}, ")).collect::<Vec<_>>().concat();
+
+	indoc::formatdoc! {"
+		#[inline]
+		pub const fn {inst}_m{size}_r{size}(base: u8, disp: i32, src: u8) -> [u8; {total_bytes}] {{
+			let [m, s, d0, d1, d2, d3] = mod_rm_sib(src, base, disp);
+			[{prefixes}0x{op:02X}, m, s, d0, d1, d2, d3]
 		}}
 	"}
 }
@@ -7,7 +7,7 @@ enum Token<'a> {
 	Number(u64)
 }
 
-fn tokenize(s: &[u8]) -> Vec<Token> {
+fn tokenize(s: &[u8]) -> Vec<Token<'_>> {
 	let mut out = vec![];
 	let mut ptr = 0;
 
@@ -35,7 +35,7 @@ fn tokenize(s: &[u8]) -> Vec<Token> {
 				let start = ptr;
 
 				ptr += 1;
-				while ptr < s.len() && matches!(s[ptr], b'0'..=b'9') {
+				while ptr < s.len() && s[ptr].is_ascii_digit() {
 					ptr += 1;
 				}
 
@@ -122,7 +122,7 @@ fn parse<'a>(toks: &'a [Token]) -> Vec<Node<'a>> {
 						}
 						
 						*ind += 1;
-						return Node::Call(i, args);
+						Node::Call(i, args)
 					},
 					Some(whatever) => panic!("Expected ident, not {whatever:#?}"),
 					None => panic!("Expected name for function call")
@@ -131,17 +131,17 @@ fn parse<'a>(toks: &'a [Token]) -> Vec<Node<'a>> {
 			
 			Token::Ident(i) => {
 				*ind += 1;
-				return Node::Ident(i);
+				Node::Ident(i)
 			},
 			
 			Token::Number(n) => {
 				*ind += 1;
-				return Node::Number(*n);
+				Node::Number(*n)
 			},
 			
 			Token::String(s) => {
 				*ind += 1;
-				return Node::String(s.to_owned());
+				Node::String(s.clone())
 			}
 
 			whatever => panic!("Didn't expect {whatever:#?}")
@@ -183,7 +183,7 @@ fn assemble<'a>(nodes: &'a [Node<'a>]) -> extern "C" fn() -> u64 {
 	
 	fn get_register<'a>(r: Option<&Node<'a>>) -> u8 {
 		match r {
-			Some(Node::Ident(i)) => ident_to_register(*i),
+			Some(Node::Ident(i)) => ident_to_register(i),
 			whatever => panic!("Expected ident, got {whatever:#?}")
 		}
 	}
@@ -202,13 +202,13 @@ fn assemble<'a>(nodes: &'a [Node<'a>]) -> extern "C" fn() -> u64 {
 
 	fn assemble_exp<'a>(node: &'a Node<'a>, out: &mut Vec<u8>) -> Value {
 		match node {
-			Node::Ident(i) => Value::Register(ident_to_register(*i)),
+			Node::Ident(i) => Value::Register(ident_to_register(i)),
 			Node::Number(n) => Value::Imm(*n),
 			Node::String(s) => Value::SizedAddress(s.as_ptr() as _, s.len()),
 			Node::Call(name, args) => {
 				match *name {
 					b"set" => {
-						let register = get_register(args.get(0));
+						let register = get_register(args.first());
 						let value = get_value(args.get(1), out);
 
 						match value {
@@ -226,7 +226,7 @@ fn assemble<'a>(nodes: &'a [Node<'a>]) -> extern "C" fn() -> u64 {
 					},
 
 					b"add" => {
-						let lhs = get_value(args.get(0), out);
+						let lhs = get_value(args.first(), out);
 						let rhs = get_value(args.get(1), out);
 
 						match lhs {
@@ -259,7 +259,7 @@ fn assemble<'a>(nodes: &'a [Node<'a>]) -> extern "C" fn() -> u64 {
 					},
 
 					b"ret" => {
-						let val = get_value(args.get(0), out);
+						let val = get_value(args.first(), out);
 
 						match val {
 							Value::Imm(i) => out.extend(dasm::tier::raw::amd64::mov_r64_i64(0, i)),
@@ -274,7 +274,7 @@ fn assemble<'a>(nodes: &'a [Node<'a>]) -> extern "C" fn() -> u64 {
 					},
 					
 					b"print" => {
-						let val = get_value(args.get(0), out);
+						let val = get_value(args.first(), out);
 						match val {
 							Value::SizedAddress(addr, len) => {
 								out.extend(dasm::tier::raw::amd64::mov_r64_i64(6, addr as _));
@@ -303,9 +303,8 @@ fn assemble<'a>(nodes: &'a [Node<'a>]) -> extern "C" fn() -> u64 {
 	}
 
 	while ind < nodes.len() {
-		match assemble_exp(&nodes[ind], &mut out) {
-			Value::Stack => out.extend(dasm::tier::raw::amd64::pop_r64(0)),
-			_ => ()
+		if let Value::Stack = assemble_exp(&nodes[ind], &mut out) {
+			out.extend(dasm::tier::raw::amd64::pop_r64(0));
 		}
 
 		ind += 1;
@@ -316,11 +315,7 @@ fn assemble<'a>(nodes: &'a [Node<'a>]) -> extern "C" fn() -> u64 {
 	let mapped = dasm::mmap::Mmap::exec(&out)
 		.expect("Failed to mmap");
 
-	let f = unsafe { std::mem::transmute(mapped.as_ptr()) };
-
-	std::mem::forget(mapped);
-
-	f
+	unsafe { mapped.leak() }
 }
 
 fn main() {
@@ -0,0 +1,357 @@
+use super::Abi;
+
+#[cfg(feature = "std")]
+use super::Arch;
+#[cfg(feature = "std")]
+use crate::tier::raw::{aarch64, amd64, x86};
+
+/// An argument to [Call].
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Arg {
+	/// An integer or pointer held in a general purpose register.
+	Int(u8),
+	/// An integer or pointer constant.
+	Imm(u64),
+	/// A double whose bits are held in a general purpose register.
+	Float(u8),
+	/// A double constant.
+	FloatImm(f64)
+}
+
+#[cfg(feature = "std")]
+impl Arg {
+	fn is_float(&self) -> bool {
+		matches!(self, Arg::Float(_) | Arg::FloatImm(_))
+	}
+}
+
+/// Where to put the result of a [Call].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Ret {
+	/// An integer result, moved into this register.
+	Int(u8),
+	/// A double result, whose bits are moved into this general purpose register.
+	Float(u8)
+}
+
+/// A call to a function following some [Abi], from code whose stack pointer is aligned to [Abi::stack_align], as after a [super::Frame] prologue.
+///
+/// Arguments are read before any of them is moved into place, so they can come from any register, including ones the call itself uses.
+/// Floats are doubles, and can't come from or go to registers on x86, which only has 32 bit ones.
+/// Apple's variadic convention on arm64, which passes every variadic argument on the stack, isn't covered.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Call<'a> {
+	pub abi: Abi,
+	/// Address of the function.
+	pub target: u64,
+	pub args: &'a [Arg],
+	/// Registers whose values have to survive the call, like [Abi::caller_saved] to keep all of them.
+	pub preserve: &'a [u8],
+	pub ret: Option<Ret>,
+	/// Whether the function takes variable arguments, like `printf`.
+	pub variadic: bool
+}
+
+/// Where an argument goes.
+#[cfg(feature = "std")]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Place {
+	Reg(u8),
+	Vector(u8),
+	/// The vector register, copied into the general one too, as Win64 wants for variadic floats.
+	Both(u8, u8),
+	/// Offset from the stack pointer at the call.
+	Stack(u32)
+}
+
+impl<'a> Call<'a> {
+	pub fn new(abi: Abi, target: u64) -> Self {
+		Self {
+			abi,
+			target,
+			args: &[],
+			preserve: &[],
+			ret: None,
+			variadic: false
+		}
+	}
+
+	pub fn args(mut self, args: &'a [Arg]) -> Self {
+		self.args = args;
+		self
+	}
+
+	pub fn preserve(mut self, regs: &'a [u8]) -> Self {
+		self.preserve = regs;
+		self
+	}
+
+	pub fn ret(mut self, ret: Ret) -> Self {
+		self.ret = Some(ret);
+		self
+	}
+
+	pub fn variadic(mut self) -> Self {
+		self.variadic = true;
+		self
+	}
+}
+
+#[cfg(feature = "std")]
+impl Call<'_> {
+	/// Emits the call, leaving the stack pointer and every preserved register as they were.
+	///
+	/// # Panics
+	/// On aarch64, if stack arguments and saved registers take up 32KiB or more, which `ldr` and `str` can't reach.
+	pub fn emit(&self) -> Vec<u8> {
+		let abi = &self.abi;
+		let word = abi.word();
+
+		if abi.arch == Arch::X86 {
+			let float_reg = self.args.iter().any(|a| matches!(a, Arg::Float(_)));
+			assert!(!float_reg && !matches!(self.ret, Some(Ret::Float(_))), "x86 has no 64 bit registers to hold doubles");
+		}
+
+		let mut places = vec![Place::Stack(0); self.args.len()];
+		let (stack, vectors) = self.places(&mut places);
+
+		// Above the shadow space and stack arguments, one slot per argument read from a register, reused for the result,
+		// then the preserved registers.
+		let staging = abi.shadow_space + stack;
+		let sources = self.args.iter().filter(|a| matches!(a, Arg::Int(_) | Arg::Float(_))).count().max(1) as u32;
+		let spill = staging + sources * 8;
+		let total = (spill + self.preserve.len() as u32 * word).next_multiple_of(abi.stack_align);
+
+		let mut e = Emitter { arch: abi.arch, out: vec![] };
+		e.adjust_sp(total, false);
+
+		for (i, &r) in self.preserve.iter().enumerate() {
+			e.store(spill + i as u32 * word, r);
+		}
+
+		let mut slot = staging;
+		let mut slots = vec![0; self.args.len()];
+		for (i, arg) in self.args.iter().enumerate() {
+			if let Arg::Int(r) | Arg::Float(r) = *arg {
+				e.store(slot, r);
+				slots[i] = slot;
+				slot += 8;
+			}
+		}
+
+		let scratch = e.scratch();
+		for (i, (arg, place)) in self.args.iter().zip(&places).enumerate() {
+			match *place {
+				Place::Reg(r) => e.value(r, arg, slots[i]),
+				Place::Vector(x) => {
+					e.value(scratch, arg, slots[i]);
+					e.move_to_vector(x, scratch);
+				}
+				Place::Both(x, r) => {
+					e.value(r, arg, slots[i]);
+					e.move_to_vector(x, r);
+				}
+				Place::Stack(at) => match (abi.arch, *arg) {
+					(Arch::X86, Arg::FloatImm(f)) => {
+						let bits = f.to_bits();
+						e.value(scratch, &Arg::Imm(bits & 0xFFFF_FFFF), 0);
+						e.store(at, scratch);
+						e.value(scratch, &Arg::Imm(bits >> 32), 0);
+						e.store(at + 4, scratch);
+					}
+					_ => {
+						e.value(scratch, arg, slots[i]);
+						e.store(at, scratch);
+					}
+				}
+			}
+		}
+
+		// System V wants an upper bound on the vector registers used in `al`.
+		if self.variadic && abi.arch == Arch::Amd64 && !abi.positional {
+			e.out.extend(amd64::mov_r32_i32(amd64::RAX, vectors as u32));
+		}
+
+		e.call(self.target);
+
+		// The callee popped its stack arguments, put the space back so the offsets above hold.
+		if abi.callee_pops && stack > 0 {
+			e.out.extend(x86::sub_r32_i32(x86::ESP, stack));
+		}
+
+		if let Some(ret) = self.ret {
+			if let Ret::Float(_) = ret {
+				e.move_from_vector(abi.ret, 0);
+			}
+			e.store(staging, abi.ret);
+		}
+
+		for (i, &r) in self.preserve.iter().enumerate() {
+			e.load(r, spill + i as u32 * word);
+		}
+
+		if let Some(Ret::Int(r) | Ret::Float(r)) = self.ret {
+			e.load(r, staging);
+		}
+
+		e.adjust_sp(total, true);
+		e.out
+	}
+
+	/// Size of an argument on the stack.
+	fn stack_size(&self, arg: &Arg) -> u32 {
+		if arg.is_float() {
+			8
+		} else {
+			self.abi.word()
+		}
+	}
+
+	/// Places every argument, returning the bytes of stack they take and how many vector registers they use.
+	fn places(&self, out: &mut [Place]) -> (u32, u8) {
+		let abi = &self.abi;
+		let (mut ints, mut floats) = (0, 0);
+		let mut stack = 0;
+
+		for (i, arg) in self.args.iter().enumerate() {
+			let float = arg.is_float();
+
+			let place = if abi.positional {
+				match (float, abi.args.get(i), abi.float_args.get(i)) {
+					(true, Some(&r), Some(&x)) if self.variadic => Some(Place::Both(x, r)),
+					(true, _, Some(&x)) => Some(Place::Vector(x)),
+					(false, Some(&r), _) => Some(Place::Reg(r)),
+					_ => None
+				}
+			} else if float {
+				abi.float_args.get(floats).map(|&x| {
+					floats += 1;
+					Place::Vector(x)
+				})
+			} else {
+				abi.args.get(ints).map(|&r| {
+					ints += 1;
+					Place::Reg(r)
+				})
+			};
+
+			out[i] = place.unwrap_or_else(|| {
+				let at = stack;
+				stack += self.stack_size(arg);
+				Place::Stack(abi.shadow_space + at)
+			});
+		}
+
+		let vectors = out.iter().filter(|p| matches!(p, Place::Vector(_) | Place::Both(..))).count();
+		(stack, vectors as u8)
+	}
+}
+
+/// The first intra procedure call register on aarch64, free to clobber between calls.
+#[cfg(feature = "std")]
+const IP0: u8 = 16;
+
+#[cfg(feature = "std")]
+struct Emitter {
+	arch: Arch,
+	out: Vec<u8>
+}
+
+#[cfg(feature = "std")]
+impl Emitter {
+	/// Register free to clobber while arguments are moved, as no convention passes anything in it.
+	fn scratch(&self) -> u8 {
+		match self.arch {
+			Arch::X86 => x86::EAX,
+			Arch::Amd64 => amd64::R11,
+			Arch::Aarch64 => IP0
+		}
+	}
+
+	fn adjust_sp(&mut self, size: u32, release: bool) {
+		if size == 0 {
+			return;
+		}
+
+		match (self.arch, release) {
+			(Arch::X86, false) => self.out.extend(x86::sub_r32_i32(x86::ESP, size)),
+			(Arch::X86, true) => self.out.extend(x86::add_r32_i32(x86::ESP, size)),
+			(Arch::Amd64, false) => self.out.extend(amd64::sub_r64_i32(amd64::RSP, size)),
+			(Arch::Amd64, true) => self.out.extend(amd64::add_r64_i32(amd64::RSP, size)),
+			(Arch::Aarch64, false) => super::adjust_sp(&mut self.out, size, aarch64::sub_r64_r64_i12),
+			(Arch::Aarch64, true) => super::adjust_sp(&mut self.out, size, aarch64::add_r64_r64_i12)
+		}
+	}
+
+	/// Checks a stack offset fits the scaled immediate of `ldr` and `str` on aarch64.
+	/// Every slot is 8 bytes there, so only the size of the whole area can get in the way.
+	fn scaled(at: u32) -> u16 {
+		assert!(at.is_multiple_of(8) && at < 8 * 4096, "Stack offset {at} is out of reach of ldr and str");
+		at as u16
+	}
+
+	fn store(&mut self, at: u32, r: u8) {
+		match self.arch {
+			Arch::X86 => self.out.extend(x86::mov_m32_r32(x86::ESP, at as i32, r)),
+			Arch::Amd64 => self.out.extend(amd64::mov_m64_r64(amd64::RSP, at as i32, r)),
+			Arch::Aarch64 => self.out.extend(aarch64::str_r64_m(r, aarch64::SP, Self::scaled(at)))
+		}
+	}
+
+	fn load(&mut self, r: u8, at: u32) {
+		match self.arch {
+			Arch::X86 => self.out.extend(x86::mov_r32_m32(r, x86::ESP, at as i32)),
+			Arch::Amd64 => self.out.extend(amd64::mov_r64_m64(r, amd64::RSP, at as i32)),
+			Arch::Aarch64 => self.out.extend(aarch64::ldr_r64_m(r, aarch64::SP, Self::scaled(at)))
+		}
+	}
+
+	/// Moves an argument into `dst`, reading register arguments from their staging slot.
+	fn value(&mut self, dst: u8, arg: &Arg, slot: u32) {
+		let imm = match *arg {
+			Arg::Int(_) | Arg::Float(_) => return self.load(dst, slot),
+			Arg::Imm(v) => v,
+			Arg::FloatImm(f) => f.to_bits()
+		};
+
+		match self.arch {
+			Arch::X86 => self.out.extend(x86::mov_r32_i32(dst, imm as u32)),
+			Arch::Amd64 => self.out.extend(amd64::mov_r64_i64(dst, imm)),
+			Arch::Aarch64 => self.out.extend(aarch64::mov_r64_i64(dst, imm))
+		}
+	}
+
+	fn move_to_vector(&mut self, dst: u8, src: u8) {
+		match self.arch {
+			Arch::X86 => unreachable!(),
+			Arch::Amd64 => self.out.extend(amd64::movq_x_r64(dst, src)),
+			Arch::Aarch64 => self.out.extend(aarch64::fmov_d_r64(dst, src))
+		}
+	}
+
+	fn move_from_vector(&mut self, dst: u8, src: u8) {
+		match self.arch {
+			Arch::X86 => unreachable!(),
+			Arch::Amd64 => self.out.extend(amd64::movq_r64_x(dst, src)),
+			Arch::Aarch64 => self.out.extend(aarch64::fmov_r64_d(dst, src))
+		}
+	}
+
+	fn call(&mut self, target: u64) {
+		let scratch = self.scratch();
+		match self.arch {
+			Arch::X86 => {
+				self.out.extend(x86::mov_r32_i32(scratch, target as u32));
+				self.out.extend(x86::callnai_r32(scratch));
+			}
+			Arch::Amd64 => {
+				self.out.extend(amd64::mov_r64_i64(scratch, target));
+				self.out.extend(amd64::callnai_r64(scratch));
+			}
+			Arch::Aarch64 => {
+				self.out.extend(aarch64::mov_r64_i64(scratch, target));
+				self.out.extend(aarch64::blr_r64(scratch));
+			}
+		}
+	}
+}
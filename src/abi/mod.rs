@@ -0,0 +1,344 @@
+//! Calling conventions, describing where arguments go and what a function has to preserve.
+//!
+//! Floats are doubles held in general purpose registers, as the raw tiers have no vector registers of their own.
+//! Registers are numbered as in the raw tier for their architecture.
+
+mod call;
+pub use call::{Arg, Call, Ret};
+
+#[cfg(feature = "std")]
+mod thunk;
+#[cfg(feature = "std")]
+pub use thunk::{abi_adapter, closure_thunk};
+
+use crate::tier::raw::amd64::{R10, R11, R12, R13, R14, R15, R8, R9, RAX, RBP, RBX, RCX, RDI, RDX, RSI};
+use crate::tier::raw::x86::{EAX, EBP, EBX, ECX, EDI, EDX, ESI};
+
+#[cfg(feature = "std")]
+use crate::tier::raw::{aarch64, amd64, x86};
+
+/// Architectures a calling convention can target.
+///
+/// Separate from `object::Arch`, which only lists what the object writers support, and is gated on `std` while this module isn't.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Arch {
+	X86,
+	Amd64,
+	Aarch64
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Abi {
+	pub arch: Arch,
+	/// Registers holding the first integer arguments, in order. The rest go on the stack.
+	pub args: &'static [u8],
+	/// Vector registers holding the first float arguments, like `xmm0` or `d0`.
+	pub float_args: &'static [u8],
+	/// Whether integer and float arguments share positions, so the third argument uses the third register of its kind.
+	pub positional: bool,
+	/// Register holding an integer result.
+	pub ret: u8,
+	/// Registers a function has to restore before returning, besides the stack pointer.
+	pub callee_saved: &'static [u8],
+	/// Registers a call may clobber.
+	pub caller_saved: &'static [u8],
+	/// Bytes the caller reserves above the return address, for the callee to spill register arguments into.
+	pub shadow_space: u32,
+	/// Bytes below the stack pointer that a function can use without reserving them.
+	pub red_zone: u32,
+	/// Alignment of the stack pointer at a call.
+	pub stack_align: u32,
+	/// Whether the callee pops stack arguments when returning.
+	pub callee_pops: bool
+}
+
+impl Abi {
+	/// Size of a register, and of each stack slot.
+	pub const fn word(&self) -> u32 {
+		match self.arch {
+			Arch::X86 => 4,
+			Arch::Amd64 | Arch::Aarch64 => 8
+		}
+	}
+}
+
+/// System V, used by `extern "C"` on amd64 everywhere but Windows.
+pub const SYSV_AMD64: Abi = Abi {
+	arch: Arch::Amd64,
+	args: &[RDI, RSI, RDX, RCX, R8, R9],
+	float_args: &[0, 1, 2, 3, 4, 5, 6, 7],
+	positional: false,
+	ret: RAX,
+	callee_saved: &[RBX, RBP, R12, R13, R14, R15],
+	caller_saved: &[RAX, RCX, RDX, RSI, RDI, R8, R9, R10, R11],
+	shadow_space: 0,
+	red_zone: 128,
+	stack_align: 16,
+	callee_pops: false
+};
+
+/// Microsoft x64, used on Windows. `xmm6` through `xmm15` are callee saved too.
+pub const WIN64: Abi = Abi {
+	arch: Arch::Amd64,
+	args: &[RCX, RDX, R8, R9],
+	float_args: &[0, 1, 2, 3],
+	positional: true,
+	ret: RAX,
+	callee_saved: &[RBX, RBP, RDI, RSI, R12, R13, R14, R15],
+	caller_saved: &[RAX, RCX, RDX, R8, R9, R10, R11],
+	shadow_space: 32,
+	red_zone: 0,
+	stack_align: 16,
+	callee_pops: false
+};
+
+/// cdecl as in the i386 System V ABI, with every argument on the stack and popped by the caller.
+pub const CDECL: Abi = Abi {
+	arch: Arch::X86,
+	args: &[],
+	float_args: &[],
+	positional: false,
+	ret: EAX,
+	callee_saved: &[EBX, ESI, EDI, EBP],
+	caller_saved: &[EAX, ECX, EDX],
+	shadow_space: 0,
+	red_zone: 0,
+	stack_align: 16,
+	callee_pops: false
+};
+
+/// Win32 stdcall, like cdecl but popped by the callee.
+pub const STDCALL: Abi = Abi {
+	arch: Arch::X86,
+	args: &[],
+	float_args: &[],
+	positional: false,
+	ret: EAX,
+	callee_saved: &[EBX, ESI, EDI, EBP],
+	caller_saved: &[EAX, ECX, EDX],
+	shadow_space: 0,
+	red_zone: 0,
+	stack_align: 4,
+	callee_pops: true
+};
+
+/// Microsoft fastcall, passing the first two arguments in `ecx` and `edx`.
+pub const FASTCALL: Abi = Abi {
+	arch: Arch::X86,
+	args: &[ECX, EDX],
+	float_args: &[],
+	positional: false,
+	ret: EAX,
+	callee_saved: &[EBX, ESI, EDI, EBP],
+	caller_saved: &[EAX, ECX, EDX],
+	shadow_space: 0,
+	red_zone: 0,
+	stack_align: 4,
+	callee_pops: true
+};
+
+/// The Arm procedure call standard. Apple allows a 128 byte red zone on top of this, which isn't relied on.
+pub const AAPCS64: Abi = Abi {
+	arch: Arch::Aarch64,
+	args: &[0, 1, 2, 3, 4, 5, 6, 7],
+	float_args: &[0, 1, 2, 3, 4, 5, 6, 7],
+	positional: false,
+	ret: 0,
+	callee_saved: &[19, 20, 21, 22, 23, 24, 25, 26, 27, 28],
+	caller_saved: &[0, 1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12, 13, 14, 15, 16, 17],
+	shadow_space: 0,
+	red_zone: 0,
+	stack_align: 16,
+	callee_pops: false
+};
+
+/// The convention of `extern "C"` on the target being built for.
+#[cfg(all(target_arch = "x86_64", windows))]
+pub const HOST: Abi = WIN64;
+
+#[cfg(all(target_arch = "x86_64", not(windows)))]
+pub const HOST: Abi = SYSV_AMD64;
+
+#[cfg(target_arch = "x86")]
+pub const HOST: Abi = CDECL;
+
+#[cfg(target_arch = "aarch64")]
+pub const HOST: Abi = AAPCS64;
+
+/// A stack frame, saving registers and reserving space for locals while keeping the stack aligned for calls.
+///
+/// Locals start [Frame::locals_offset] bytes above the stack pointer, past any shadow space reserved for calls.
+/// On aarch64 the frame also saves the frame pointer and link register.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Frame<'a> {
+	pub abi: Abi,
+	/// Registers to save, usually some of [Abi::callee_saved].
+	pub saved: &'a [u8],
+	/// Bytes of locals.
+	pub locals: u32,
+	/// Bytes of arguments passed on the stack, which the epilogue pops if the callee has to.
+	pub stack_args: u32
+}
+
+impl<'a> Frame<'a> {
+	pub fn new(abi: Abi) -> Self {
+		Self {
+			abi,
+			saved: &[],
+			locals: 0,
+			stack_args: 0
+		}
+	}
+
+	pub fn save(mut self, regs: &'a [u8]) -> Self {
+		self.saved = regs;
+		self
+	}
+
+	pub fn locals(mut self, size: u32) -> Self {
+		self.locals = size;
+		self
+	}
+
+	pub fn stack_args(mut self, size: u32) -> Self {
+		self.stack_args = size;
+		self
+	}
+
+	pub fn locals_offset(&self) -> u32 {
+		self.abi.shadow_space
+	}
+
+	/// Bytes subtracted from the stack pointer after the registers are saved.
+	pub fn reserved(&self) -> u32 {
+		match self.abi.arch {
+			// Registers are saved in 16 byte pairs, so only the locals need rounding.
+			Arch::Aarch64 => self.locals.next_multiple_of(16),
+			Arch::X86 | Arch::Amd64 => {
+				// The return address and saved registers are already on the stack.
+				let pushed = self.abi.word() * (1 + self.saved.len() as u32);
+				let size = self.abi.shadow_space + self.locals;
+				(pushed + size).next_multiple_of(self.abi.stack_align) - pushed
+			}
+		}
+	}
+}
+
+#[cfg(feature = "std")]
+impl Frame<'_> {
+	pub fn prologue(&self) -> Vec<u8> {
+		let mut out = vec![];
+		let reserved = self.reserved();
+
+		match self.abi.arch {
+			Arch::X86 => {
+				for &r in self.saved {
+					out.extend(x86::push_r32(r));
+				}
+
+				if reserved > 0 {
+					out.extend(x86::sub_r32_i32(x86::ESP, reserved));
+				}
+			}
+			Arch::Amd64 => {
+				for &r in self.saved {
+					out.extend(amd64::push_r64(r));
+				}
+
+				if reserved > 0 {
+					out.extend(amd64::sub_r64_i32(amd64::RSP, reserved));
+				}
+			}
+			Arch::Aarch64 => {
+				out.extend(aarch64::stp_r64_pre(FP, aarch64::LR, aarch64::SP, -16));
+				out.extend(aarch64::add_r64_r64_i12(FP, aarch64::SP, 0));
+
+				for pair in self.saved.chunks(2) {
+					match *pair {
+						[a, b] => out.extend(aarch64::stp_r64_pre(a, b, aarch64::SP, -16)),
+						[a] => out.extend(aarch64::str_r64_pre(a, aarch64::SP, -16)),
+						_ => unreachable!()
+					}
+				}
+
+				adjust_sp(&mut out, reserved, aarch64::sub_r64_r64_i12);
+			}
+		}
+
+		out
+	}
+
+	/// Undoes the prologue and returns.
+	///
+	/// # Panics
+	/// If the callee pops more than [u16::MAX] bytes of stack arguments, which `ret` can't encode.
+	pub fn epilogue(&self) -> Vec<u8> {
+		let mut out = vec![];
+		let reserved = self.reserved();
+		let pops = (self.abi.callee_pops && self.stack_args > 0)
+			.then(|| u16::try_from(self.stack_args).expect("Too many stack arguments to pop"));
+
+		match self.abi.arch {
+			Arch::X86 => {
+				if reserved > 0 {
+					out.extend(x86::add_r32_i32(x86::ESP, reserved));
+				}
+
+				for &r in self.saved.iter().rev() {
+					out.extend(x86::pop_r32(r));
+				}
+
+				if let Some(size) = pops {
+					out.extend(x86::ret_i16(size));
+				} else {
+					out.extend(x86::ret());
+				}
+			}
+			Arch::Amd64 => {
+				if reserved > 0 {
+					out.extend(amd64::add_r64_i32(amd64::RSP, reserved));
+				}
+
+				for &r in self.saved.iter().rev() {
+					out.extend(amd64::pop_r64(r));
+				}
+
+				if let Some(size) = pops {
+					out.extend(amd64::ret_i16(size));
+				} else {
+					out.extend(amd64::ret());
+				}
+			}
+			Arch::Aarch64 => {
+				adjust_sp(&mut out, reserved, aarch64::add_r64_r64_i12);
+
+				for pair in self.saved.chunks(2).rev() {
+					match *pair {
+						[a, b] => out.extend(aarch64::ldp_r64_post(a, b, aarch64::SP, 16)),
+						[a] => out.extend(aarch64::ldr_r64_post(a, aarch64::SP, 16)),
+						_ => unreachable!()
+					}
+				}
+
+				out.extend(aarch64::ldp_r64_post(FP, aarch64::LR, aarch64::SP, 16));
+				out.extend(aarch64::ret());
+			}
+		}
+
+		out
+	}
+}
+
+/// Frame pointer on aarch64.
+#[cfg(feature = "std")]
+const FP: u8 = 29;
+
+/// Moves `sp` by `size` in steps an immediate can hold, keeping it 16 byte aligned throughout.
+#[cfg(feature = "std")]
+fn adjust_sp(out: &mut Vec<u8>, mut size: u32, op: fn(u8, u8, u16) -> [u8; 4]) {
+	while size > 0 {
+		let step = size.min(4080);
+		out.extend(op(aarch64::SP, aarch64::SP, step as u16));
+		size -= step;
+	}
+}
@@ -0,0 +1,55 @@
+use super::{Abi, Arch, Arg, Call, Frame, Ret, WIN64};
+use crate::tier::raw::amd64::*;
+
+/// Code that calls `target` with `ctx` as its first argument, followed by the arguments it was called with.
+///
+/// Arguments move up by one register, so `target` can take one less register argument than `abi` has, and none on the stack.
+/// Under [WIN64] floats move up a position too.
+pub fn closure_thunk(abi: Abi, ctx: u64, target: u64) -> Vec<u8> {
+	assert_eq!(abi.arch, Arch::Amd64, "Thunks are only generated for amd64");
+
+	let mut out = vec![];
+	for i in (1..abi.args.len()).rev() {
+		out.extend(mov_r64_r64(abi.args[i], abi.args[i - 1]));
+		if abi.positional {
+			out.extend(movaps_x_x(abi.float_args[i], abi.float_args[i - 1]));
+		}
+	}
+
+	out.extend(mov_r64_i64(abi.args[0], ctx));
+	out.extend(mov_r64_i64(R11, target));
+	out.extend(jmpnai_r64(R11));
+	out
+}
+
+/// Code following `from` that calls `target` following `to`, passing along `args` integer arguments and the integer result.
+///
+/// Registers `from` expects preserved but `to` can clobber are saved, including `xmm6` through `xmm15` when coming from [WIN64].
+///
+/// # Panics
+/// If `from` passes fewer than `args` arguments in registers.
+pub fn abi_adapter(from: Abi, to: Abi, target: u64, args: usize) -> Vec<u8> {
+	assert!(from.arch == Arch::Amd64 && to.arch == Arch::Amd64, "Thunks are only generated for amd64");
+	assert!(args <= from.args.len(), "Arguments on the stack can't be adapted");
+
+	let saved: Vec<u8> = from.callee_saved.iter().copied().filter(|r| to.caller_saved.contains(r)).collect();
+	let vectors = if from == WIN64 && to != WIN64 { 6..16 } else { 0..0 };
+
+	let frame = Frame::new(from).save(&saved).locals(16 * vectors.len() as u32);
+	let at = |i: usize| (frame.locals_offset() + 16 * i as u32) as i32;
+
+	let mut out = frame.prologue();
+	for (i, x) in vectors.clone().enumerate() {
+		out.extend(movdqu_m128_x(RSP, at(i), x));
+	}
+
+	let args: Vec<Arg> = from.args[..args].iter().map(|&r| Arg::Int(r)).collect();
+	out.extend(Call::new(to, target).args(&args).ret(Ret::Int(from.ret)).emit());
+
+	for (i, x) in vectors.enumerate() {
+		out.extend(movdqu_x_m128(x, RSP, at(i)));
+	}
+
+	out.extend(frame.epilogue());
+	out
+}
@@ -1,6 +1,10 @@
 #![doc = include_str!("../README.md")]
 
+pub mod abi;
 pub mod tier;
 
 #[cfg(feature = "mmap")]
 pub mod mmap;
+
+#[cfg(feature = "std")]
+pub mod object;
@@ -0,0 +1,197 @@
+use super::linux::{close, errno, ftruncate, map, memfd_create, munmap, MmapOptions, PROT_EXEC, PROT_READ, PROT_WRITE};
+
+/// The same memory mapped twice, once as read + write and once as read + execute.
+///
+/// Code is written through the writable view and run through the executable one,
+/// so no page is ever writable and executable at once, and no `mprotect` call is needed to patch.
+#[derive(Debug)]
+pub struct DualMmap {
+	rw: *mut u8,
+	rx: *const u8,
+	len: usize
+}
+
+impl Drop for DualMmap {
+	fn drop(&mut self) {
+		unsafe {
+			munmap(self.rw as _, self.len);
+			munmap(self.rx as _, self.len);
+		}
+	}
+}
+
+impl DualMmap {
+	/// Creates a zeroed memfd of `len` bytes and maps both views of it.
+	pub fn new(len: usize) -> super::MmapResult<Self> {
+		Self::new_with(len, &MmapOptions::new())
+	}
+
+	/// Like [DualMmap::new], but placing the executable view according to `options`.
+	/// Explicit huge pages aren't supported, failing with [Errno::EINVAL](super::Errno::EINVAL).
+	pub fn new_with(len: usize, options: &MmapOptions) -> super::MmapResult<Self> {
+		if len == 0 {
+			return Err(super::MmapError::ZeroLength);
+		}
+
+		if options.huge_tlb {
+			return Err(super::MmapError::Map(super::Errno::EINVAL));
+		}
+
+		unsafe {
+			let fd = memfd_create(c"dasm".as_ptr(), 0x1 /* MFD_CLOEXEC */);
+			if fd == -1 {
+				return Err(super::MmapError::Memfd(errno()));
+			}
+
+			if ftruncate(fd, len as i64) != 0 {
+				let e = errno();
+				close(fd);
+				return Err(super::MmapError::Memfd(e));
+			}
+
+			let rw = match map(len, PROT_READ | PROT_WRITE, Some(fd), &MmapOptions::new()) {
+				Ok((rw, _)) => rw,
+				Err(e) => {
+					close(fd);
+					return Err(e);
+				}
+			};
+
+			let rx = match map(len, PROT_READ | PROT_EXEC, Some(fd), options) {
+				Ok((rx, _)) => rx as *const u8,
+				Err(e) => {
+					munmap(rw as _, len);
+					close(fd);
+					return Err(e);
+				}
+			};
+
+			// Both mappings keep the memory alive on their own.
+			close(fd);
+
+			Ok(Self {
+				rw,
+				rx,
+				len
+			})
+		}
+	}
+
+	/// Unmaps both views, reporting the first failure that dropping would ignore.
+	/// Both are unmapped even if the first fails.
+	pub fn unmap(self) -> super::MmapResult<()> {
+		let unmap = |ptr: *const u8| {
+			if unsafe { munmap(ptr as _, self.len) } == 0 {
+				Ok(())
+			} else {
+				Err(super::MmapError::Unmap(errno()))
+			}
+		};
+
+		let result = [unmap(self.rw), unmap(self.rx)].into_iter().collect();
+
+		core::mem::forget(self);
+		result
+	}
+
+	/// Maps both views of a memfd holding a copy of `mem`.
+	pub fn copy(mem: impl AsRef<[u8]>) -> super::MmapResult<Self> {
+		let mem = mem.as_ref();
+
+		let mut map = Self::new(mem.len())?;
+		map.as_mut().copy_from_slice(mem);
+
+		Ok(map)
+	}
+
+	/// Start of the executable view.
+	pub fn as_ptr(&self) -> *const u8 {
+		self.rx
+	}
+
+	/// Casts the start of the executable view to a function, which can't outlive the mapping.
+	///
+	/// # Safety
+	/// The mapped code must match the signature and calling convention of `F`.
+	pub unsafe fn as_fn<F: super::FnPtr>(&self) -> super::Func<'_, F> {
+		unsafe { super::Func::new(self.rx) }
+	}
+
+	/// Casts the start of the executable view to a function, and never unmaps either view.
+	///
+	/// # Safety
+	/// The mapped code must match the signature and calling convention of `F`.
+	pub unsafe fn leak<F: super::FnPtr>(self) -> F {
+		let f = unsafe { F::from_ptr(self.rx) };
+		core::mem::forget(self);
+		f
+	}
+
+	/// Start of the writable view.
+	pub fn as_mut_ptr(&mut self) -> *mut u8 {
+		self.rw
+	}
+
+	pub fn len(&self) -> usize {
+		self.len
+	}
+
+	pub fn is_empty(&self) -> bool {
+		self.len == 0
+	}
+
+	/// Atomically replaces the 8 bytes of code at `offset`, which must be 8 byte aligned.
+	///
+	/// Slices from [AsRef::as_ref] must not be held across a patch, as it changes the bytes under them.
+	///
+	/// Fails with [MmapError::OutOfRange](super::MmapError::OutOfRange) if the patch would go out of bounds of the mapping.
+	pub fn patch(&self, offset: usize, code: [u8; 8]) -> super::MmapResult<()> {
+		super::patch::check_bounds(offset, self.len)?;
+
+		unsafe { super::patch_u64(self.rw.add(offset), code)? };
+		super::flush_icache(self.rx.wrapping_add(offset), 8);
+
+		Ok(())
+	}
+
+	/// Atomically writes a `jmp rel32` to `target` at `offset`, which must not straddle an 8 byte boundary.
+	/// The jump is relative to the executable view.
+	///
+	/// Slices from [AsRef::as_ref] must not be held across a patch, as it changes the bytes under them.
+	///
+	/// Fails with [MmapError::OutOfRange](super::MmapError::OutOfRange) if the 8 byte word containing the jump would go out of bounds of the mapping.
+	pub fn patch_jmp(&self, offset: usize, target: *const u8) -> super::MmapResult<()> {
+		let start = offset - offset % 8;
+		super::patch::check_bounds(start, self.len)?;
+
+		// Encoded relative to where the jump executes, then stored through the writable view.
+		let code = self.as_ref()[start..start + 8].try_into().unwrap();
+		self.patch(start, super::patch::splice_jmp(code, self.rx.wrapping_add(offset), target)?)
+	}
+
+	/// Translates a pointer into the writable view to the same byte in the executable view.
+	pub fn to_exec(&self, ptr: *const u8) -> Option<*const u8> {
+		let offset = (ptr as usize).checked_sub(self.rw as usize)?;
+		(offset < self.len).then(|| self.rx.wrapping_add(offset))
+	}
+
+	/// Translates a pointer into the executable view to the same byte in the writable view.
+	pub fn to_write(&self, ptr: *const u8) -> Option<*mut u8> {
+		let offset = (ptr as usize).checked_sub(self.rx as usize)?;
+		(offset < self.len).then(|| self.rw.wrapping_add(offset))
+	}
+}
+
+/// Reads through the executable view.
+impl AsRef<[u8]> for DualMmap {
+	fn as_ref(&self) -> &[u8] {
+		unsafe { core::slice::from_raw_parts(self.rx, self.len) }
+	}
+}
+
+/// Writes through the writable view.
+impl AsMut<[u8]> for DualMmap {
+	fn as_mut(&mut self) -> &mut [u8] {
+		unsafe { core::slice::from_raw_parts_mut(self.rw, self.len) }
+	}
+}
@@ -0,0 +1,76 @@
+/// A function pointer type that code in a mapping can be cast to.
+///
+/// # Safety
+/// Must only be implemented for function pointers.
+pub unsafe trait FnPtr: Copy {
+	/// # Safety
+	/// `ptr` must point to code matching the signature and calling convention of `Self`.
+	unsafe fn from_ptr(ptr: *const u8) -> Self;
+}
+
+macro_rules! impl_fn_ptr {
+	($($arg:ident: $ty:ident),*) => {
+		impl_fn_ptr!(@abi "C"; $($arg: $ty),*);
+		impl_fn_ptr!(@abi "system"; $($arg: $ty),*);
+	};
+
+	(@abi $abi:literal; $($arg:ident: $ty:ident),*) => {
+		unsafe impl<R, $($ty),*> FnPtr for extern $abi fn($($ty),*) -> R {
+			unsafe fn from_ptr(ptr: *const u8) -> Self {
+				unsafe { core::mem::transmute_copy(&ptr) }
+			}
+		}
+
+		unsafe impl<R, $($ty),*> FnPtr for unsafe extern $abi fn($($ty),*) -> R {
+			unsafe fn from_ptr(ptr: *const u8) -> Self {
+				unsafe { core::mem::transmute_copy(&ptr) }
+			}
+		}
+
+		impl<R, $($ty),*> Func<'_, extern $abi fn($($ty),*) -> R> {
+			#[allow(clippy::too_many_arguments)]
+			pub fn call(&self, $($arg: $ty),*) -> R {
+				(self.f)($($arg),*)
+			}
+		}
+
+		impl<R, $($ty),*> Func<'_, unsafe extern $abi fn($($ty),*) -> R> {
+			/// # Safety
+			/// Whatever the function itself requires.
+			#[allow(clippy::too_many_arguments)]
+			pub unsafe fn call(&self, $($arg: $ty),*) -> R {
+				unsafe { (self.f)($($arg),*) }
+			}
+		}
+	};
+}
+
+impl_fn_ptr!();
+impl_fn_ptr!(a: A);
+impl_fn_ptr!(a: A, b: B);
+impl_fn_ptr!(a: A, b: B, c: C);
+impl_fn_ptr!(a: A, b: B, c: C, d: D);
+impl_fn_ptr!(a: A, b: B, c: C, d: D, e: E);
+impl_fn_ptr!(a: A, b: B, c: C, d: D, e: E, f: F);
+impl_fn_ptr!(a: A, b: B, c: C, d: D, e: E, f: F, g: G);
+impl_fn_ptr!(a: A, b: B, c: C, d: D, e: E, f: F, g: G, h: H);
+
+/// A function living inside of a mapping, which can't outlive it.
+///
+/// Called through [Func::call], so the bare function pointer never escapes the mapping's lifetime.
+#[derive(Debug, Clone, Copy)]
+pub struct Func<'a, F: FnPtr> {
+	f: F,
+	map: core::marker::PhantomData<&'a ()>
+}
+
+impl<'a, F: FnPtr> Func<'a, F> {
+	/// # Safety
+	/// `ptr` must point to code matching `F`, which stays mapped for `'a`.
+	pub unsafe fn new(ptr: *const u8) -> Self {
+		Self {
+			f: unsafe { F::from_ptr(ptr) },
+			map: core::marker::PhantomData
+		}
+	}
+}
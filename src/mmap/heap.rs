@@ -0,0 +1,225 @@
+use super::linux::{page_size, MmapOptions};
+use super::DualMmap;
+
+#[derive(Debug)]
+struct Region {
+	map: DualMmap,
+	/// Sorted, non-adjacent (offset, len) ranges that are free.
+	free: Vec<(usize, usize)>
+}
+
+impl Region {
+	fn new(len: usize, options: &MmapOptions) -> super::MmapResult<Self> {
+		let map = DualMmap::new_with(len, options)?;
+
+		Ok(Self {
+			free: vec![(0, map.len())],
+			map
+		})
+	}
+
+	fn contains(&self, ptr: *const u8) -> bool {
+		let start = self.map.as_ptr() as usize;
+		(start..start + self.map.len()).contains(&(ptr as usize))
+	}
+
+	fn alloc(&mut self, len: usize) -> Option<usize> {
+		let i = self.free.iter().position(|&(_, free)| free >= len)?;
+		let (offset, free) = self.free[i];
+
+		if free == len {
+			self.free.remove(i);
+		} else {
+			self.free[i] = (offset + len, free - len);
+		}
+
+		Some(offset)
+	}
+
+	fn free(&mut self, offset: usize, len: usize) {
+		let i = self.free.partition_point(|&(o, _)| o < offset);
+		self.free.insert(i, (offset, len));
+
+		// Merge with the next range, then the previous one.
+		if i + 1 < self.free.len() && offset + len == self.free[i + 1].0 {
+			self.free[i].1 += self.free.remove(i + 1).1;
+		}
+
+		if i > 0 && self.free[i - 1].0 + self.free[i - 1].1 == offset {
+			self.free[i - 1].1 += self.free.remove(i).1;
+		}
+	}
+
+	fn free_bytes(&self) -> usize {
+		self.free.iter().map(|&(_, len)| len).sum()
+	}
+
+	/// Copies `mem` in through the writable view, returning where it runs from in the executable one.
+	fn write(&mut self, offset: usize, mem: &[u8]) -> *const u8 {
+		unsafe { core::ptr::copy_nonoverlapping(mem.as_ptr(), self.map.as_mut_ptr().add(offset), mem.len()) };
+
+		let ptr = self.map.as_ptr().wrapping_add(offset);
+		super::flush_icache(ptr, mem.len());
+		ptr
+	}
+}
+
+/// A function allocated inside of a [CodeHeap].
+///
+/// Only valid for as long as the heap it came from, and until it's [freed](CodeHeap::free).
+#[derive(Debug)]
+pub struct CodeSlot {
+	ptr: *const u8,
+	len: usize
+}
+
+impl CodeSlot {
+	pub fn as_ptr(&self) -> *const u8 {
+		self.ptr
+	}
+
+	/// Length of the code, not including alignment padding.
+	pub fn len(&self) -> usize {
+		self.len
+	}
+
+	pub fn is_empty(&self) -> bool {
+		self.len == 0
+	}
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CodeHeapStats {
+	/// Number of regions mapped.
+	pub regions: usize,
+	/// Number of live allocations.
+	pub allocations: usize,
+	/// Total bytes mapped across all regions.
+	pub reserved: usize,
+	/// Bytes handed out, including alignment padding.
+	pub used: usize,
+	/// Bytes available for future allocations.
+	pub free: usize
+}
+
+/// Executable memory shared between many functions.
+///
+/// Reserves large regions up front and sub-allocates aligned slots out of them,
+/// so each function costs bytes instead of a mapping of its own.
+///
+/// Each region is a [DualMmap], so no page is ever writable and executable at once.
+/// Code is copied in through the writable view and runs from the executable one, without a syscall per function.
+#[derive(Debug)]
+pub struct CodeHeap {
+	regions: Vec<Region>,
+	region_size: usize,
+	align: usize,
+	allocations: usize,
+	options: MmapOptions
+}
+
+impl Default for CodeHeap {
+	fn default() -> Self {
+		Self::new()
+	}
+}
+
+impl CodeHeap {
+	/// Creates a heap reserving 1 MiB at a time, with 16 byte aligned slots.
+	/// Nothing is mapped until the first allocation.
+	pub fn new() -> Self {
+		Self {
+			regions: vec![],
+			region_size: 1024 * 1024,
+			align: 16,
+			allocations: 0,
+			options: MmapOptions::new()
+		}
+	}
+
+	/// Size of each region reserved from the kernel. Rounded up to the page size.
+	pub fn with_region_size(mut self, size: usize) -> Self {
+		self.region_size = size;
+		self
+	}
+
+	/// Alignment of every slot. Must be a power of two, no larger than [page_size](super::page_size) as that's all regions are aligned to.
+	pub fn with_align(mut self, align: usize) -> Self {
+		assert!(align.is_power_of_two(), "Alignment must be a power of two");
+		assert!(align <= page_size(), "Alignment can't be larger than a page");
+		self.align = align;
+		self
+	}
+
+	/// Where and how the executable view of each region gets mapped.
+	/// For example, [MmapOptions::near] keeps every function within `call rel32` reach of some code.
+	///
+	/// # Panics
+	/// If `options` asks for [MmapOptions::huge_tlb], which [DualMmap] can't map.
+	pub fn with_options(mut self, options: MmapOptions) -> Self {
+		assert!(!options.huge_tlb, "Code heaps can't use explicit huge pages");
+		self.options = options;
+		self
+	}
+
+	/// Copies `mem` into a free slot, reserving another region if none fit.
+	pub fn alloc(&mut self, mem: impl AsRef<[u8]>) -> super::MmapResult<CodeSlot> {
+		let mem = mem.as_ref();
+		if mem.is_empty() {
+			return Err(super::MmapError::ZeroLength);
+		}
+
+		let len = mem.len().next_multiple_of(self.align);
+
+		let found = self.regions.iter_mut()
+			.enumerate()
+			.find_map(|(i, r)| r.alloc(len).map(|offset| (i, offset)));
+
+		let (i, offset) = match found {
+			Some(found) => found,
+			None => {
+				let mut region = Region::new(self.region_size.max(len).next_multiple_of(page_size()), &self.options)?;
+				let offset = region.alloc(len).unwrap();
+
+				self.regions.push(region);
+				(self.regions.len() - 1, offset)
+			}
+		};
+
+		let ptr = self.regions[i].write(offset, mem);
+		self.allocations += 1;
+
+		Ok(CodeSlot {
+			ptr,
+			len: mem.len()
+		})
+	}
+
+	/// Returns a slot's memory to the heap.
+	///
+	/// # Panics
+	/// If the slot wasn't allocated from this heap.
+	pub fn free(&mut self, slot: CodeSlot) {
+		let region = self.regions.iter_mut()
+			.find(|r| r.contains(slot.ptr))
+			.expect("Slot does not belong to this heap");
+
+		let offset = slot.ptr as usize - region.map.as_ptr() as usize;
+		region.free(offset, slot.len.next_multiple_of(self.align));
+
+		self.allocations -= 1;
+	}
+
+	pub fn stats(&self) -> CodeHeapStats {
+		let reserved = self.regions.iter().map(|r| r.map.len()).sum();
+		let free = self.regions.iter().map(Region::free_bytes).sum();
+
+		CodeHeapStats {
+			regions: self.regions.len(),
+			allocations: self.allocations,
+			reserved,
+			used: reserved - free,
+			free
+		}
+	}
+}
@@ -0,0 +1,243 @@
+use super::linux::{errno, mprotect, page_size, Mmap, MmapOptions, PROT_EXEC, PROT_READ, PROT_WRITE};
+use crate::tier::raw::amd64::{jmpnai_rip, jmpnrd_i32};
+use crate::tier::raw::x86::decode::{decode, Insn, Mode, Rel};
+
+/// Bytes of the `jmp rel32` written over the start of a hooked function.
+const PATCH: usize = 5;
+
+/// Offset of the relocated instructions in a trampoline, after the jump to the detour.
+const RELOCATED: usize = 8;
+
+/// Rewrites `insns`, which ran at `from`, to run at `to`.
+fn relocate(code: &[u8], insns: &[Insn], from: u64, to: u64) -> super::MmapResult<Vec<u8>> {
+	// Branching back under the patch would land in the middle of the jump.
+	let stolen = from..from + code.len() as u64;
+	let into_patch = |target: i64| stolen.contains(&(target as u64)) && target as u64 != from;
+
+	let mut out = vec![];
+	let mut at = 0;
+
+	for insn in insns {
+		let bytes = &code[at..at + insn.len];
+		let end = (from + (at + insn.len) as u64) as i64;
+
+		match insn.rel {
+			None => out.extend(bytes),
+			Some(Rel::Disp(o) | Rel::Branch(o)) => {
+				let disp = i32::from_le_bytes(bytes[o..o + 4].try_into().unwrap()) as i64;
+
+				// Only branches, as `[rip + disp]` can point anywhere and still mean the same address.
+				if matches!(insn.rel, Some(Rel::Branch(_))) && into_patch(end + disp) {
+					return Err(super::MmapError::Relocate);
+				}
+
+				let new_end = (to + (out.len() + insn.len) as u64) as i64;
+				let disp = i32::try_from(disp + end - new_end).map_err(|_| super::MmapError::OutOfRange)?;
+
+				out.extend(&bytes[..o]);
+				out.extend(disp.to_le_bytes());
+				out.extend(&bytes[o + 4..]);
+			}
+			Some(Rel::Short(op)) => {
+				let target = end + bytes[insn.len - 1] as i8 as i64;
+
+				if into_patch(target) {
+					return Err(super::MmapError::Relocate);
+				}
+
+				let wide: &[u8] = match op {
+					0xEB => &[0xE9],
+					0x70..=0x7F => &[0x0F, 0x80 | (op & 0xF)],
+					// `loop` and `jrcxz` have no 32 bit form.
+					_ => return Err(super::MmapError::Relocate)
+				};
+
+				let new_end = (to + (out.len() + wide.len() + 4) as u64) as i64;
+				let disp = i32::try_from(target - new_end).map_err(|_| super::MmapError::OutOfRange)?;
+
+				out.extend(wide);
+				out.extend(disp.to_le_bytes());
+			}
+		}
+
+		at += insn.len;
+	}
+
+	Ok(out)
+}
+
+/// Protection of each page, looked up in `/proc/self/maps` as there's no call asking the kernel directly.
+fn protections(pages: &[usize]) -> super::MmapResult<Vec<core::ffi::c_int>> {
+	let maps = std::fs::read_to_string("/proc/self/maps")
+		.map_err(|e| super::MmapError::Protect(super::Errno(e.raw_os_error().unwrap_or(0))))?;
+
+	let find = |page: usize| {
+		maps.lines().find_map(|line| {
+			let (range, rest) = line.split_once(' ')?;
+			let (start, end) = range.split_once('-')?;
+			let (start, end) = (usize::from_str_radix(start, 16).ok()?, usize::from_str_radix(end, 16).ok()?);
+
+			(start..end).contains(&page).then(|| {
+				let perm = |i: usize, flag: u8, prot| if rest.as_bytes().get(i) == Some(&flag) { prot } else { 0 };
+				perm(0, b'r', PROT_READ) | perm(1, b'w', PROT_WRITE) | perm(2, b'x', PROT_EXEC)
+			})
+		})
+	};
+
+	// Like `mprotect` on memory that isn't mapped.
+	pages.iter().map(|&page| find(page).ok_or(super::MmapError::Protect(super::Errno::ENOMEM))).collect()
+}
+
+/// Writes `code` over the memory at `ptr`, then puts back the protection each page had.
+unsafe fn write(ptr: *mut u8, code: &[u8]) -> super::MmapResult<()> {
+	let page = page_size();
+	let start = ptr as usize - ptr as usize % page;
+	let pages: Vec<usize> = (start..ptr as usize + code.len()).step_by(page).collect();
+	let prots = protections(&pages)?;
+
+	let protect = |page: usize, prot: core::ffi::c_int| {
+		if unsafe { mprotect(page as _, page_size(), prot) } != 0 {
+			return Err(super::MmapError::Protect(errno()));
+		}
+
+		Ok(())
+	};
+
+	// Puts back the first `count` pages, trying every one and reporting the first failure.
+	let restore = |count: usize| {
+		let mut result = Ok(());
+		for (&page, &prot) in pages[..count].iter().zip(&prots) {
+			result = result.and(protect(page, prot));
+		}
+
+		result
+	};
+
+	for (i, (&page, &prot)) in pages.iter().zip(&prots).enumerate() {
+		if let Err(e) = protect(page, prot | PROT_WRITE) {
+			let _ = restore(i);
+			return Err(e);
+		}
+	}
+
+	unsafe { core::ptr::copy_nonoverlapping(code.as_ptr(), ptr, code.len()) };
+	super::flush_icache(ptr, code.len());
+
+	restore(pages.len())
+}
+
+/// A function detoured to another, by a jump written over its first instructions.
+///
+/// Those instructions are moved into a trampoline mapped nearby, which [Hook::original] points to, to call the function as it was.
+/// Dropping the hook puts the function back, and unmaps the trampoline.
+#[derive(Debug)]
+pub struct Hook<'a> {
+	target: *mut u8,
+	/// The bytes the jump replaced, empty once they're put back.
+	stolen: Vec<u8>,
+	trampoline: Mmap<'a>
+}
+
+impl Hook<'_> {
+	/// Makes calls to `target` go to `detour` instead.
+	///
+	/// Fails with [MmapError::Relocate](super::MmapError::Relocate) if the instructions under the jump can't be moved,
+	/// for example when the function is shorter than the jump.
+	///
+	/// # Safety
+	/// `target` must be the start of a function, and `detour` must share its signature.
+	/// No thread may be running the first instructions of `target` while it's hooked or unhooked.
+	pub unsafe fn new(target: *const u8, detour: *const u8) -> super::MmapResult<Self> {
+		// Read a byte at a time until each instruction decodes, as a short function can end right before unmapped memory.
+		let mut stolen = vec![];
+		let mut insns = vec![];
+		while stolen.len() < PATCH {
+			let start = stolen.len();
+			let insn = loop {
+				if let Some(insn) = decode(&stolen[start..], Mode::Long) {
+					break insn;
+				}
+
+				if stolen.len() - start == 15 {
+					return Err(super::MmapError::Relocate);
+				}
+
+				stolen.push(unsafe { target.add(stolen.len()).read() });
+			};
+
+			if insn.ends && stolen.len() < PATCH {
+				return Err(super::MmapError::Relocate);
+			}
+
+			insns.push(insn);
+		}
+
+		let len = stolen.len();
+		let code = &stolen[..];
+
+		// Only widening short branches changes the size, which is the same wherever the code goes.
+		let size = relocate(code, &insns, target as u64, target as u64)?.len();
+		let slots = (RELOCATED + size + 6).next_multiple_of(8);
+
+		let mut trampoline = Mmap::exec_with(vec![0xCC; slots + 16], &MmapOptions::new().near(target))?;
+		let base = trampoline.as_ptr() as u64;
+
+		let mut tramp = vec![];
+		tramp.extend(jmpnai_rip((slots - 6) as i32));
+		tramp.resize(RELOCATED, 0xCC);
+		tramp.extend(relocate(code, &insns, target as u64, base + RELOCATED as u64)?);
+		tramp.extend(jmpnai_rip((slots + 8 - tramp.len() - 6) as i32));
+		tramp.resize(slots, 0xCC);
+		tramp.extend((detour as u64).to_le_bytes());
+		tramp.extend((target as u64 + len as u64).to_le_bytes());
+		trampoline.as_mut().copy_from_slice(&tramp);
+
+		let rel = i32::try_from(base as i64 - (target as i64 + PATCH as i64)).map_err(|_| super::MmapError::OutOfRange)?;
+		let mut patch = vec![0xCC; len];
+		patch[..PATCH].copy_from_slice(&jmpnrd_i32(rel as u32));
+		unsafe { write(target as *mut u8, &patch)? };
+
+		Ok(Self {
+			target: target as *mut u8,
+			stolen,
+			trampoline
+		})
+	}
+
+	pub fn target(&self) -> *const u8 {
+		self.target
+	}
+
+	/// Runs the function as it was before being hooked.
+	pub fn original(&self) -> *const u8 {
+		self.trampoline.as_ptr().wrapping_add(RELOCATED)
+	}
+
+	/// Casts [Hook::original] to a function, which can't outlive the hook.
+	///
+	/// # Safety
+	/// The hooked function must match the signature of `F`.
+	pub unsafe fn original_fn<F: super::FnPtr>(&self) -> super::Func<'_, F> {
+		unsafe { super::Func::new(self.original()) }
+	}
+
+	/// Puts the function back, reporting any failure that dropping would ignore.
+	pub fn unhook(mut self) -> super::MmapResult<()> {
+		self.restore()
+	}
+
+	fn restore(&mut self) -> super::MmapResult<()> {
+		if !self.stolen.is_empty() {
+			unsafe { write(self.target, &self.stolen)? };
+			self.stolen.clear();
+		}
+
+		Ok(())
+	}
+}
+
+impl Drop for Hook<'_> {
+	fn drop(&mut self) {
+		let _ = self.restore();
+	}
+}
@@ -1,5 +1,5 @@
 extern "system" {
-	fn mmap(
+	pub(super) fn mmap(
 		addr: *const core::ffi::c_void,
 		len: usize,
 		prot: core::ffi::c_int,
@@ -8,71 +8,315 @@ extern "system" {
 		off: i64
 	) -> *mut core::ffi::c_void;
 
-	fn munmap(
+	pub(super) fn munmap(
 		addr: *const core::ffi::c_void,
 		len: usize
 	) -> core::ffi::c_int;
+
+	pub(super) fn mprotect(
+		addr: *const core::ffi::c_void,
+		len: usize,
+		prot: core::ffi::c_int
+	) -> core::ffi::c_int;
+
+	fn sysconf(name: core::ffi::c_int) -> core::ffi::c_long;
+
+	fn __errno_location() -> *mut core::ffi::c_int;
+
+	pub(super) fn memfd_create(
+		name: *const core::ffi::c_char,
+		flags: core::ffi::c_uint
+	) -> core::ffi::c_int;
+
+	pub(super) fn ftruncate(
+		fd: core::ffi::c_int,
+		len: i64
+	) -> core::ffi::c_int;
+
+	pub(super) fn close(fd: core::ffi::c_int) -> core::ffi::c_int;
+
+	fn madvise(
+		addr: *const core::ffi::c_void,
+		len: usize,
+		advice: core::ffi::c_int
+	) -> core::ffi::c_int;
+}
+
+pub(super) const PROT_NONE: core::ffi::c_int = 0x0;
+pub(super) const PROT_READ: core::ffi::c_int = 0x1;
+pub(super) const PROT_WRITE: core::ffi::c_int = 0x2;
+pub(super) const PROT_EXEC: core::ffi::c_int = 0x4;
+
+pub(super) const MAP_SHARED: core::ffi::c_int = 0x01;
+pub(super) const MAP_PRIVATE: core::ffi::c_int = 0x02;
+pub(super) const MAP_ANONYMOUS: core::ffi::c_int = 0x20;
+const MAP_32BIT: core::ffi::c_int = 0x40;
+const MAP_HUGETLB: core::ffi::c_int = 0x40000;
+const MAP_FIXED_NOREPLACE: core::ffi::c_int = 0x100000;
+
+/// Default huge page size on x86_64 and aarch64.
+const HUGE_PAGE_SIZE: usize = 2 * 1024 * 1024;
+
+/// The error number set by the last failing call on this thread.
+pub(super) fn errno() -> super::Errno {
+	super::Errno(unsafe { *__errno_location() })
+}
+
+/// Size of a single page of memory, as reported by the kernel.
+pub fn page_size() -> usize {
+	unsafe { sysconf(30 /* _SC_PAGESIZE */) as usize }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Placement {
+	Anywhere,
+	Hint(usize),
+	Fixed(usize),
+	Near(usize)
+}
+
+/// Options for where and how memory gets mapped.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct MmapOptions {
+	placement: Placement,
+	pub(super) huge_tlb: bool,
+	transparent_huge_pages: bool,
+	low_32bit: bool
+}
+
+impl Default for MmapOptions {
+	fn default() -> Self {
+		Self::new()
+	}
+}
+
+impl MmapOptions {
+	pub const fn new() -> Self {
+		Self {
+			placement: Placement::Anywhere,
+			huge_tlb: false,
+			transparent_huge_pages: false,
+			low_32bit: false
+		}
+	}
+
+	/// Prefer mapping at `addr`, but let the kernel pick somewhere else if it's taken.
+	pub fn hint(mut self, addr: *const u8) -> Self {
+		self.placement = Placement::Hint(addr as usize);
+		self
+	}
+
+	/// Map exactly at `addr`, failing with [Errno::EEXIST](super::Errno::EEXIST) if anything is already there.
+	/// Uses `MAP_FIXED_NOREPLACE`, so existing mappings are never clobbered.
+	pub fn fixed(mut self, addr: *const u8) -> Self {
+		self.placement = Placement::Fixed(addr as usize);
+		self
+	}
+
+	/// Map somewhere entirely within ±2GiB of `addr`, so code can reach it with `call rel32` / `jmp rel32`.
+	pub fn near(mut self, addr: *const u8) -> Self {
+		self.placement = Placement::Near(addr as usize);
+		self
+	}
+
+	/// Back the mapping with explicit huge pages (`MAP_HUGETLB`).
+	/// Requires huge pages to be reserved, and rounds the length up to 2MiB.
+	pub const fn huge_tlb(mut self) -> Self {
+		self.huge_tlb = true;
+		self
+	}
+
+	/// Advise the kernel to back the mapping with transparent huge pages (`MADV_HUGEPAGE`).
+	/// Being advice, this is silently ignored if unsupported.
+	pub const fn transparent_huge_pages(mut self) -> Self {
+		self.transparent_huge_pages = true;
+		self
+	}
+
+	/// Map within the first 2GiB of the address space (`MAP_32BIT`). Only honored on x86_64.
+	pub const fn low_32bit(mut self) -> Self {
+		self.low_32bit = true;
+		self
+	}
+}
+
+/// Maps private anonymous memory following `options`.
+/// Returns the mapping along with its actual length, which may be rounded up for huge pages.
+pub(super) fn map_anonymous(len: usize, prot: core::ffi::c_int, options: &MmapOptions) -> super::MmapResult<(*mut u8, usize)> {
+	map(len, prot, None, options)
+}
+
+/// Maps `fd` shared, or private anonymous memory without one, following `options`.
+pub(super) fn map(len: usize, prot: core::ffi::c_int, fd: Option<core::ffi::c_int>, options: &MmapOptions) -> super::MmapResult<(*mut u8, usize)> {
+	if len == 0 {
+		return Err(super::MmapError::ZeroLength);
+	}
+
+	let mut flags = if fd.is_some() { MAP_SHARED } else { MAP_PRIVATE | MAP_ANONYMOUS };
+	let len = if options.huge_tlb {
+		flags |= MAP_HUGETLB;
+		len.next_multiple_of(HUGE_PAGE_SIZE)
+	} else {
+		len
+	};
+
+	if options.low_32bit {
+		flags |= MAP_32BIT;
+	}
+
+	let try_map = |addr: usize, flags: core::ffi::c_int| {
+		let ptr = unsafe { mmap(addr as _, len, prot, flags, fd.unwrap_or(-1), 0) as *mut u8 };
+		if (ptr as isize) == -1 {
+			return Err(super::MmapError::Map(errno()));
+		}
+
+		// Kernels older than 4.17 treat MAP_FIXED_NOREPLACE as a hint, so make sure it was honored.
+		if flags & MAP_FIXED_NOREPLACE != 0 && ptr as usize != addr {
+			unsafe { munmap(ptr as _, len) };
+			return Err(super::MmapError::Map(super::Errno::EEXIST));
+		}
+
+		Ok(ptr)
+	};
+
+	let ptr = match options.placement {
+		Placement::Anywhere => try_map(0, flags)?,
+		Placement::Hint(addr) => try_map(addr, flags)?,
+		Placement::Fixed(addr) => try_map(addr, flags | MAP_FIXED_NOREPLACE)?,
+		Placement::Near(target) => {
+			const REACH: usize = i32::MAX as usize;
+			const STEP: usize = 32 * 1024 * 1024;
+
+			if len >= REACH {
+				return Err(super::MmapError::Map(super::Errno::ENOMEM));
+			}
+
+			// Walk outwards from the target, trying both sides at each step.
+			let base = target - target % STEP;
+			(1..REACH / STEP)
+				.flat_map(|i| [base.checked_sub(i * STEP), base.checked_add(i * STEP)])
+				.flatten()
+				.filter(|&addr| addr.abs_diff(target) < REACH && (addr + len).abs_diff(target) < REACH)
+				.find_map(|addr| try_map(addr, flags | MAP_FIXED_NOREPLACE).ok())
+				.ok_or(super::MmapError::Map(super::Errno::ENOMEM))?
+		}
+	};
+
+	if options.transparent_huge_pages {
+		unsafe { madvise(ptr as _, len, 14 /* MADV_HUGEPAGE */) };
+	}
+
+	Ok((ptr, len))
 }
 
 #[derive(Debug)]
 pub struct Mmap<'a> {
-	// NOTE: This can only be &mut right now because only ::exec is exposed.
-	// If other permissions are allowed, need a runtime solution.
-	slice: &'a mut [u8]
+	/// Held as a raw pointer rather than a slice, as patching writes through it from `&self`.
+	pub(super) ptr: *mut u8,
+	/// Length of the code copied in.
+	size: usize,
+	/// Length of the whole mapping, which can be longer than the code when using huge pages.
+	len: usize,
+	map: core::marker::PhantomData<&'a mut [u8]>
 }
 
+// Owns the mapping outright, like a `Box<[u8]>`. Not `Sync`, as patching through `&self` would race with reads through [AsRef::as_ref].
+unsafe impl Send for Mmap<'_> {}
+
 impl<'a> Drop for Mmap<'a> {
 	fn drop(&mut self) {
-		unsafe { munmap(self.slice.as_ptr() as _, self.slice.len()) };
+		unsafe { munmap(self.ptr as _, self.len) };
 	}
 }
 
 impl<'a> Mmap<'a> {
-	fn new(mem: impl AsRef<[u8]>, prot: core::ffi::c_int) -> super::MmapResult<Self> {
+	fn new(mem: impl AsRef<[u8]>, prot: core::ffi::c_int, options: &MmapOptions) -> super::MmapResult<Self> {
 		let mem = mem.as_ref();
 
-		let slice = unsafe {
-			let map = mmap(
-				core::ptr::null(),
-				mem.len(),
-				prot,
-				0x02 /* MAP_PRIVATE */ | 0x20 /* MAP_ANONYMOUS */,
-				-1,
-				0
-			) as *mut u8;
-
-			if (map as isize) == -1 {
-				return Err(super::MmapError::Failed);
-			}
-
-			let slice = core::slice::from_raw_parts_mut(map, mem.len());
-			slice.copy_from_slice(&mem);
-
-			slice
-		};
+		let (ptr, len) = map_anonymous(mem.len(), prot, options)?;
+		unsafe { core::ptr::copy_nonoverlapping(mem.as_ptr(), ptr, mem.len()) };
 
 		Ok(Self {
-			slice,
+			ptr,
+			size: mem.len(),
+			len,
+			map: core::marker::PhantomData
 		})
 	}
 
 	pub fn exec(mem: impl AsRef<[u8]>) -> super::MmapResult<Self> {
-		Self::new(mem, 0x2 /* PROT_WRITE */ | 0x4 /* PROT_EXEC */ | 0x1 /* PROT_READ */)
+		Self::new(mem, PROT_WRITE | PROT_EXEC | PROT_READ, &MmapOptions::new())
+	}
+
+	/// Like [Mmap::exec], but placing the memory according to `options`.
+	pub fn exec_with(mem: impl AsRef<[u8]>, options: &MmapOptions) -> super::MmapResult<Self> {
+		Self::new(mem, PROT_WRITE | PROT_EXEC | PROT_READ, options)
 	}
 
 	pub fn as_ptr(&self) -> *const u8 {
-		self.slice.as_ptr()
+		self.ptr
+	}
+
+	/// Atomically replaces the 8 bytes of code at `offset`, which must be 8 byte aligned.
+	///
+	/// Slices from [AsRef::as_ref] must not be held across a patch, as it changes the bytes under them.
+	///
+	/// Fails with [MmapError::OutOfRange](super::MmapError::OutOfRange) if the patch would go out of bounds of the mapping.
+	pub fn patch(&self, offset: usize, code: [u8; 8]) -> super::MmapResult<()> {
+		super::patch::check_bounds(offset, self.size)?;
+		unsafe { super::patch_u64(self.ptr.add(offset), code) }
+	}
+
+	/// Atomically writes a `jmp rel32` to `target` at `offset`, which must not straddle an 8 byte boundary.
+	///
+	/// Fails with [MmapError::OutOfRange](super::MmapError::OutOfRange) if the 8 byte word containing the jump would go out of bounds of the mapping.
+	pub fn patch_jmp(&self, offset: usize, target: *const u8) -> super::MmapResult<()> {
+		let start = offset - offset % 8;
+		super::patch::check_bounds(start, self.size)?;
+
+		let code = unsafe { core::sync::atomic::AtomicU64::from_ptr(self.ptr.add(start) as *mut u64) }
+			.load(core::sync::atomic::Ordering::Acquire)
+			.to_ne_bytes();
+
+		self.patch(start, super::patch::splice_jmp(code, self.ptr.wrapping_add(offset), target)?)
+	}
+
+	/// Unmaps the memory, reporting any failure that dropping would ignore.
+	pub fn unmap(self) -> super::MmapResult<()> {
+		let result = unsafe { munmap(self.ptr as _, self.len) };
+		let result = if result == 0 { Ok(()) } else { Err(super::MmapError::Unmap(errno())) };
+
+		core::mem::forget(self);
+		result
+	}
+
+	/// Casts the start of the mapping to a function, which can't outlive the mapping.
+	///
+	/// # Safety
+	/// The mapped code must match the signature and calling convention of `F`.
+	pub unsafe fn as_fn<F: super::FnPtr>(&self) -> super::Func<'_, F> {
+		unsafe { super::Func::new(self.as_ptr()) }
+	}
+
+	/// Casts the start of the mapping to a function, and never unmaps it.
+	///
+	/// # Safety
+	/// The mapped code must match the signature and calling convention of `F`.
+	pub unsafe fn leak<F: super::FnPtr>(self) -> F {
+		let f = unsafe { F::from_ptr(self.as_ptr()) };
+		core::mem::forget(self);
+		f
 	}
 }
 
 impl<'a> AsRef<[u8]> for Mmap<'a> {
 	fn as_ref(&self) -> &[u8] {
-		&self.slice
+		unsafe { core::slice::from_raw_parts(self.ptr, self.size) }
 	}
 }
 
 impl<'a> AsMut<[u8]> for Mmap<'a> {
 	fn as_mut(&mut self) -> &mut [u8] {
-		&mut self.slice
+		unsafe { core::slice::from_raw_parts_mut(self.ptr, self.size) }
 	}
 }
@@ -1,13 +1,101 @@
+mod func;
+pub use func::*;
+
+mod patch;
+pub use patch::*;
+
 #[cfg(target_os = "linux")]
 mod linux;
 
 #[cfg(target_os = "linux")]
 pub use linux::*;
 
+#[cfg(target_os = "linux")]
+mod wx;
+
+#[cfg(target_os = "linux")]
+pub use wx::*;
+
+#[cfg(target_os = "linux")]
+mod dual;
+
+#[cfg(target_os = "linux")]
+pub use dual::*;
+
+#[cfg(all(target_os = "linux", feature = "std"))]
+mod heap;
+
+#[cfg(all(target_os = "linux", feature = "std"))]
+pub use heap::*;
+
+#[cfg(target_os = "linux")]
+mod stack;
+
+#[cfg(target_os = "linux")]
+pub use stack::*;
+
+#[cfg(all(target_os = "linux", target_arch = "x86_64", feature = "std"))]
+mod hook;
+
+#[cfg(all(target_os = "linux", target_arch = "x86_64", feature = "std"))]
+pub use hook::*;
+
+#[cfg(all(target_os = "linux", target_arch = "x86_64", feature = "std"))]
+mod thunk;
+
+#[cfg(all(target_os = "linux", target_arch = "x86_64", feature = "std"))]
+pub use thunk::*;
+
+/// An error number reported by the OS.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Errno(pub i32);
+
+impl Errno {
+	pub const EPERM: Self = Self(1);
+	pub const EBADF: Self = Self(9);
+	pub const ENOMEM: Self = Self(12);
+	pub const EACCES: Self = Self(13);
+	pub const EEXIST: Self = Self(17);
+	pub const EINVAL: Self = Self(22);
+	pub const EMFILE: Self = Self(24);
+}
+
+impl core::fmt::Display for Errno {
+	fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+		match *self {
+			Self::EPERM => f.write_str("operation not permitted (EPERM)"),
+			Self::EBADF => f.write_str("bad file descriptor (EBADF)"),
+			Self::ENOMEM => f.write_str("out of memory (ENOMEM)"),
+			Self::EACCES => f.write_str("permission denied (EACCES)"),
+			Self::EEXIST => f.write_str("address already mapped (EEXIST)"),
+			Self::EINVAL => f.write_str("invalid argument (EINVAL)"),
+			Self::EMFILE => f.write_str("too many open files (EMFILE)"),
+			Self(other) => write!(f, "errno {other}")
+		}
+	}
+}
+
 #[non_exhaustive]
-#[derive(Debug)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum MmapError {
-	Failed
+	/// Asked to map zero bytes, which the OS rejects.
+	ZeroLength,
+	/// Mapping the memory failed.
+	Map(Errno),
+	/// Changing permissions of the memory failed.
+	/// Under SELinux, making memory executable can fail with [Errno::EACCES].
+	Protect(Errno),
+	/// Unmapping the memory failed.
+	Unmap(Errno),
+	/// Code to patch isn't aligned to an 8 byte boundary, or a jump would straddle one.
+	Misaligned,
+	/// A jump target is too far away to be reached with a 32 bit displacement, or a patch would go past the end of the mapping.
+	OutOfRange,
+	/// Instructions a hook overwrites couldn't be decoded, or can't run from anywhere else.
+	Relocate,
+	/// Creating the backing file for a [DualMmap] failed.
+	#[cfg(target_os = "linux")]
+	Memfd(Errno)
 }
 
 pub type MmapResult<T> = Result<T, MmapError>;
@@ -15,15 +103,17 @@ pub type MmapResult<T> = Result<T, MmapError>;
 impl core::fmt::Display for MmapError {
 	fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
 		match self {
-			Self::Failed => f.write_str("Failed to create mmap")
+			Self::ZeroLength => f.write_str("Failed to create mmap: zero length"),
+			Self::Map(e) => write!(f, "Failed to create mmap: {e}"),
+			Self::Protect(e) => write!(f, "Failed to change mmap permissions: {e}"),
+			Self::Unmap(e) => write!(f, "Failed to unmap mmap: {e}"),
+			Self::Misaligned => f.write_str("Failed to patch code: misaligned"),
+			Self::OutOfRange => f.write_str("Failed to patch code: out of range"),
+			Self::Relocate => f.write_str("Failed to hook function: can't relocate its first instructions"),
+			#[cfg(target_os = "linux")]
+			Self::Memfd(e) => write!(f, "Failed to create memfd: {e}")
 		}
 	}
 }
 
-impl core::error::Error for MmapError {
-	fn description(&self) -> &str {
-		match self {
-			Self::Failed => "Failed to create mmap"
-		}
-	}
-}
+impl core::error::Error for MmapError {}
@@ -0,0 +1,78 @@
+/// Makes code written to `ptr..ptr + len` visible to instruction fetch.
+///
+/// x86 keeps its instruction cache coherent with stores, so this only orders the writes.
+/// Other architectures need the cache lines explicitly invalidated.
+pub fn flush_icache(ptr: *const u8, len: usize) {
+	#[cfg(any(target_arch = "x86", target_arch = "x86_64"))]
+	{
+		let _ = (ptr, len);
+		core::sync::atomic::fence(core::sync::atomic::Ordering::SeqCst);
+	}
+
+	#[cfg(not(any(target_arch = "x86", target_arch = "x86_64")))]
+	{
+		extern "C" {
+			fn __clear_cache(start: *mut core::ffi::c_char, end: *mut core::ffi::c_char);
+		}
+
+		unsafe { __clear_cache(ptr as _, ptr.wrapping_add(len) as _) };
+	}
+}
+
+/// Atomically replaces the 8 bytes of code at `ptr`.
+///
+/// Threads executing the code see either all of the old bytes or all of the new ones.
+///
+/// # Safety
+/// `ptr` must be valid for writes of 8 bytes, and the old and new code must each be valid on their own.
+pub unsafe fn patch_u64(ptr: *mut u8, code: [u8; 8]) -> super::MmapResult<()> {
+	if !(ptr as usize).is_multiple_of(8) {
+		return Err(super::MmapError::Misaligned);
+	}
+
+	unsafe { core::sync::atomic::AtomicU64::from_ptr(ptr as *mut u64) }
+		.store(u64::from_ne_bytes(code), core::sync::atomic::Ordering::Release);
+
+	flush_icache(ptr, 8);
+
+	Ok(())
+}
+
+/// Checks the 8 bytes at `offset` lie within a mapping of `len` bytes.
+pub(super) fn check_bounds(offset: usize, len: usize) -> super::MmapResult<()> {
+	if offset.checked_add(8).is_some_and(|end| end <= len) {
+		Ok(())
+	} else {
+		Err(super::MmapError::OutOfRange)
+	}
+}
+
+/// Splices a `jmp rel32` to `target` into an aligned 8 byte word of code, for a jump that will execute at `at`.
+pub(super) fn splice_jmp(mut code: [u8; 8], at: *const u8, target: *const u8) -> super::MmapResult<[u8; 8]> {
+	let offset = at as usize % 8;
+	if offset > 3 {
+		return Err(super::MmapError::Misaligned);
+	}
+
+	let rel = (target as isize).wrapping_sub(at as isize + 5);
+	let rel = i32::try_from(rel).map_err(|_| super::MmapError::OutOfRange)?;
+
+	code[offset..offset + 5].copy_from_slice(&crate::tier::raw::x86::jmpnrd_i32(rel as u32));
+
+	Ok(code)
+}
+
+/// Atomically writes a 5 byte `jmp rel32` at `ptr`, jumping to `target`.
+///
+/// The jump must not straddle an 8 byte boundary, since it is written as part of a single aligned store.
+///
+/// # Safety
+/// `ptr` must be valid for reads and writes of the 8 byte word containing it, and the old code must be valid on its own.
+pub unsafe fn patch_jmp(ptr: *mut u8, target: *const u8) -> super::MmapResult<()> {
+	let word = ptr.wrapping_sub(ptr as usize % 8);
+	let code = unsafe { core::sync::atomic::AtomicU64::from_ptr(word as *mut u64) }
+		.load(core::sync::atomic::Ordering::Acquire)
+		.to_ne_bytes();
+
+	unsafe { patch_u64(word, splice_jmp(code, ptr, target)?) }
+}
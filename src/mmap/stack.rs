@@ -0,0 +1,67 @@
+use super::linux::{errno, map_anonymous, mprotect, munmap, page_size, MmapOptions, PROT_NONE, PROT_READ, PROT_WRITE};
+
+/// A read + write stack for generated code to run on, with an inaccessible guard page below it.
+///
+/// Overflowing the stack faults on the guard page rather than silently corrupting whatever is mapped below.
+/// Switch to it with [switch_stack](crate::tier::raw::amd64::switch_stack).
+#[derive(Debug)]
+pub struct Stack {
+	/// Start of the whole mapping, which is the guard page.
+	ptr: *mut u8,
+	/// Length of the whole mapping, including the guard page.
+	len: usize
+}
+
+impl Drop for Stack {
+	fn drop(&mut self) {
+		unsafe { munmap(self.ptr as _, self.len) };
+	}
+}
+
+impl Stack {
+	/// Maps a stack of at least `size` usable bytes, rounded up to the page size.
+	pub fn new(size: usize) -> super::MmapResult<Self> {
+		if size == 0 {
+			return Err(super::MmapError::ZeroLength);
+		}
+
+		let page = page_size();
+		let (ptr, len) = map_anonymous(size.next_multiple_of(page) + page, PROT_READ | PROT_WRITE, &MmapOptions::new())?;
+
+		if unsafe { mprotect(ptr as _, page, PROT_NONE) } != 0 {
+			let e = errno();
+			unsafe { munmap(ptr as _, len) };
+			return Err(super::MmapError::Protect(e));
+		}
+
+		Ok(Self {
+			ptr,
+			len
+		})
+	}
+
+	/// The guard page, which faults on any access.
+	pub fn guard(&self) -> *const u8 {
+		self.ptr
+	}
+
+	/// Lowest usable address, just above the guard page.
+	pub fn bottom(&self) -> *mut u8 {
+		self.ptr.wrapping_add(page_size())
+	}
+
+	/// One past the highest usable address. Stacks grow down, so this is the initial stack pointer.
+	/// Always 16 byte aligned.
+	pub fn top(&self) -> *mut u8 {
+		self.ptr.wrapping_add(self.len)
+	}
+
+	/// Number of usable bytes, not including the guard page.
+	pub fn len(&self) -> usize {
+		self.len - page_size()
+	}
+
+	pub fn is_empty(&self) -> bool {
+		self.len() == 0
+	}
+}
@@ -0,0 +1,143 @@
+use crate::abi::{Arg, Call, Ret};
+use crate::tier::raw::amd64::*;
+
+/// Registers holding arguments, plus `rax` for the vector count of variadic calls and `r10` for the static chain.
+fn live_on_entry() -> Vec<u8> {
+	let mut regs = crate::abi::HOST.args.to_vec();
+	regs.extend([RAX, R10]);
+	regs
+}
+
+/// A stub that asks a resolver for the real function the first time it's called, then jumps straight to it from then on.
+///
+/// The stub jumps through a slot that starts out pointing at the resolving code, which overwrites it with the resolved function.
+/// Threads calling the stub at the same time can each run the resolver, so it has to return the same function every time.
+#[derive(Debug)]
+pub struct LazyStub<'a> {
+	map: super::Mmap<'a>,
+	slot: usize
+}
+
+impl LazyStub<'_> {
+	/// Offset of the resolving code, right after the first jump.
+	const RESOLVE: usize = 6;
+
+	/// Creates a stub calling `resolver` with `ctx` to find the function, with every argument left in place for it.
+	pub fn new(resolver: extern "C" fn(u64) -> *const u8, ctx: u64) -> super::MmapResult<Self> {
+		let abi = crate::abi::HOST;
+
+		// Entered with the return address pushed, so 8 more bytes realign the stack under the saved vectors.
+		let vectors = abi.float_args;
+		let size = 8 + 16 * vectors.len() as u32;
+
+		let mut code = vec![];
+		code.extend(jmpnai_rip(0));
+		code.extend(sub_r64_i32(RSP, size));
+		for (i, &x) in vectors.iter().enumerate() {
+			code.extend(movdqu_m128_x(RSP, 16 * i as i32, x));
+		}
+
+		let preserve = live_on_entry();
+		code.extend(Call::new(abi, resolver as *const () as u64).args(&[Arg::Imm(ctx)]).preserve(&preserve).ret(Ret::Int(R11)).emit());
+
+		for (i, &x) in vectors.iter().enumerate() {
+			code.extend(movdqu_x_m128(x, RSP, 16 * i as i32));
+		}
+		code.extend(add_r64_i32(RSP, size));
+
+		// The store and jump are fixed in size, so the slot can go right after them.
+		let slot = (code.len() + 7 + 3).next_multiple_of(8);
+		code.extend(mov_rip_r64((slot - code.len() - 7) as i32, R11));
+		code.extend(jmpnai_r64(R11));
+		code.resize(slot + 8, 0xCC);
+		code[2..6].copy_from_slice(&((slot - Self::RESOLVE) as i32).to_le_bytes());
+
+		let map = super::Mmap::exec(code)?;
+		map.patch(slot, (map.as_ptr() as u64 + Self::RESOLVE as u64).to_le_bytes())?;
+
+		Ok(Self { map, slot })
+	}
+
+	pub fn as_ptr(&self) -> *const u8 {
+		self.map.as_ptr()
+	}
+
+	/// Casts the stub to a function, which can't outlive it.
+	///
+	/// # Safety
+	/// The resolved function must match the signature of `F`.
+	pub unsafe fn as_fn<F: super::FnPtr>(&self) -> super::Func<'_, F> {
+		unsafe { self.map.as_fn() }
+	}
+
+	/// The function the stub was resolved to, if it has been called yet.
+	pub fn resolved(&self) -> Option<*const u8> {
+		// The stub writes the slot while running, so it's read atomically, through the mapping's own pointer.
+		let slot = unsafe { core::sync::atomic::AtomicU64::from_ptr(self.map.ptr.add(self.slot) as *mut u64) };
+		let target = slot.load(core::sync::atomic::Ordering::Acquire) as *const u8;
+
+		(target != self.map.as_ptr().wrapping_add(Self::RESOLVE)).then_some(target)
+	}
+}
+
+/// A table of stubs that each jump to a function through a slot, like an ELF procedure linkage table.
+///
+/// Code can call a stub directly, while the function behind it is swapped out with [Plt::set].
+#[derive(Debug)]
+pub struct Plt<'a> {
+	map: super::Mmap<'a>,
+	len: usize
+}
+
+impl Plt<'_> {
+	/// Size of each stub, padded so slots stay 8 byte aligned.
+	const ENTRY: usize = 8;
+
+	/// Creates a stub for each of `targets`.
+	/// Fails with [MmapError::ZeroLength](super::MmapError::ZeroLength) if there are none, as an empty table can't be mapped.
+	pub fn new(targets: &[*const u8]) -> super::MmapResult<Self> {
+		let len = targets.len();
+
+		let mut code = vec![];
+		for i in 0..len {
+			let slot = Self::ENTRY * (len + i);
+			code.extend(jmpnai_rip((slot - code.len() - 6) as i32));
+			code.extend([0xCC, 0xCC]);
+		}
+
+		for target in targets {
+			code.extend((*target as u64).to_le_bytes());
+		}
+
+		Ok(Self {
+			map: super::Mmap::exec(code)?,
+			len
+		})
+	}
+
+	/// The stub for the `i`th function.
+	///
+	/// # Panics
+	/// If `i` is out of bounds.
+	pub fn entry(&self, i: usize) -> *const u8 {
+		assert!(i < self.len, "Entry out of bounds");
+		self.map.as_ptr().wrapping_add(Self::ENTRY * i)
+	}
+
+	/// Atomically points the `i`th stub at `target`.
+	///
+	/// # Panics
+	/// If `i` is out of bounds.
+	pub fn set(&self, i: usize, target: *const u8) -> super::MmapResult<()> {
+		assert!(i < self.len, "Entry out of bounds");
+		self.map.patch(Self::ENTRY * (self.len + i), (target as u64).to_le_bytes())
+	}
+
+	pub fn len(&self) -> usize {
+		self.len
+	}
+
+	pub fn is_empty(&self) -> bool {
+		self.len == 0
+	}
+}
@@ -0,0 +1,143 @@
+use super::linux::{errno, map_anonymous, mprotect, munmap, MmapOptions, PROT_EXEC, PROT_READ, PROT_WRITE};
+
+/// State of a [WxMmap] whose pages are mapped as read + write.
+#[derive(Debug)]
+pub struct Writable;
+
+/// State of a [WxMmap] whose pages are mapped as read + execute.
+#[derive(Debug)]
+pub struct Executable;
+
+/// A mapping that is never writable and executable at the same time.
+///
+/// Starts out as [Writable] so code can be copied in and patched, then is [sealed](WxMmap::seal) to [Executable].
+/// Going back to [Writable] with [WxMmap::unseal] allows patching again.
+#[derive(Debug)]
+pub struct WxMmap<State> {
+	ptr: *mut u8,
+	len: usize,
+	state: core::marker::PhantomData<State>
+}
+
+impl<State> Drop for WxMmap<State> {
+	fn drop(&mut self) {
+		unsafe { munmap(self.ptr as _, self.len) };
+	}
+}
+
+impl<State> WxMmap<State> {
+	fn transition<Next>(self, prot: core::ffi::c_int) -> Result<WxMmap<Next>, (Self, super::MmapError)> {
+		if unsafe { mprotect(self.ptr as _, self.len, prot) } != 0 {
+			return Err((self, super::MmapError::Protect(errno())));
+		}
+
+		let next = WxMmap {
+			ptr: self.ptr,
+			len: self.len,
+			state: core::marker::PhantomData
+		};
+
+		core::mem::forget(self);
+
+		Ok(next)
+	}
+
+	pub fn as_ptr(&self) -> *const u8 {
+		self.ptr
+	}
+
+	/// Unmaps the memory, reporting any failure that dropping would ignore.
+	pub fn unmap(self) -> super::MmapResult<()> {
+		let result = unsafe { munmap(self.ptr as _, self.len) };
+		let result = if result == 0 { Ok(()) } else { Err(super::MmapError::Unmap(errno())) };
+
+		core::mem::forget(self);
+		result
+	}
+
+	pub fn len(&self) -> usize {
+		self.len
+	}
+
+	pub fn is_empty(&self) -> bool {
+		self.len == 0
+	}
+}
+
+impl WxMmap<Writable> {
+	/// Maps `len` zeroed bytes as read + write.
+	pub fn new(len: usize) -> super::MmapResult<Self> {
+		Self::new_with(len, &MmapOptions::new())
+	}
+
+	/// Maps at least `len` zeroed bytes as read + write, placed according to `options`.
+	/// With huge pages the length is rounded up.
+	pub fn new_with(len: usize, options: &MmapOptions) -> super::MmapResult<Self> {
+		let (ptr, len) = map_anonymous(len, PROT_READ | PROT_WRITE, options)?;
+
+		Ok(Self {
+			ptr,
+			len,
+			state: core::marker::PhantomData
+		})
+	}
+
+	/// Maps a copy of `mem` as read + write.
+	pub fn copy(mem: impl AsRef<[u8]>) -> super::MmapResult<Self> {
+		Self::copy_with(mem, &MmapOptions::new())
+	}
+
+	/// Maps a copy of `mem` as read + write, placed according to `options`.
+	pub fn copy_with(mem: impl AsRef<[u8]>, options: &MmapOptions) -> super::MmapResult<Self> {
+		let mem = mem.as_ref();
+
+		let mut map = Self::new_with(mem.len(), options)?;
+		map.as_mut()[..mem.len()].copy_from_slice(mem);
+
+		Ok(map)
+	}
+
+	/// Drops write permissions and makes the mapping executable.
+	/// On failure the mapping is handed back unchanged, along with the error.
+	pub fn seal(self) -> Result<WxMmap<Executable>, (Self, super::MmapError)> {
+		self.transition(PROT_READ | PROT_EXEC)
+	}
+}
+
+impl WxMmap<Executable> {
+	/// Casts the start of the mapping to a function, which can't outlive the mapping.
+	///
+	/// # Safety
+	/// The mapped code must match the signature and calling convention of `F`.
+	pub unsafe fn as_fn<F: super::FnPtr>(&self) -> super::Func<'_, F> {
+		unsafe { super::Func::new(self.ptr) }
+	}
+
+	/// Casts the start of the mapping to a function, and never unmaps it.
+	///
+	/// # Safety
+	/// The mapped code must match the signature and calling convention of `F`.
+	pub unsafe fn leak<F: super::FnPtr>(self) -> F {
+		let f = unsafe { F::from_ptr(self.ptr) };
+		core::mem::forget(self);
+		f
+	}
+
+	/// Drops execute permissions and makes the mapping writable again.
+	/// On failure the mapping is handed back unchanged, along with the error.
+	pub fn unseal(self) -> Result<WxMmap<Writable>, (Self, super::MmapError)> {
+		self.transition(PROT_READ | PROT_WRITE)
+	}
+}
+
+impl<State> AsRef<[u8]> for WxMmap<State> {
+	fn as_ref(&self) -> &[u8] {
+		unsafe { core::slice::from_raw_parts(self.ptr, self.len) }
+	}
+}
+
+impl AsMut<[u8]> for WxMmap<Writable> {
+	fn as_mut(&mut self) -> &mut [u8] {
+		unsafe { core::slice::from_raw_parts_mut(self.ptr, self.len) }
+	}
+}
@@ -0,0 +1,206 @@
+//! COFF objects, as produced by Windows compilers and consumed by `link.exe` or `lld-link`.
+
+use super::{Arch, Emit, Object, ObjectError, ObjectResult, RelocationKind, Section, StringTable};
+
+pub(crate) const IMAGE_FILE_MACHINE_I386: u16 = 0x14C;
+pub(crate) const IMAGE_FILE_MACHINE_AMD64: u16 = 0x8664;
+
+pub(crate) const IMAGE_SCN_CNT_CODE: u32 = 0x20;
+pub(crate) const IMAGE_SCN_CNT_INITIALIZED_DATA: u32 = 0x40;
+pub(crate) const IMAGE_SCN_MEM_EXECUTE: u32 = 0x2000_0000;
+pub(crate) const IMAGE_SCN_MEM_READ: u32 = 0x4000_0000;
+pub(crate) const IMAGE_SCN_MEM_WRITE: u32 = 0x8000_0000;
+const IMAGE_SCN_ALIGN_16BYTES: u32 = 0x0050_0000;
+
+const IMAGE_SYM_CLASS_EXTERNAL: u8 = 2;
+const IMAGE_SYM_CLASS_STATIC: u8 = 3;
+const IMAGE_SYM_DTYPE_FUNCTION: u16 = 0x20;
+
+const FILE_HEADER_SIZE: usize = 20;
+pub(crate) const SECTION_HEADER_SIZE: usize = 40;
+const RELOCATION_SIZE: usize = 10;
+
+pub(crate) fn machine(arch: Arch) -> u16 {
+	match arch {
+		Arch::X86 => IMAGE_FILE_MACHINE_I386,
+		Arch::Amd64 => IMAGE_FILE_MACHINE_AMD64
+	}
+}
+
+pub(crate) struct SectionHeader {
+	pub(crate) name: [u8; 8],
+	pub(crate) virtual_size: u32,
+	pub(crate) virtual_address: u32,
+	pub(crate) raw_size: u32,
+	pub(crate) raw_offset: u32,
+	pub(crate) relocations_offset: u32,
+	pub(crate) relocations: u16,
+	pub(crate) characteristics: u32
+}
+
+impl SectionHeader {
+	pub(crate) fn write(&self, out: &mut Vec<u8>) {
+		out.extend(self.name);
+		out.u32(self.virtual_size);
+		out.u32(self.virtual_address);
+		out.u32(self.raw_size);
+		out.u32(self.raw_offset);
+		out.u32(self.relocations_offset);
+		out.u32(0); // Line numbers
+		out.u16(self.relocations);
+		out.u16(0);
+		out.u32(self.characteristics);
+	}
+}
+
+/// The relocation type, and what to add to the field on top of the addend.
+/// COFF has no explicit addends, and its relative relocations are already relative to the end of the field.
+fn relocation_type(arch: Arch, kind: RelocationKind) -> ObjectResult<(u16, i64)> {
+	match (arch, kind) {
+		(Arch::Amd64, RelocationKind::Absolute64) => Ok((0x1, 0)), // IMAGE_REL_AMD64_ADDR64
+		(Arch::Amd64, RelocationKind::Absolute32) => Ok((0x2, 0)), // IMAGE_REL_AMD64_ADDR32
+		(Arch::Amd64, RelocationKind::Relative32 | RelocationKind::Branch32) => Ok((0x4, 4)), // IMAGE_REL_AMD64_REL32
+		(Arch::X86, RelocationKind::Absolute32) => Ok((0x6, 0)), // IMAGE_REL_I386_DIR32
+		(Arch::X86, RelocationKind::Relative32 | RelocationKind::Branch32) => Ok((0x14, 4)), // IMAGE_REL_I386_REL32
+		(arch, kind) => Err(ObjectError::UnsupportedRelocation(arch, kind))
+	}
+}
+
+/// Writes a symbol name, spilling into the string table if it doesn't fit in 8 bytes.
+fn write_name(out: &mut Vec<u8>, strtab: &mut StringTable, name: &str) {
+	if name.len() <= 8 {
+		let mut short = [0; 8];
+		short[..name.len()].copy_from_slice(name.as_bytes());
+		out.extend(short);
+	} else {
+		// Offsets count the 4 byte size prefix of the table.
+		out.u32(0);
+		out.u32(strtab.add(name) + 3);
+	}
+}
+
+/// Writes `object` out as a COFF object, ready to be passed to a linker.
+///
+/// Symbols named by relocations but not defined in `object` become undefined externals, resolved at link time.
+pub fn write(object: &Object) -> ObjectResult<Vec<u8>> {
+	object.validate()?;
+
+	let arch = object.arch;
+	let mut text = object.text.to_vec();
+	let mut data = object.data.to_vec();
+
+	// Symbol indices, in the order they'll be written out.
+	let externs = object.externs();
+	let names = object.symbols.iter().map(|s| s.name).chain(externs.iter().copied()).collect::<Vec<_>>();
+
+	// Relocations, split by the section they patch. Addends are stored in the fields themselves.
+	let mut relocations = [vec![], vec![]];
+
+	for r in &object.relocations {
+		let (kind, bias) = relocation_type(arch, r.kind)?;
+		let symbol = names.iter().position(|&n| n == r.symbol).unwrap() as u32;
+
+		let (section, out) = match r.section {
+			Section::Text => (&mut text, &mut relocations[0]),
+			Section::Data => (&mut data, &mut relocations[1])
+		};
+
+		let at = r.offset as usize;
+		match r.kind {
+			RelocationKind::Absolute64 => {
+				let field = &mut section[at..at + 8];
+				let value = u64::from_le_bytes(field.try_into().unwrap()).wrapping_add((r.addend + bias) as u64);
+				field.copy_from_slice(&value.to_le_bytes());
+			},
+			_ => {
+				let field = &mut section[at..at + 4];
+				let value = u32::from_le_bytes(field.try_into().unwrap()).wrapping_add((r.addend + bias) as u32);
+				field.copy_from_slice(&value.to_le_bytes());
+			}
+		}
+
+		out.u32(r.offset as u32);
+		out.u32(symbol);
+		out.u16(kind);
+	}
+
+	let sections = [
+		(*b".text\0\0\0", &text, &relocations[0], IMAGE_SCN_CNT_CODE | IMAGE_SCN_MEM_EXECUTE | IMAGE_SCN_MEM_READ),
+		(*b".data\0\0\0", &data, &relocations[1], IMAGE_SCN_CNT_INITIALIZED_DATA | IMAGE_SCN_MEM_READ | IMAGE_SCN_MEM_WRITE)
+	];
+
+	// Raw data and relocations follow the headers back to back.
+	let mut offset = FILE_HEADER_SIZE + SECTION_HEADER_SIZE * sections.len();
+	let mut headers = vec![];
+	let mut body: Vec<u8> = vec![];
+
+	for (name, bytes, relocs, characteristics) in sections {
+		let raw_offset = offset;
+		offset += bytes.len();
+		let relocations_offset = offset;
+		offset += relocs.len();
+
+		SectionHeader {
+			name,
+			virtual_size: 0,
+			virtual_address: 0,
+			raw_size: bytes.len() as u32,
+			raw_offset: if bytes.is_empty() { 0 } else { raw_offset as u32 },
+			relocations_offset: if relocs.is_empty() { 0 } else { relocations_offset as u32 },
+			relocations: (relocs.len() / RELOCATION_SIZE) as u16,
+			characteristics: characteristics | IMAGE_SCN_ALIGN_16BYTES
+		}.write(&mut headers);
+
+		body.extend(bytes.iter());
+		body.extend(relocs.iter());
+	}
+
+	let symtab_offset = offset;
+
+	let mut strtab = StringTable::new();
+	let mut symtab = vec![];
+
+	for symbol in &object.symbols {
+		write_name(&mut symtab, &mut strtab, symbol.name);
+		symtab.u32(symbol.offset as u32);
+
+		let (section, kind) = match symbol.section {
+			Section::Text => (1, IMAGE_SYM_DTYPE_FUNCTION),
+			Section::Data => (2, 0)
+		};
+
+		symtab.u16(section);
+		symtab.u16(kind);
+		symtab.u8(if symbol.global { IMAGE_SYM_CLASS_EXTERNAL } else { IMAGE_SYM_CLASS_STATIC });
+		symtab.u8(0);
+	}
+
+	for name in &externs {
+		write_name(&mut symtab, &mut strtab, name);
+		symtab.u32(0);
+		symtab.u16(0); // IMAGE_SYM_UNDEFINED
+		symtab.u16(0);
+		symtab.u8(IMAGE_SYM_CLASS_EXTERNAL);
+		symtab.u8(0);
+	}
+
+	let mut out = vec![];
+	out.u16(machine(arch));
+	out.u16(sections.len() as u16);
+	out.u32(0); // Timestamp
+	out.u32(symtab_offset as u32);
+	out.u32(names.len() as u32);
+	out.u16(0); // No optional header
+	out.u16(0);
+
+	out.extend(headers);
+	out.extend(body);
+	out.extend(symtab);
+
+	// The string table starts with its own size, in place of the leading empty string.
+	let strings = &strtab.bytes()[1..];
+	out.u32(strings.len() as u32 + 4);
+	out.extend(strings);
+
+	Ok(out)
+}
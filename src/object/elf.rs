@@ -0,0 +1,530 @@
+//! ELF relocatable objects (`ET_REL`) and static executables (`ET_EXEC`), ELF64 for amd64 and ELF32 for x86.
+
+use super::{Arch, Emit, Object, ObjectError, ObjectResult, RelocationKind, Section, StringTable};
+
+const ET_REL: u16 = 1;
+const ET_EXEC: u16 = 2;
+
+const PT_LOAD: u32 = 1;
+const PT_GNU_STACK: u32 = 0x6474E551;
+
+const PF_X: u32 = 0x1;
+const PF_W: u32 = 0x2;
+const PF_R: u32 = 0x4;
+
+/// Alignment of loadable segments, both in memory and in the file.
+const PAGE: u64 = 0x1000;
+
+const EM_386: u16 = 3;
+const EM_X86_64: u16 = 62;
+
+const SHT_PROGBITS: u32 = 1;
+const SHT_SYMTAB: u32 = 2;
+const SHT_STRTAB: u32 = 3;
+const SHT_RELA: u32 = 4;
+const SHT_REL: u32 = 9;
+
+const SHF_WRITE: u64 = 0x1;
+const SHF_ALLOC: u64 = 0x2;
+const SHF_EXECINSTR: u64 = 0x4;
+const SHF_INFO_LINK: u64 = 0x40;
+
+const STB_LOCAL: u8 = 0;
+const STB_GLOBAL: u8 = 1;
+
+const STT_NOTYPE: u8 = 0;
+const STT_OBJECT: u8 = 1;
+const STT_FUNC: u8 = 2;
+const STT_SECTION: u8 = 3;
+
+/// Section header indices, which are fixed regardless of contents.
+const TEXT: u16 = 1;
+const DATA: u16 = 2;
+const SYMTAB: u32 = 3;
+const STRTAB: u32 = 4;
+
+/// 32 or 64 bit ELF, which changes the size and order of most fields.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum Class {
+	Elf32,
+	Elf64
+}
+
+impl Class {
+	pub(crate) fn of(arch: Arch) -> Self {
+		match arch {
+			Arch::X86 => Self::Elf32,
+			Arch::Amd64 => Self::Elf64
+		}
+	}
+
+	pub(crate) fn machine(arch: Arch) -> u16 {
+		match arch {
+			Arch::X86 => EM_386,
+			Arch::Amd64 => EM_X86_64
+		}
+	}
+
+	/// Writes an address or offset sized field.
+	pub(crate) fn word(self, out: &mut Vec<u8>, v: u64) {
+		match self {
+			Self::Elf32 => out.u32(v as u32),
+			Self::Elf64 => out.u64(v)
+		}
+	}
+
+	pub(crate) fn header_size(self) -> usize {
+		match self {
+			Self::Elf32 => 52,
+			Self::Elf64 => 64
+		}
+	}
+
+	pub(crate) fn program_header_size(self) -> usize {
+		match self {
+			Self::Elf32 => 32,
+			Self::Elf64 => 56
+		}
+	}
+
+	fn section_header_size(self) -> usize {
+		match self {
+			Self::Elf32 => 40,
+			Self::Elf64 => 64
+		}
+	}
+
+	fn symbol_size(self) -> usize {
+		match self {
+			Self::Elf32 => 16,
+			Self::Elf64 => 24
+		}
+	}
+
+	/// Size of a relocation entry. ELF32 uses `REL`, with addends stored in the field itself.
+	fn relocation_size(self) -> usize {
+		match self {
+			Self::Elf32 => 8,
+			Self::Elf64 => 24
+		}
+	}
+}
+
+/// Everything in the ELF header that differs between object and executable files.
+pub(crate) struct Header {
+	pub(crate) class: Class,
+	pub(crate) kind: u16,
+	pub(crate) machine: u16,
+	pub(crate) entry: u64,
+	pub(crate) phoff: u64,
+	pub(crate) phnum: u16,
+	pub(crate) shoff: u64,
+	pub(crate) shnum: u16,
+	pub(crate) shstrndx: u16
+}
+
+impl Header {
+	pub(crate) fn write(&self, out: &mut Vec<u8>) {
+		let class = self.class;
+
+		out.extend(b"\x7FELF");
+		out.u8(match class { Class::Elf32 => 1, Class::Elf64 => 2 });
+		out.u8(1); // Little endian
+		out.u8(1); // Version
+		out.u8(0); // System V ABI
+		out.resize(16, 0);
+
+		out.u16(self.kind);
+		out.u16(self.machine);
+		out.u32(1);
+		class.word(out, self.entry);
+		class.word(out, self.phoff);
+		class.word(out, self.shoff);
+		out.u32(0);
+		out.u16(class.header_size() as u16);
+		out.u16(if self.phnum == 0 { 0 } else { class.program_header_size() as u16 });
+		out.u16(self.phnum);
+		out.u16(if self.shnum == 0 { 0 } else { class.section_header_size() as u16 });
+		out.u16(self.shnum);
+		out.u16(self.shstrndx);
+	}
+}
+
+struct SectionHeader {
+	name: u32,
+	kind: u32,
+	flags: u64,
+	offset: u64,
+	size: u64,
+	link: u32,
+	info: u32,
+	align: u64,
+	entsize: u64
+}
+
+impl SectionHeader {
+	fn write(&self, class: Class, out: &mut Vec<u8>) {
+		out.u32(self.name);
+		out.u32(self.kind);
+		class.word(out, self.flags);
+		class.word(out, 0); // Address, always 0 for relocatable objects
+		class.word(out, self.offset);
+		class.word(out, self.size);
+		out.u32(self.link);
+		out.u32(self.info);
+		class.word(out, self.align);
+		class.word(out, self.entsize);
+	}
+}
+
+fn write_symbol(class: Class, out: &mut Vec<u8>, name: u32, info: u8, shndx: u16, value: u64, size: u64) {
+	match class {
+		Class::Elf32 => {
+			out.u32(name);
+			out.u32(value as u32);
+			out.u32(size as u32);
+			out.u8(info);
+			out.u8(0);
+			out.u16(shndx);
+		},
+		Class::Elf64 => {
+			out.u32(name);
+			out.u8(info);
+			out.u8(0);
+			out.u16(shndx);
+			out.u64(value);
+			out.u64(size);
+		}
+	}
+}
+
+fn relocation_type(arch: Arch, kind: RelocationKind) -> ObjectResult<u32> {
+	match (arch, kind) {
+		(Arch::Amd64, RelocationKind::Absolute64) => Ok(1), // R_X86_64_64
+		(Arch::Amd64, RelocationKind::Relative32) => Ok(2), // R_X86_64_PC32
+		(Arch::Amd64, RelocationKind::Branch32) => Ok(4), // R_X86_64_PLT32
+		(Arch::Amd64, RelocationKind::GotRelative32) => Ok(9), // R_X86_64_GOTPCREL
+		(Arch::Amd64, RelocationKind::Absolute32) => Ok(10), // R_X86_64_32
+		(Arch::X86, RelocationKind::Absolute32) => Ok(1), // R_386_32
+		(Arch::X86, RelocationKind::Relative32 | RelocationKind::Branch32) => Ok(2), // R_386_PC32
+		(arch, kind) => Err(ObjectError::UnsupportedRelocation(arch, kind))
+	}
+}
+
+/// Writes `object` out as an ELF relocatable object, ready to be passed to a linker.
+///
+/// Symbols named by relocations but not defined in `object` become undefined globals, resolved at link time.
+pub fn write(object: &Object) -> ObjectResult<Vec<u8>> {
+	object.validate()?;
+
+	let class = Class::of(object.arch);
+	let arch = object.arch;
+
+	// ELF32 stores addends in the fields being relocated, so the sections need to be patched.
+	let mut text = object.text.to_vec();
+	let mut data = object.data.to_vec();
+
+	// Symbol table. Locals must come before globals.
+	let mut strtab = StringTable::new();
+	let mut symtab = vec![];
+	let mut names: Vec<&str> = vec![];
+
+	write_symbol(class, &mut symtab, 0, 0, 0, 0, 0);
+	write_symbol(class, &mut symtab, 0, (STB_LOCAL << 4) | STT_SECTION, TEXT, 0, 0);
+	write_symbol(class, &mut symtab, 0, (STB_LOCAL << 4) | STT_SECTION, DATA, 0, 0);
+	names.extend(["", "", ""]);
+
+	let (locals, globals): (Vec<&super::Symbol>, Vec<&super::Symbol>) = object.symbols.iter().partition(|s| !s.global);
+	let first_global = names.len() + locals.len();
+
+	for symbol in locals.into_iter().chain(globals) {
+		let (kind, shndx) = match symbol.section {
+			Section::Text => (STT_FUNC, TEXT),
+			Section::Data => (STT_OBJECT, DATA)
+		};
+
+		let bind = if symbol.global { STB_GLOBAL } else { STB_LOCAL };
+		let name = strtab.add(symbol.name);
+
+		write_symbol(class, &mut symtab, name, (bind << 4) | kind, shndx, symbol.offset, symbol.size);
+		names.push(symbol.name);
+	}
+
+	for name in object.externs() {
+		let offset = strtab.add(name);
+
+		write_symbol(class, &mut symtab, offset, (STB_GLOBAL << 4) | STT_NOTYPE, 0 /* SHN_UNDEF */, 0, 0);
+		names.push(name);
+	}
+
+	// Relocations, split by the section they patch.
+	let mut relocations = [(Section::Text, vec![]), (Section::Data, vec![])];
+
+	for r in &object.relocations {
+		let kind = relocation_type(arch, r.kind)?;
+		let symbol = names.iter().rposition(|&n| n == r.symbol).unwrap() as u64;
+
+		let out = &mut relocations.iter_mut().find(|(s, _)| *s == r.section).unwrap().1;
+
+		match class {
+			Class::Elf32 => {
+				let section = match r.section {
+					Section::Text => &mut text,
+					Section::Data => &mut data
+				};
+
+				let field = &mut section[r.offset as usize..r.offset as usize + 4];
+				let value = u32::from_le_bytes(field.try_into().unwrap()).wrapping_add(r.addend as u32);
+				field.copy_from_slice(&value.to_le_bytes());
+
+				out.u32(r.offset as u32);
+				out.u32(((symbol as u32) << 8) | kind);
+			},
+			Class::Elf64 => {
+				out.u64(r.offset);
+				out.u64((symbol << 32) | kind as u64);
+				out.u64(r.addend as u64);
+			}
+		}
+	}
+
+	let (rel_kind, rel_prefix) = match class {
+		Class::Elf32 => (SHT_REL, ".rel"),
+		Class::Elf64 => (SHT_RELA, ".rela")
+	};
+
+	// Lay out the file, filling in section headers as it goes.
+	let mut shstrtab = StringTable::new();
+	let mut headers = vec![SectionHeader { name: 0, kind: 0, flags: 0, offset: 0, size: 0, link: 0, info: 0, align: 0, entsize: 0 }];
+	let mut out = vec![0; class.header_size()];
+
+	let mut section = |out: &mut Vec<u8>, name: &str, kind: u32, flags: u64, bytes: &[u8], link: u32, info: u32, align: u64, entsize: u64| {
+		out.align(align as usize);
+
+		headers.push(SectionHeader {
+			name: shstrtab.add(name),
+			kind,
+			flags,
+			offset: out.len() as u64,
+			size: bytes.len() as u64,
+			link,
+			info,
+			align,
+			entsize
+		});
+
+		out.extend(bytes);
+	};
+
+	let symbol_size = class.symbol_size() as u64;
+	let relocation_size = class.relocation_size() as u64;
+
+	section(&mut out, ".text", SHT_PROGBITS, SHF_ALLOC | SHF_EXECINSTR, &text, 0, 0, 16, 0);
+	section(&mut out, ".data", SHT_PROGBITS, SHF_ALLOC | SHF_WRITE, &data, 0, 0, 16, 0);
+	section(&mut out, ".symtab", SHT_SYMTAB, 0, &symtab, STRTAB, first_global as u32, 8, symbol_size);
+	section(&mut out, ".strtab", SHT_STRTAB, 0, strtab.bytes(), 0, 0, 1, 0);
+
+	// Without this, linkers assume the object needs an executable stack.
+	section(&mut out, ".note.GNU-stack", SHT_PROGBITS, 0, &[], 0, 0, 1, 0);
+
+	for (target, (name, bytes)) in [(TEXT, (".text", &relocations[0].1)), (DATA, (".data", &relocations[1].1))] {
+		if !bytes.is_empty() {
+			let name = [rel_prefix, name].concat();
+			section(&mut out, &name, rel_kind, SHF_INFO_LINK, bytes, SYMTAB, target as u32, 8, relocation_size);
+		}
+	}
+
+	let shstrndx = headers.len() as u16;
+	let name = shstrtab.add(".shstrtab");
+	headers.push(SectionHeader {
+		name,
+		kind: SHT_STRTAB,
+		flags: 0,
+		offset: out.len() as u64,
+		size: 0,
+		link: 0,
+		info: 0,
+		align: 1,
+		entsize: 0
+	});
+
+	out.extend(shstrtab.bytes());
+	headers.last_mut().unwrap().size = shstrtab.bytes().len() as u64;
+
+	out.align(8);
+	let shoff = out.len() as u64;
+
+	for header in &headers {
+		header.write(class, &mut out);
+	}
+
+	let mut header = vec![];
+	Header {
+		class,
+		kind: ET_REL,
+		machine: Class::machine(arch),
+		entry: 0,
+		phoff: 0,
+		phnum: 0,
+		shoff,
+		shnum: headers.len() as u16,
+		shstrndx
+	}.write(&mut header);
+
+	out[..header.len()].copy_from_slice(&header);
+
+	Ok(out)
+}
+
+/// A static executable, loaded as-is without a linker or dynamic loader.
+///
+/// Segments live at fixed addresses chosen up front, so code can refer to data with absolute addresses.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Executable<'a> {
+	pub arch: Arch,
+	/// Mapped as read + execute at [Executable::text_addr].
+	pub text: &'a [u8],
+	/// Mapped as read + write at [Executable::data_addr].
+	pub data: &'a [u8],
+	/// Zeroed bytes following `data`, which take no space in the file.
+	pub bss: u64,
+	pub text_addr: u64,
+	pub data_addr: u64,
+	/// Offset into `text` where execution starts.
+	pub entry: u64
+}
+
+impl<'a> Executable<'a> {
+	/// Uses the traditional load addresses for `arch`.
+	pub fn new(arch: Arch) -> Self {
+		let (text_addr, data_addr) = match arch {
+			Arch::X86 => (0x0804_9000, 0x0820_0000),
+			Arch::Amd64 => (0x40_1000, 0x60_0000)
+		};
+
+		Self {
+			arch,
+			text: &[],
+			data: &[],
+			bss: 0,
+			text_addr,
+			data_addr,
+			entry: 0
+		}
+	}
+
+	pub fn text(mut self, text: &'a [u8]) -> Self {
+		self.text = text;
+		self
+	}
+
+	pub fn data(mut self, data: &'a [u8]) -> Self {
+		self.data = data;
+		self
+	}
+
+	pub fn bss(mut self, len: u64) -> Self {
+		self.bss = len;
+		self
+	}
+
+	pub fn text_addr(mut self, addr: u64) -> Self {
+		self.text_addr = addr;
+		self
+	}
+
+	pub fn data_addr(mut self, addr: u64) -> Self {
+		self.data_addr = addr;
+		self
+	}
+
+	pub fn entry(mut self, offset: u64) -> Self {
+		self.entry = offset;
+		self
+	}
+}
+
+/// Writes `exe` out as a static ELF executable.
+pub fn write_executable(exe: &Executable) -> ObjectResult<Vec<u8>> {
+	let class = Class::of(exe.arch);
+
+	if exe.entry >= exe.text.len() as u64 {
+		return Err(ObjectError::OutOfBounds(exe.entry));
+	}
+
+	let data_len = exe.data.len() as u64 + exe.bss;
+	let has_data = data_len != 0;
+
+	// Segments are mapped a page at a time, so two sharing a page would have one replace the other.
+	let pages = |addr: u64, len: u64| (addr & !(PAGE - 1), (addr + len).next_multiple_of(PAGE));
+	let (text_start, text_end) = pages(exe.text_addr, exe.text.len() as u64);
+	let (data_start, data_end) = pages(exe.data_addr, data_len);
+	if has_data && text_start < data_end && data_start < text_end {
+		return Err(ObjectError::Overlapping);
+	}
+
+	let phnum = if has_data { 3 } else { 2 };
+
+	// File offsets must match addresses modulo the page size.
+	let headers_end = (class.header_size() + class.program_header_size() * phnum) as u64;
+	let text_offset = headers_end.next_multiple_of(PAGE) + exe.text_addr % PAGE;
+	let data_offset = (text_offset + exe.text.len() as u64).next_multiple_of(PAGE) + exe.data_addr % PAGE;
+
+	let mut out = vec![];
+	Header {
+		class,
+		kind: ET_EXEC,
+		machine: Class::machine(exe.arch),
+		entry: exe.text_addr + exe.entry,
+		phoff: class.header_size() as u64,
+		phnum: phnum as u16,
+		shoff: 0,
+		shnum: 0,
+		shstrndx: 0
+	}.write(&mut out);
+
+	let mut segment = |kind: u32, flags: u32, offset: u64, addr: u64, filesz: u64, memsz: u64, align: u64| {
+		match class {
+			Class::Elf32 => {
+				out.u32(kind);
+				out.u32(offset as u32);
+				out.u32(addr as u32);
+				out.u32(addr as u32);
+				out.u32(filesz as u32);
+				out.u32(memsz as u32);
+				out.u32(flags);
+				out.u32(align as u32);
+			},
+			Class::Elf64 => {
+				out.u32(kind);
+				out.u32(flags);
+				out.u64(offset);
+				out.u64(addr);
+				out.u64(addr);
+				out.u64(filesz);
+				out.u64(memsz);
+				out.u64(align);
+			}
+		}
+	};
+
+	let text_len = exe.text.len() as u64;
+	segment(PT_LOAD, PF_R | PF_X, text_offset, exe.text_addr, text_len, text_len, PAGE);
+
+	if has_data {
+		segment(PT_LOAD, PF_R | PF_W, data_offset, exe.data_addr, exe.data.len() as u64, data_len, PAGE);
+	}
+
+	// Without this, the kernel gives the process an executable stack.
+	segment(PT_GNU_STACK, PF_R | PF_W, 0, 0, 0, 0, 16);
+
+	out.resize(text_offset as usize, 0);
+	out.extend(exe.text);
+
+	if !exe.data.is_empty() {
+		out.resize(data_offset as usize, 0);
+		out.extend(exe.data);
+	}
+
+	Ok(out)
+}
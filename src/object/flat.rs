@@ -0,0 +1,194 @@
+//! Flat binaries, with no headers at all. The bytes are loaded as is at a fixed origin, like a boot sector at `0x7C00`.
+//!
+//! Labels are resolved against the origin when the image is written, so code can refer to them before they're defined.
+
+use super::{ObjectError, ObjectResult};
+
+/// Boot sectors are a single 512 byte sector, ending in [BOOT_SIGNATURE].
+pub const BOOT_SECTOR_SIZE: usize = 512;
+
+/// Marks a sector as bootable to the BIOS.
+pub const BOOT_SIGNATURE: [u8; 2] = [0x55, 0xAA];
+
+/// How a reference to a label gets filled in.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Fixup {
+	/// 8 bit displacement relative to the end of the field, like a short `jmp`.
+	Relative8,
+	/// 16 bit displacement relative to the end of the field.
+	Relative16,
+	/// 32 bit displacement relative to the end of the field.
+	Relative32,
+	/// Absolute 16 bit address, origin included.
+	Absolute16,
+	/// Absolute 32 bit address, origin included.
+	Absolute32,
+	/// Absolute 64 bit address, origin included.
+	Absolute64
+}
+
+impl Fixup {
+	fn width(self) -> usize {
+		match self {
+			Self::Relative8 => 1,
+			Self::Relative16 | Self::Absolute16 => 2,
+			Self::Relative32 | Self::Absolute32 => 4,
+			Self::Absolute64 => 8
+		}
+	}
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct Reference<'a> {
+	/// Offset of the field in the image.
+	offset: usize,
+	label: &'a str,
+	fixup: Fixup
+}
+
+/// A flat image, built up from bytes, labels and padding.
+///
+/// Layout errors, like padding to an offset that was already passed, are reported by [Flat::write].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Flat<'a> {
+	origin: u64,
+	bytes: Vec<u8>,
+	labels: Vec<(&'a str, usize)>,
+	references: Vec<Reference<'a>>,
+	/// First offset that padding couldn't reach.
+	overflow: Option<usize>
+}
+
+impl<'a> Flat<'a> {
+	/// An empty image loaded at `origin`.
+	pub fn new(origin: u64) -> Self {
+		Self {
+			origin,
+			bytes: vec![],
+			labels: vec![],
+			references: vec![],
+			overflow: None
+		}
+	}
+
+	/// An empty image at `0x7C00`, where the BIOS loads boot sectors.
+	pub fn boot_sector() -> Self {
+		Self::new(0x7C00)
+	}
+
+	pub fn origin(&self) -> u64 {
+		self.origin
+	}
+
+	/// Current offset from the start of the image.
+	pub fn len(&self) -> usize {
+		self.bytes.len()
+	}
+
+	pub fn is_empty(&self) -> bool {
+		self.bytes.is_empty()
+	}
+
+	/// Appends bytes to the image.
+	pub fn bytes(mut self, bytes: &[u8]) -> Self {
+		self.bytes.extend_from_slice(bytes);
+		self
+	}
+
+	/// Defines a label at the current offset.
+	pub fn label(mut self, name: &'a str) -> Self {
+		self.labels.push((name, self.bytes.len()));
+		self
+	}
+
+	/// Appends bytes whose last `fixup` sized field refers to `label`.
+	///
+	/// Fields are last in most instructions taking an address or displacement, like `jmpnrd_i16` or `mov_r16_i16`.
+	pub fn reference(self, bytes: &[u8], label: &'a str, fixup: Fixup) -> Self {
+		self.reference_at(bytes, bytes.len().saturating_sub(fixup.width()), label, fixup)
+	}
+
+	/// Appends bytes with a field at offset `at` into them referring to `label`, for instructions like far jumps where it isn't last.
+	pub fn reference_at(mut self, bytes: &[u8], at: usize, label: &'a str, fixup: Fixup) -> Self {
+		self.references.push(Reference {
+			offset: self.bytes.len() + at,
+			label,
+			fixup
+		});
+		self.bytes.extend_from_slice(bytes);
+		self
+	}
+
+	/// Pads with `fill` up to a multiple of `align`.
+	pub fn align(mut self, align: usize, fill: u8) -> Self {
+		self.bytes.resize(self.bytes.len().next_multiple_of(align), fill);
+		self
+	}
+
+	/// Pads with `fill` up to `offset` from the start of the image.
+	pub fn pad_to(mut self, offset: usize, fill: u8) -> Self {
+		if offset < self.bytes.len() {
+			self.overflow.get_or_insert(offset);
+		} else {
+			self.bytes.resize(offset, fill);
+		}
+
+		self
+	}
+
+	/// Pads with zeroes to the end of the sector and adds the [BOOT_SIGNATURE].
+	pub fn boot_signature(self) -> Self {
+		self.pad_to(BOOT_SECTOR_SIZE - BOOT_SIGNATURE.len(), 0)
+			.bytes(&BOOT_SIGNATURE)
+	}
+
+	/// Address of a label, origin included.
+	pub fn address(&self, label: &str) -> Option<u64> {
+		self.labels
+			.iter()
+			.find(|(name, _)| *name == label)
+			.map(|&(_, offset)| self.origin + offset as u64)
+	}
+
+	/// Resolves every reference, returning the finished image.
+	pub fn write(&self) -> ObjectResult<Vec<u8>> {
+		if let Some(offset) = self.overflow {
+			return Err(ObjectError::OutOfBounds(offset as u64));
+		}
+
+		let mut out = self.bytes.clone();
+
+		for r in &self.references {
+			let width = r.fixup.width();
+			if r.offset + width > out.len() {
+				return Err(ObjectError::OutOfBounds(r.offset as u64));
+			}
+
+			let target = self.address(r.label).ok_or(ObjectError::Undefined(r.offset as u64))?;
+			let end = self.origin + (r.offset + width) as u64;
+			let rel = target.wrapping_sub(end) as i64;
+
+			let fits = match r.fixup {
+				Fixup::Relative8 => i8::try_from(rel).is_ok(),
+				Fixup::Relative16 => i16::try_from(rel).is_ok(),
+				Fixup::Relative32 => i32::try_from(rel).is_ok(),
+				Fixup::Absolute16 => u16::try_from(target).is_ok(),
+				Fixup::Absolute32 => u32::try_from(target).is_ok(),
+				Fixup::Absolute64 => true
+			};
+
+			if !fits {
+				return Err(ObjectError::OutOfRange(r.offset as u64));
+			}
+
+			let value = match r.fixup {
+				Fixup::Relative8 | Fixup::Relative16 | Fixup::Relative32 => rel as u64,
+				_ => target
+			};
+
+			out[r.offset..r.offset + width].copy_from_slice(&value.to_le_bytes()[..width]);
+		}
+
+		Ok(out)
+	}
+}
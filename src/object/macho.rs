@@ -0,0 +1,211 @@
+//! Mach-O objects (`MH_OBJECT`), as consumed by Apple's `ld`. Only amd64 is supported.
+//!
+//! Like C compilers targeting macOS, symbols are expected to already carry their leading underscore.
+
+use super::{Arch, Emit, Object, ObjectError, ObjectResult, RelocationKind, Section, StringTable};
+
+const MH_MAGIC_64: u32 = 0xFEED_FACF;
+const MH_OBJECT: u32 = 0x1;
+const CPU_TYPE_X86_64: u32 = 0x0100_0007;
+const CPU_SUBTYPE_X86_64_ALL: u32 = 0x3;
+
+const LC_SEGMENT_64: u32 = 0x19;
+const LC_SYMTAB: u32 = 0x2;
+const LC_DYSYMTAB: u32 = 0xB;
+const LC_BUILD_VERSION: u32 = 0x32;
+
+const S_ATTR_PURE_INSTRUCTIONS: u32 = 0x8000_0000;
+const S_ATTR_SOME_INSTRUCTIONS: u32 = 0x400;
+
+const N_EXT: u8 = 0x01;
+const N_SECT: u8 = 0x0E;
+
+const HEADER_SIZE: usize = 32;
+const SEGMENT_SIZE: usize = 72;
+const SECTION_SIZE: usize = 80;
+const BUILD_VERSION_SIZE: usize = 24;
+const SYMTAB_SIZE: usize = 24;
+const DYSYMTAB_SIZE: usize = 80;
+
+/// The relocation type, whether it's pc relative, log2 of its length, and what to add to the field on top of the addend.
+/// Addends are stored in the field, with pc relative ones already relative to the end of it.
+fn relocation_type(kind: RelocationKind) -> ObjectResult<(u32, bool, u32, i64)> {
+	match kind {
+		RelocationKind::Absolute64 => Ok((0, false, 3, 0)), // X86_64_RELOC_UNSIGNED
+		RelocationKind::Relative32 => Ok((1, true, 2, 4)), // X86_64_RELOC_SIGNED
+		RelocationKind::Branch32 => Ok((2, true, 2, 4)), // X86_64_RELOC_BRANCH
+		RelocationKind::GotRelative32 => Ok((3, true, 2, 4)), // X86_64_RELOC_GOT_LOAD
+		kind => Err(ObjectError::UnsupportedRelocation(Arch::Amd64, kind))
+	}
+}
+
+fn name16(name: &str) -> [u8; 16] {
+	let mut out = [0; 16];
+	out[..name.len()].copy_from_slice(name.as_bytes());
+	out
+}
+
+/// Writes `object` out as a Mach-O object, ready to be passed to a linker.
+///
+/// Symbols named by relocations but not defined in `object` become undefined externals, resolved at link time.
+pub fn write(object: &Object) -> ObjectResult<Vec<u8>> {
+	if object.arch != Arch::Amd64 {
+		return Err(ObjectError::UnsupportedArch(object.arch));
+	}
+
+	object.validate()?;
+
+	let mut text = object.text.to_vec();
+	let mut data = object.data.to_vec();
+
+	// Sections share one address space. __data follows __text.
+	let data_addr = (text.len() as u64).next_multiple_of(16);
+	let section_addr = |section: Section| match section {
+		Section::Text => 0,
+		Section::Data => data_addr
+	};
+
+	// Symbols must be ordered locals, defined externals, then undefined externals.
+	let (locals, globals): (Vec<&super::Symbol>, Vec<&super::Symbol>) = object.symbols.iter().partition(|s| !s.global);
+	let externs = object.externs();
+
+	let names = locals.iter().chain(&globals).map(|s| s.name)
+		.chain(externs.iter().copied())
+		.collect::<Vec<_>>();
+
+	let mut strtab = StringTable::new();
+	let mut symtab = vec![];
+
+	for symbol in locals.iter().chain(&globals) {
+		symtab.u32(strtab.add(symbol.name));
+		symtab.u8(N_SECT | if symbol.global { N_EXT } else { 0 });
+		symtab.u8(match symbol.section { Section::Text => 1, Section::Data => 2 });
+		symtab.u16(0);
+		symtab.u64(section_addr(symbol.section) + symbol.offset);
+	}
+
+	for name in &externs {
+		symtab.u32(strtab.add(name));
+		symtab.u8(N_EXT); // N_UNDF
+		symtab.u8(0); // NO_SECT
+		symtab.u16(0);
+		symtab.u64(0);
+	}
+
+	let mut relocations = [vec![], vec![]];
+
+	for r in &object.relocations {
+		let (kind, pcrel, length, bias) = relocation_type(r.kind)?;
+		let symbol = names.iter().position(|&n| n == r.symbol).unwrap() as u32;
+
+		let (section, out) = match r.section {
+			Section::Text => (&mut text, &mut relocations[0]),
+			Section::Data => (&mut data, &mut relocations[1])
+		};
+
+		let at = r.offset as usize;
+		if length == 3 {
+			let field = &mut section[at..at + 8];
+			let value = u64::from_le_bytes(field.try_into().unwrap()).wrapping_add((r.addend + bias) as u64);
+			field.copy_from_slice(&value.to_le_bytes());
+		} else {
+			let field = &mut section[at..at + 4];
+			let value = u32::from_le_bytes(field.try_into().unwrap()).wrapping_add((r.addend + bias) as u32);
+			field.copy_from_slice(&value.to_le_bytes());
+		}
+
+		out.u32(r.offset as u32);
+		out.u32(symbol | (pcrel as u32) << 24 | length << 25 | 1 << 27 /* r_extern */ | kind << 28);
+	}
+
+	let commands_size = SEGMENT_SIZE + SECTION_SIZE * 2 + BUILD_VERSION_SIZE + SYMTAB_SIZE + DYSYMTAB_SIZE;
+
+	// Lay out everything after the load commands.
+	let text_offset = (HEADER_SIZE + commands_size).next_multiple_of(16);
+	let data_offset = text_offset + data_addr as usize;
+	let relocations_offset = (data_offset + data.len()).next_multiple_of(8);
+	let data_relocations_offset = relocations_offset + relocations[0].len();
+	let symtab_offset = (data_relocations_offset + relocations[1].len()).next_multiple_of(8);
+	let strtab_offset = symtab_offset + symtab.len();
+
+	let mut out = vec![];
+	out.u32(MH_MAGIC_64);
+	out.u32(CPU_TYPE_X86_64);
+	out.u32(CPU_SUBTYPE_X86_64_ALL);
+	out.u32(MH_OBJECT);
+	out.u32(4);
+	out.u32(commands_size as u32);
+	out.u32(0);
+	out.u32(0);
+
+	// A single unnamed segment holding every section, as is usual for objects.
+	let segment_size = data_addr + data.len() as u64;
+	out.u32(LC_SEGMENT_64);
+	out.u32((SEGMENT_SIZE + SECTION_SIZE * 2) as u32);
+	out.extend([0; 16]);
+	out.u64(0);
+	out.u64(segment_size);
+	out.u64(text_offset as u64);
+	out.u64(segment_size);
+	out.u32(7); // rwx
+	out.u32(7);
+	out.u32(2);
+	out.u32(0);
+
+	let sections = [
+		("__text", "__TEXT", 0, text.len(), text_offset, relocations_offset, &relocations[0], S_ATTR_PURE_INSTRUCTIONS | S_ATTR_SOME_INSTRUCTIONS),
+		("__data", "__DATA", data_addr, data.len(), data_offset, data_relocations_offset, &relocations[1], 0)
+	];
+
+	for (name, segment, addr, size, offset, reloff, relocs, flags) in sections {
+		out.extend(name16(name));
+		out.extend(name16(segment));
+		out.u64(addr);
+		out.u64(size as u64);
+		out.u32(offset as u32);
+		out.u32(4); // 2^4 alignment
+		out.u32(if relocs.is_empty() { 0 } else { reloff as u32 });
+		out.u32((relocs.len() / 8) as u32);
+		out.u32(flags);
+		out.u32(0);
+		out.u32(0);
+		out.u32(0);
+	}
+
+	out.u32(LC_BUILD_VERSION);
+	out.u32(BUILD_VERSION_SIZE as u32);
+	out.u32(1); // PLATFORM_MACOS
+	out.u32(0x000A_0D00); // Minimum OS 10.13
+	out.u32(0); // SDK
+	out.u32(0); // No tools
+
+	out.u32(LC_SYMTAB);
+	out.u32(SYMTAB_SIZE as u32);
+	out.u32(symtab_offset as u32);
+	out.u32(names.len() as u32);
+	out.u32(strtab_offset as u32);
+	out.u32(strtab.bytes().len() as u32);
+
+	out.u32(LC_DYSYMTAB);
+	out.u32(DYSYMTAB_SIZE as u32);
+	out.u32(0);
+	out.u32(locals.len() as u32);
+	out.u32(locals.len() as u32);
+	out.u32(globals.len() as u32);
+	out.u32((locals.len() + globals.len()) as u32);
+	out.u32(externs.len() as u32);
+	out.resize(out.len() + DYSYMTAB_SIZE - 32, 0);
+
+	out.resize(text_offset, 0);
+	out.extend(&text);
+	out.resize(data_offset, 0);
+	out.extend(&data);
+	out.resize(relocations_offset, 0);
+	out.extend(&relocations[0]);
+	out.extend(&relocations[1]);
+	out.resize(symtab_offset, 0);
+	out.extend(&symtab);
+	out.extend(strtab.bytes());
+
+	Ok(out)
+}
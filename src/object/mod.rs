@@ -0,0 +1,281 @@
+//! Writers for object files and executables, wrapping assembled code so it can be linked or run outside of this process.
+
+pub mod coff;
+pub mod elf;
+pub mod flat;
+pub mod macho;
+pub mod pe;
+pub mod wasm;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Arch {
+	X86,
+	Amd64
+}
+
+/// Which section something lives in.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Section {
+	Text,
+	Data
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Symbol<'a> {
+	pub name: &'a str,
+	pub section: Section,
+	pub offset: u64,
+	pub size: u64,
+	/// Whether the symbol is visible to other objects when linking.
+	pub global: bool
+}
+
+impl<'a> Symbol<'a> {
+	/// A global function in `.text`.
+	pub fn function(name: &'a str, offset: u64, size: u64) -> Self {
+		Self {
+			name,
+			section: Section::Text,
+			offset,
+			size,
+			global: true
+		}
+	}
+
+	/// A global variable in `.data`.
+	pub fn data(name: &'a str, offset: u64, size: u64) -> Self {
+		Self {
+			name,
+			section: Section::Data,
+			offset,
+			size,
+			global: true
+		}
+	}
+
+	/// Hides the symbol from other objects.
+	pub fn local(mut self) -> Self {
+		self.global = false;
+		self
+	}
+}
+
+/// How a relocation gets applied.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RelocationKind {
+	/// 32 bit displacement relative to the end of the field, like `lea rax, [rip + symbol]`.
+	Relative32,
+	/// 32 bit displacement for `call` / `jmp`, which may go through a PLT.
+	Branch32,
+	/// 32 bit displacement to the symbol's GOT entry, like `mov rax, [rip + symbol@GOTPCREL]`.
+	GotRelative32,
+	/// Absolute 32 bit address.
+	Absolute32,
+	/// Absolute 64 bit address.
+	Absolute64
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Relocation<'a> {
+	/// Section containing the field to patch.
+	pub section: Section,
+	/// Offset of the field inside of its section.
+	pub offset: u64,
+	/// Symbol the field refers to. Names without a matching [Symbol] are treated as external.
+	pub symbol: &'a str,
+	pub kind: RelocationKind,
+	/// Added to the symbol's address. For relative fields this is usually `-4`, since they're relative to the end of the field.
+	pub addend: i64
+}
+
+impl<'a> Relocation<'a> {
+	pub fn new(section: Section, offset: u64, symbol: &'a str, kind: RelocationKind, addend: i64) -> Self {
+		Self {
+			section,
+			offset,
+			symbol,
+			kind,
+			addend
+		}
+	}
+}
+
+/// Assembled code and data, along with the symbols and relocations needed to link it.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Object<'a> {
+	pub arch: Arch,
+	pub text: &'a [u8],
+	pub data: &'a [u8],
+	pub symbols: Vec<Symbol<'a>>,
+	pub relocations: Vec<Relocation<'a>>
+}
+
+impl<'a> Object<'a> {
+	pub fn new(arch: Arch) -> Self {
+		Self {
+			arch,
+			text: &[],
+			data: &[],
+			symbols: vec![],
+			relocations: vec![]
+		}
+	}
+
+	pub fn text(mut self, text: &'a [u8]) -> Self {
+		self.text = text;
+		self
+	}
+
+	pub fn data(mut self, data: &'a [u8]) -> Self {
+		self.data = data;
+		self
+	}
+
+	pub fn symbol(mut self, symbol: Symbol<'a>) -> Self {
+		self.symbols.push(symbol);
+		self
+	}
+
+	pub fn relocation(mut self, relocation: Relocation<'a>) -> Self {
+		self.relocations.push(relocation);
+		self
+	}
+
+	fn section(&self, section: Section) -> &'a [u8] {
+		match section {
+			Section::Text => self.text,
+			Section::Data => self.data
+		}
+	}
+
+	/// Names referenced by relocations without a matching symbol, in order of first use.
+	fn externs(&self) -> Vec<&'a str> {
+		let mut out: Vec<&'a str> = vec![];
+
+		for r in &self.relocations {
+			if !self.symbols.iter().any(|s| s.name == r.symbol) && !out.contains(&r.symbol) {
+				out.push(r.symbol);
+			}
+		}
+
+		out
+	}
+
+	/// Checks that every relocation fits in its section.
+	fn validate(&self) -> ObjectResult<()> {
+		for r in &self.relocations {
+			let width = match r.kind {
+				RelocationKind::Absolute64 => 8,
+				_ => 4
+			};
+
+			if r.offset.checked_add(width).is_none_or(|end| end > self.section(r.section).len() as u64) {
+				return Err(ObjectError::OutOfBounds(r.offset));
+			}
+		}
+
+		for s in &self.symbols {
+			if s.offset > self.section(s.section).len() as u64 {
+				return Err(ObjectError::OutOfBounds(s.offset));
+			}
+		}
+
+		Ok(())
+	}
+}
+
+#[non_exhaustive]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ObjectError {
+	/// The format has no way to express this relocation for this architecture.
+	UnsupportedRelocation(Arch, RelocationKind),
+	/// The format doesn't support this architecture.
+	UnsupportedArch(Arch),
+	/// A symbol, relocation or entry point lies outside of its section.
+	OutOfBounds(u64),
+	/// Segments of an executable overlap in memory.
+	Overlapping,
+	/// The field at this offset refers to a label that was never defined.
+	Undefined(u64),
+	/// The value for the field at this offset doesn't fit in it.
+	OutOfRange(u64),
+	/// An index doesn't refer to anything, like a function using a type that was never added.
+	InvalidIndex(u64)
+}
+
+pub type ObjectResult<T> = Result<T, ObjectError>;
+
+impl core::fmt::Display for ObjectError {
+	fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+		match self {
+			Self::UnsupportedRelocation(arch, kind) => write!(f, "Unsupported relocation {kind:?} for {arch:?}"),
+			Self::UnsupportedArch(arch) => write!(f, "Unsupported architecture {arch:?}"),
+			Self::OutOfBounds(offset) => write!(f, "Offset {offset:#x} is out of bounds of its section"),
+			Self::Overlapping => f.write_str("Segments overlap in memory"),
+			Self::Undefined(offset) => write!(f, "Field at {offset:#x} refers to an undefined label"),
+			Self::OutOfRange(offset) => write!(f, "Value for field at {offset:#x} is out of range"),
+			Self::InvalidIndex(index) => write!(f, "Index {index} doesn't refer to anything")
+		}
+	}
+}
+
+impl core::error::Error for ObjectError {}
+
+/// Little endian helpers for writing out headers.
+pub(crate) trait Emit {
+	fn u8(&mut self, v: u8);
+	fn u16(&mut self, v: u16);
+	fn u32(&mut self, v: u32);
+	fn u64(&mut self, v: u64);
+
+	/// Pads with zeroes up to a multiple of `align`.
+	fn align(&mut self, align: usize);
+}
+
+impl Emit for Vec<u8> {
+	fn u8(&mut self, v: u8) {
+		self.push(v);
+	}
+
+	fn u16(&mut self, v: u16) {
+		self.extend(v.to_le_bytes());
+	}
+
+	fn u32(&mut self, v: u32) {
+		self.extend(v.to_le_bytes());
+	}
+
+	fn u64(&mut self, v: u64) {
+		self.extend(v.to_le_bytes());
+	}
+
+	fn align(&mut self, align: usize) {
+		self.resize(self.len().next_multiple_of(align), 0);
+	}
+}
+
+/// A table of NUL terminated strings, referenced by offset.
+#[derive(Debug)]
+pub(crate) struct StringTable {
+	bytes: Vec<u8>
+}
+
+impl StringTable {
+	/// Starts with an empty string at offset 0.
+	pub(crate) fn new() -> Self {
+		Self {
+			bytes: vec![0]
+		}
+	}
+
+	pub(crate) fn add(&mut self, s: &str) -> u32 {
+		let offset = self.bytes.len() as u32;
+		self.bytes.extend(s.as_bytes());
+		self.bytes.push(0);
+		offset
+	}
+
+	pub(crate) fn bytes(&self) -> &[u8] {
+		&self.bytes
+	}
+}
@@ -0,0 +1,221 @@
+//! PE32 and PE32+ images, the executable format used by Windows.
+//!
+//! Images are minimal: no imports, exports or base relocations, so they load at their preferred base.
+
+use super::coff::{self, SectionHeader};
+use super::{Arch, Emit, ObjectError, ObjectResult};
+
+const SECTION_ALIGNMENT: u32 = 0x1000;
+const FILE_ALIGNMENT: u32 = 0x200;
+
+const IMAGE_FILE_RELOCS_STRIPPED: u16 = 0x1;
+const IMAGE_FILE_EXECUTABLE_IMAGE: u16 = 0x2;
+const IMAGE_FILE_LARGE_ADDRESS_AWARE: u16 = 0x20;
+const IMAGE_FILE_32BIT_MACHINE: u16 = 0x100;
+
+const IMAGE_DLLCHARACTERISTICS_NX_COMPAT: u16 = 0x100;
+
+/// Offset of the `PE\0\0` signature, right after the DOS header.
+const PE_OFFSET: u32 = 0x40;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Subsystem {
+	Native = 1,
+	Gui = 2,
+	Console = 3,
+	EfiApplication = 10
+}
+
+/// A Windows executable image.
+///
+/// `.text` starts at [Image::text_addr], with `.data` following directly after it in memory.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Image<'a> {
+	pub arch: Arch,
+	pub text: &'a [u8],
+	pub data: &'a [u8],
+	pub image_base: u64,
+	/// Offset into `text` where execution starts.
+	pub entry: u64,
+	pub subsystem: Subsystem
+}
+
+impl<'a> Image<'a> {
+	/// Uses the default image base of each architecture: PE32 for x86, PE32+ for amd64.
+	pub fn new(arch: Arch) -> Self {
+		let image_base = match arch {
+			Arch::X86 => 0x40_0000,
+			Arch::Amd64 => 0x1_4000_0000
+		};
+
+		Self {
+			arch,
+			text: &[],
+			data: &[],
+			image_base,
+			entry: 0,
+			subsystem: Subsystem::Console
+		}
+	}
+
+	pub fn text(mut self, text: &'a [u8]) -> Self {
+		self.text = text;
+		self
+	}
+
+	pub fn data(mut self, data: &'a [u8]) -> Self {
+		self.data = data;
+		self
+	}
+
+	pub fn image_base(mut self, base: u64) -> Self {
+		self.image_base = base;
+		self
+	}
+
+	pub fn entry(mut self, offset: u64) -> Self {
+		self.entry = offset;
+		self
+	}
+
+	pub fn subsystem(mut self, subsystem: Subsystem) -> Self {
+		self.subsystem = subsystem;
+		self
+	}
+
+	fn text_rva(&self) -> u32 {
+		SECTION_ALIGNMENT
+	}
+
+	fn data_rva(&self) -> u32 {
+		self.text_rva() + (self.text.len() as u32).max(1).next_multiple_of(SECTION_ALIGNMENT)
+	}
+
+	/// Address `.text` is loaded at.
+	pub fn text_addr(&self) -> u64 {
+		self.image_base + self.text_rva() as u64
+	}
+
+	/// Address `.data` is loaded at. Depends on the length of `text`, so set that first.
+	pub fn data_addr(&self) -> u64 {
+		self.image_base + self.data_rva() as u64
+	}
+}
+
+/// Writes `image` out as a PE32 (x86) or PE32+ (amd64) executable.
+pub fn write(image: &Image) -> ObjectResult<Vec<u8>> {
+	if image.entry >= image.text.len() as u64 {
+		return Err(ObjectError::OutOfBounds(image.entry));
+	}
+
+	let plus = image.arch == Arch::Amd64;
+	let has_data = !image.data.is_empty();
+	let sections = if has_data { 2 } else { 1 };
+
+	let optional_header_size: u16 = if plus { 240 } else { 224 };
+	let headers_size = PE_OFFSET as usize + 4 + 20 + optional_header_size as usize + coff::SECTION_HEADER_SIZE * sections;
+	let headers_size = (headers_size as u32).next_multiple_of(FILE_ALIGNMENT);
+
+	let text_raw_size = (image.text.len() as u32).next_multiple_of(FILE_ALIGNMENT);
+	let data_raw_size = (image.data.len() as u32).next_multiple_of(FILE_ALIGNMENT);
+
+	let image_end = if has_data {
+		image.data_rva() + image.data.len() as u32
+	} else {
+		image.text_rva() + image.text.len() as u32
+	};
+
+	let mut out = vec![];
+
+	// DOS header, which only needs its magic and a pointer to the PE header.
+	out.extend(b"MZ");
+	out.resize(0x3C, 0);
+	out.u32(PE_OFFSET);
+
+	out.extend(b"PE\0\0");
+
+	let characteristics = IMAGE_FILE_EXECUTABLE_IMAGE | IMAGE_FILE_RELOCS_STRIPPED
+		| if plus { IMAGE_FILE_LARGE_ADDRESS_AWARE } else { IMAGE_FILE_32BIT_MACHINE };
+
+	out.u16(coff::machine(image.arch));
+	out.u16(sections as u16);
+	out.u32(0); // Timestamp
+	out.u32(0); // No symbol table
+	out.u32(0);
+	out.u16(optional_header_size);
+	out.u16(characteristics);
+
+	// Fields that are 32 bit in PE32, and 64 bit in PE32+.
+	let word = |out: &mut Vec<u8>, v: u64| if plus { out.u64(v) } else { out.u32(v as u32) };
+
+	out.u16(if plus { 0x20B } else { 0x10B });
+	out.u8(0); // Linker version
+	out.u8(0);
+	out.u32(text_raw_size);
+	out.u32(data_raw_size);
+	out.u32(0);
+	out.u32(image.text_rva() + image.entry as u32);
+	out.u32(image.text_rva());
+
+	if !plus {
+		out.u32(image.data_rva());
+	}
+
+	word(&mut out, image.image_base);
+	out.u32(SECTION_ALIGNMENT);
+	out.u32(FILE_ALIGNMENT);
+	out.u16(6); // OS version
+	out.u16(0);
+	out.u16(0); // Image version
+	out.u16(0);
+	out.u16(6); // Subsystem version
+	out.u16(0);
+	out.u32(0);
+	out.u32(image_end.next_multiple_of(SECTION_ALIGNMENT));
+	out.u32(headers_size);
+	out.u32(0); // Checksum
+	out.u16(image.subsystem as u16);
+	out.u16(IMAGE_DLLCHARACTERISTICS_NX_COMPAT);
+	word(&mut out, 0x10_0000); // Stack reserve
+	word(&mut out, 0x1000); // Stack commit
+	word(&mut out, 0x10_0000); // Heap reserve
+	word(&mut out, 0x1000); // Heap commit
+	out.u32(0);
+	out.u32(16);
+	out.resize(out.len() + 16 * 8, 0); // Data directories, all empty
+
+	SectionHeader {
+		name: *b".text\0\0\0",
+		virtual_size: image.text.len() as u32,
+		virtual_address: image.text_rva(),
+		raw_size: text_raw_size,
+		raw_offset: headers_size,
+		relocations_offset: 0,
+		relocations: 0,
+		characteristics: coff::IMAGE_SCN_CNT_CODE | coff::IMAGE_SCN_MEM_EXECUTE | coff::IMAGE_SCN_MEM_READ
+	}.write(&mut out);
+
+	if has_data {
+		SectionHeader {
+			name: *b".data\0\0\0",
+			virtual_size: image.data.len() as u32,
+			virtual_address: image.data_rva(),
+			raw_size: data_raw_size,
+			raw_offset: headers_size + text_raw_size,
+			relocations_offset: 0,
+			relocations: 0,
+			characteristics: coff::IMAGE_SCN_CNT_INITIALIZED_DATA | coff::IMAGE_SCN_MEM_READ | coff::IMAGE_SCN_MEM_WRITE
+		}.write(&mut out);
+	}
+
+	out.align(FILE_ALIGNMENT as usize);
+	out.extend(image.text);
+	out.align(FILE_ALIGNMENT as usize);
+
+	if has_data {
+		out.extend(image.data);
+		out.align(FILE_ALIGNMENT as usize);
+	}
+
+	Ok(out)
+}
@@ -0,0 +1,238 @@
+//! WebAssembly modules, wrapping function bodies built with [crate::tier::raw::wasm] so browsers and other runtimes can load them.
+//!
+//! Modules are minimal: types, functions, an optional memory and exports. No imports, tables, globals or data.
+
+use super::{ObjectError, ObjectResult};
+use crate::tier::raw::wasm::uleb128;
+
+const MAGIC: [u8; 4] = *b"\0asm";
+const VERSION: u32 = 1;
+
+const SECTION_TYPE: u8 = 1;
+const SECTION_FUNCTION: u8 = 3;
+const SECTION_MEMORY: u8 = 5;
+const SECTION_EXPORT: u8 = 7;
+const SECTION_CODE: u8 = 10;
+
+const FUNC_TYPE: u8 = 0x60;
+
+/// A function signature, as lists of value types like [crate::tier::raw::wasm::I32].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct FuncType<'a> {
+	pub params: &'a [u8],
+	pub results: &'a [u8]
+}
+
+impl<'a> FuncType<'a> {
+	pub fn new(params: &'a [u8], results: &'a [u8]) -> Self {
+		Self { params, results }
+	}
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Function<'a> {
+	/// Index of the signature in [Module::types].
+	pub ty: u32,
+	/// Locals following the parameters, as runs of `count` locals of a value type.
+	pub locals: &'a [(u32, u8)],
+	/// Instructions, including the final `end`.
+	pub body: &'a [u8]
+}
+
+impl<'a> Function<'a> {
+	pub fn new(ty: u32, locals: &'a [(u32, u8)], body: &'a [u8]) -> Self {
+		Self { ty, locals, body }
+	}
+}
+
+/// Linear memory, sized in 64 KiB pages.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Memory {
+	pub min: u32,
+	pub max: Option<u32>
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ExportKind {
+	Function = 0,
+	Memory = 2
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Export<'a> {
+	pub name: &'a str,
+	pub kind: ExportKind,
+	pub index: u32
+}
+
+impl<'a> Export<'a> {
+	pub fn function(name: &'a str, index: u32) -> Self {
+		Self {
+			name,
+			kind: ExportKind::Function,
+			index
+		}
+	}
+
+	pub fn memory(name: &'a str) -> Self {
+		Self {
+			name,
+			kind: ExportKind::Memory,
+			index: 0
+		}
+	}
+}
+
+/// A WebAssembly module. Functions are numbered in the order they're added.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Module<'a> {
+	pub types: Vec<FuncType<'a>>,
+	pub functions: Vec<Function<'a>>,
+	pub memory: Option<Memory>,
+	pub exports: Vec<Export<'a>>
+}
+
+impl<'a> Module<'a> {
+	pub fn new() -> Self {
+		Self {
+			types: vec![],
+			functions: vec![],
+			memory: None,
+			exports: vec![]
+		}
+	}
+
+	pub fn func_type(mut self, ty: FuncType<'a>) -> Self {
+		self.types.push(ty);
+		self
+	}
+
+	pub fn function(mut self, function: Function<'a>) -> Self {
+		self.functions.push(function);
+		self
+	}
+
+	pub fn memory(mut self, min: u32, max: Option<u32>) -> Self {
+		self.memory = Some(Memory { min, max });
+		self
+	}
+
+	pub fn export(mut self, export: Export<'a>) -> Self {
+		self.exports.push(export);
+		self
+	}
+
+	/// Checks that every index refers to something in the module.
+	fn validate(&self) -> ObjectResult<()> {
+		for f in &self.functions {
+			if f.ty as usize >= self.types.len() {
+				return Err(ObjectError::InvalidIndex(f.ty as u64));
+			}
+		}
+
+		for e in &self.exports {
+			let valid = match e.kind {
+				ExportKind::Function => (e.index as usize) < self.functions.len(),
+				ExportKind::Memory => e.index == 0 && self.memory.is_some()
+			};
+
+			if !valid {
+				return Err(ObjectError::InvalidIndex(e.index as u64));
+			}
+		}
+
+		Ok(())
+	}
+}
+
+impl Default for Module<'_> {
+	fn default() -> Self {
+		Self::new()
+	}
+}
+
+fn leb(out: &mut Vec<u8>, v: u32) {
+	out.extend_from_slice(&uleb128(v as u64));
+}
+
+/// Length prefixed bytes, as used for names and function bodies.
+fn bytes(out: &mut Vec<u8>, b: &[u8]) {
+	leb(out, b.len() as u32);
+	out.extend_from_slice(b);
+}
+
+/// Writes a section, skipping it entirely when it has no entries.
+fn section(out: &mut Vec<u8>, id: u8, count: usize, entries: &[u8]) {
+	if count == 0 {
+		return;
+	}
+
+	let mut content = vec![];
+	leb(&mut content, count as u32);
+	content.extend_from_slice(entries);
+
+	out.push(id);
+	bytes(out, &content);
+}
+
+pub fn write(module: &Module) -> ObjectResult<Vec<u8>> {
+	module.validate()?;
+
+	let mut out = vec![];
+	out.extend(MAGIC);
+	out.extend(VERSION.to_le_bytes());
+
+	let mut types = vec![];
+	for ty in &module.types {
+		types.push(FUNC_TYPE);
+		bytes(&mut types, ty.params);
+		bytes(&mut types, ty.results);
+	}
+	section(&mut out, SECTION_TYPE, module.types.len(), &types);
+
+	let mut functions = vec![];
+	for f in &module.functions {
+		leb(&mut functions, f.ty);
+	}
+	section(&mut out, SECTION_FUNCTION, module.functions.len(), &functions);
+
+	if let Some(memory) = module.memory {
+		let mut limits = vec![];
+		match memory.max {
+			Some(max) => {
+				limits.push(0x01);
+				leb(&mut limits, memory.min);
+				leb(&mut limits, max);
+			}
+			None => {
+				limits.push(0x00);
+				leb(&mut limits, memory.min);
+			}
+		}
+		section(&mut out, SECTION_MEMORY, 1, &limits);
+	}
+
+	let mut exports = vec![];
+	for e in &module.exports {
+		bytes(&mut exports, e.name.as_bytes());
+		exports.push(e.kind as u8);
+		leb(&mut exports, e.index);
+	}
+	section(&mut out, SECTION_EXPORT, module.exports.len(), &exports);
+
+	let mut code = vec![];
+	for f in &module.functions {
+		let mut body = vec![];
+		leb(&mut body, f.locals.len() as u32);
+		for &(count, ty) in f.locals {
+			leb(&mut body, count);
+			body.push(ty);
+		}
+		body.extend_from_slice(f.body);
+
+		bytes(&mut code, &body);
+	}
+	section(&mut out, SECTION_CODE, module.functions.len(), &code);
+
+	Ok(out)
+}
@@ -1 +1,2 @@
+pub mod portable;
 pub mod raw;
@@ -0,0 +1,153 @@
+use super::{Backend, Cond, Reg, REGS};
+use crate::tier::raw::aarch64::*;
+
+/// Frame pointer, saved with the link register by the prologue.
+const FP: u8 = 29;
+
+/// Scratch registers, never holding a value across instructions.
+const TMP: u8 = 9;
+const TMP_OFFSET: u8 = 10;
+
+/// Intra procedure call register, holding the address being called.
+const IP0: u8 = 16;
+
+/// Registers saved around a call, already a multiple of 16 bytes.
+const CALL_FRAME: u16 = 8 * REGS as u16;
+
+/// Registers map to the argument registers `x0` onwards.
+fn phys(r: Reg) -> u8 {
+	r.0
+}
+
+/// Loads or stores with whichever offset form reaches `offset`, falling back to a register offset.
+fn access(
+	out: &mut Vec<u8>,
+	(rt, rn, offset): (u8, u8, i32),
+	unscaled: fn(u8, u8, i16) -> [u8; 4],
+	scaled: fn(u8, u8, u16) -> [u8; 4],
+	register: fn(u8, u8, u8, u8, bool) -> [u8; 4]
+) {
+	if (-256..256).contains(&offset) {
+		out.extend(unscaled(rt, rn, offset as i16));
+	} else if offset > 0 && offset % 8 == 0 && offset < 8 * 4096 {
+		out.extend(scaled(rt, rn, offset as u16));
+	} else {
+		out.extend(mov_r64_i64(TMP_OFFSET, offset as i64 as u64));
+		out.extend(register(rt, rn, TMP_OFFSET, EXTEND_LSL, false));
+	}
+}
+
+/// AArch64 with the AAPCS64 calling convention.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Aarch64;
+
+impl Backend for Aarch64 {
+	fn prologue(out: &mut Vec<u8>, _params: usize) {
+		out.extend(stp_r64_pre(FP, LR, SP, -16));
+		out.extend(add_r64_r64_i12(FP, SP, 0));
+	}
+
+	fn mov(out: &mut Vec<u8>, dst: Reg, src: Reg) {
+		if dst != src {
+			out.extend(mov_r64_r64(phys(dst), phys(src)));
+		}
+	}
+
+	fn mov_imm(out: &mut Vec<u8>, dst: Reg, imm: u64) {
+		out.extend(mov_r64_i64(phys(dst), imm));
+	}
+
+	fn load(out: &mut Vec<u8>, dst: Reg, base: Reg, offset: i32) {
+		access(out, (phys(dst), phys(base), offset), ldur_r64_m, ldr_r64_m, ldr_r64_mr);
+	}
+
+	fn store(out: &mut Vec<u8>, base: Reg, offset: i32, src: Reg) {
+		access(out, (phys(src), phys(base), offset), stur_r64_m, str_r64_m, str_r64_mr);
+	}
+
+	fn add(out: &mut Vec<u8>, dst: Reg, a: Reg, b: Reg) {
+		out.extend(add_r64_r64_r64(phys(dst), phys(a), phys(b)));
+	}
+
+	fn sub(out: &mut Vec<u8>, dst: Reg, a: Reg, b: Reg) {
+		out.extend(sub_r64_r64_r64(phys(dst), phys(a), phys(b)));
+	}
+
+	fn mul(out: &mut Vec<u8>, dst: Reg, a: Reg, b: Reg) {
+		out.extend(mul_r64_r64_r64(phys(dst), phys(a), phys(b)));
+	}
+
+	fn branch(out: &mut Vec<u8>, cond: Cond, a: Reg, b: Reg) -> usize {
+		let cond = match cond {
+			Cond::Eq => COND_EQ,
+			Cond::Ne => COND_NE,
+			Cond::Lt => COND_LT,
+			Cond::Le => COND_LE,
+			Cond::Gt => COND_GT,
+			Cond::Ge => COND_GE,
+			Cond::Below => COND_LO,
+			Cond::BelowEq => COND_LS,
+			Cond::Above => COND_HI,
+			Cond::AboveEq => COND_HS
+		};
+
+		out.extend(cmp_r64_r64(phys(a), phys(b)));
+		out.extend(bcond_i19(cond, 0));
+		out.len() - 4
+	}
+
+	fn jump(out: &mut Vec<u8>) -> usize {
+		out.extend(b_i26(0));
+		out.len() - 4
+	}
+
+	fn patch(code: &mut [u8], at: usize, target: usize) -> bool {
+		let word = u32::from_le_bytes([code[at], code[at + 1], code[at + 2], code[at + 3]]);
+		let offset = target as i64 - at as i64;
+
+		// Either a `b.cond` with 19 bits of words or a `b` with 26.
+		let (inst, bits) = if word >> 24 == 0x54 {
+			(bcond_i19((word & 0b1111) as u8, offset as i32), 19)
+		} else {
+			(b_i26(offset as i32), 26)
+		};
+
+		if !(-(1 << (bits + 1))..(1 << (bits + 1))).contains(&offset) {
+			return false;
+		}
+
+		code[at..at + 4].copy_from_slice(&inst);
+		true
+	}
+
+	fn call(out: &mut Vec<u8>, addr: u64, args: &[Reg], ret: Option<Reg>) {
+		// Arguments are read back from the saved registers, since they may overlap the argument registers.
+		out.extend(sub_r64_r64_i12(SP, SP, CALL_FRAME));
+		for r in (0..REGS as u8).step_by(2) {
+			out.extend(stp_r64_m(r, r + 1, SP, 8 * r as i16));
+		}
+
+		for (i, arg) in args.iter().enumerate() {
+			out.extend(ldr_r64_m(i as u8, SP, 8 * arg.0 as u16));
+		}
+
+		out.extend(mov_r64_i64(IP0, addr));
+		out.extend(blr_r64(IP0));
+		out.extend(mov_r64_r64(TMP, 0));
+
+		for r in (0..REGS as u8).step_by(2) {
+			out.extend(ldp_r64_m(r, r + 1, SP, 8 * r as i16));
+		}
+		out.extend(add_r64_r64_i12(SP, SP, CALL_FRAME));
+
+		if let Some(ret) = ret {
+			out.extend(mov_r64_r64(phys(ret), TMP));
+		}
+	}
+
+	fn ret(out: &mut Vec<u8>, value: Reg) {
+		out.extend(mov_r64_r64(0, phys(value)));
+		out.extend(ldp_r64_post(FP, LR, SP, 16));
+		out.extend(crate::tier::raw::aarch64::ret());
+	}
+}
@@ -0,0 +1,97 @@
+use super::{patch_rel32, two_operand, x86_cond, Backend, Cond, Reg, REGS};
+use crate::tier::raw::amd64::*;
+
+/// The System V argument registers, so arguments need no moving.
+const MAP: [u8; REGS] = [RDI, RSI, RDX, RCX, R8, R9];
+
+/// Scratch register, never holding a value across instructions.
+const TMP: u8 = R11;
+
+/// Registers saved around a call, padded so `rsp` stays 16 byte aligned.
+const CALL_FRAME: i32 = 8 * REGS as i32 + 8;
+
+fn phys(r: Reg) -> u8 {
+	MAP[r.0 as usize]
+}
+
+/// amd64 with the System V calling convention.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Amd64;
+
+impl Backend for Amd64 {
+	fn prologue(_out: &mut Vec<u8>, _params: usize) {}
+
+	fn mov(out: &mut Vec<u8>, dst: Reg, src: Reg) {
+		if dst != src {
+			out.extend(mov_r64_r64(phys(dst), phys(src)));
+		}
+	}
+
+	fn mov_imm(out: &mut Vec<u8>, dst: Reg, imm: u64) {
+		out.extend(mov_r64_i64(phys(dst), imm));
+	}
+
+	fn load(out: &mut Vec<u8>, dst: Reg, base: Reg, offset: i32) {
+		out.extend(mov_r64_m64(phys(dst), phys(base), offset));
+	}
+
+	fn store(out: &mut Vec<u8>, base: Reg, offset: i32, src: Reg) {
+		out.extend(mov_m64_r64(phys(base), offset, phys(src)));
+	}
+
+	fn add(out: &mut Vec<u8>, dst: Reg, a: Reg, b: Reg) {
+		two_operand(out, (phys(dst), phys(a), phys(b), TMP), mov_r64_r64, add_r64_r64, true);
+	}
+
+	fn sub(out: &mut Vec<u8>, dst: Reg, a: Reg, b: Reg) {
+		two_operand(out, (phys(dst), phys(a), phys(b), TMP), mov_r64_r64, sub_r64_r64, false);
+	}
+
+	fn mul(out: &mut Vec<u8>, dst: Reg, a: Reg, b: Reg) {
+		two_operand(out, (phys(dst), phys(a), phys(b), TMP), mov_r64_r64, imul_r64_r64, true);
+	}
+
+	fn branch(out: &mut Vec<u8>, cond: Cond, a: Reg, b: Reg) -> usize {
+		out.extend(cmp_r64_r64(phys(a), phys(b)));
+		out.extend(jccnrd_i32(x86_cond(cond), 0));
+		out.len() - 4
+	}
+
+	fn jump(out: &mut Vec<u8>) -> usize {
+		out.extend(jmpnrd_i32(0));
+		out.len() - 4
+	}
+
+	fn patch(code: &mut [u8], at: usize, target: usize) -> bool {
+		patch_rel32(code, at, target)
+	}
+
+	fn call(out: &mut Vec<u8>, addr: u64, args: &[Reg], ret: Option<Reg>) {
+		// Arguments are read back from the saved registers, since they may overlap the argument registers.
+		out.extend(sub_r64_i32(RSP, CALL_FRAME as u32));
+		for (i, &r) in MAP.iter().enumerate() {
+			out.extend(mov_m64_r64(RSP, 8 * i as i32, r));
+		}
+
+		for (i, arg) in args.iter().enumerate() {
+			out.extend(mov_r64_m64(MAP[i], RSP, 8 * arg.0 as i32));
+		}
+
+		out.extend(mov_r64_i64(RAX, addr));
+		out.extend(callnai_r64(RAX));
+
+		for (i, &r) in MAP.iter().enumerate() {
+			out.extend(mov_r64_m64(r, RSP, 8 * i as i32));
+		}
+		out.extend(add_r64_i32(RSP, CALL_FRAME as u32));
+
+		if let Some(ret) = ret {
+			out.extend(mov_r64_r64(phys(ret), RAX));
+		}
+	}
+
+	fn ret(out: &mut Vec<u8>, value: Reg) {
+		out.extend(mov_r64_r64(RAX, phys(value)));
+		out.extend(crate::tier::raw::amd64::ret());
+	}
+}
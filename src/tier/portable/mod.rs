@@ -0,0 +1,267 @@
+//! A small instruction set over virtual registers, assembled for whichever [Backend] is picked.
+//!
+//! Registers hold machine words, 64 bits on [Amd64] and [Aarch64] and 32 bits on [X86].
+//! Each one is pinned to a physical register, and a function's arguments arrive in [R0] onwards.
+//! Generated functions follow [crate::abi::SYSV_AMD64], [crate::abi::CDECL] and [crate::abi::AAPCS64].
+
+mod aarch64;
+mod amd64;
+mod x86;
+
+pub use aarch64::Aarch64;
+pub use amd64::Amd64;
+pub use x86::X86;
+
+/// A virtual register, one of [R0] through [R5].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Reg(u8);
+
+pub const R0: Reg = Reg(0);
+pub const R1: Reg = Reg(1);
+pub const R2: Reg = Reg(2);
+pub const R3: Reg = Reg(3);
+pub const R4: Reg = Reg(4);
+pub const R5: Reg = Reg(5);
+
+/// Number of virtual registers.
+pub const REGS: usize = 6;
+
+/// A position in the code, which can be branched to before it's bound.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Label(usize);
+
+#[non_exhaustive]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AsmError {
+	/// A branch refers to a label that was never bound.
+	Undefined(Label),
+	/// The branch at this offset can't reach its label.
+	OutOfRange(usize)
+}
+
+pub type AsmResult<T> = Result<T, AsmError>;
+
+impl core::fmt::Display for AsmError {
+	fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+		match self {
+			Self::Undefined(label) => write!(f, "Label {} was never bound", label.0),
+			Self::OutOfRange(offset) => write!(f, "Branch at {offset:#x} can't reach its label")
+		}
+	}
+}
+
+impl core::error::Error for AsmError {}
+
+/// Comparisons for [Assembler::branch]. `Lt` through `Ge` are signed, `Below` through `AboveEq` unsigned.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Cond {
+	Eq,
+	Ne,
+	Lt,
+	Le,
+	Gt,
+	Ge,
+	Below,
+	BelowEq,
+	Above,
+	AboveEq
+}
+
+/// An architecture the portable instructions lower to.
+///
+/// Branches are emitted with a placeholder target, and [Backend::patch] fills it in once labels are bound.
+pub trait Backend {
+	/// Sets up the frame, moving `params` arguments into [R0] onwards.
+	fn prologue(out: &mut Vec<u8>, params: usize);
+
+	fn mov(out: &mut Vec<u8>, dst: Reg, src: Reg);
+	/// Truncated to the register size.
+	fn mov_imm(out: &mut Vec<u8>, dst: Reg, imm: u64);
+
+	/// Loads the word at `base` + `offset`.
+	fn load(out: &mut Vec<u8>, dst: Reg, base: Reg, offset: i32);
+	/// Stores a word at `base` + `offset`.
+	fn store(out: &mut Vec<u8>, base: Reg, offset: i32, src: Reg);
+
+	fn add(out: &mut Vec<u8>, dst: Reg, a: Reg, b: Reg);
+	fn sub(out: &mut Vec<u8>, dst: Reg, a: Reg, b: Reg);
+	fn mul(out: &mut Vec<u8>, dst: Reg, a: Reg, b: Reg);
+
+	/// Compares `a` with `b` and branches if `cond` holds, returning the offset to patch.
+	fn branch(out: &mut Vec<u8>, cond: Cond, a: Reg, b: Reg) -> usize;
+	/// Branches unconditionally, returning the offset to patch.
+	fn jump(out: &mut Vec<u8>) -> usize;
+	/// Points the branch at `at` to `target`, returning false if it's out of range.
+	fn patch(code: &mut [u8], at: usize, target: usize) -> bool;
+
+	/// Calls the function at `addr` with `args`, keeping every register but `ret`, which gets the result.
+	fn call(out: &mut Vec<u8>, addr: u64, args: &[Reg], ret: Option<Reg>);
+	/// Tears down the frame and returns `value`.
+	fn ret(out: &mut Vec<u8>, value: Reg);
+}
+
+/// Assembles a single function for `B`.
+#[derive(Debug, Clone)]
+pub struct Assembler<B: Backend> {
+	code: Vec<u8>,
+	labels: Vec<Option<usize>>,
+	/// Offsets to patch, with the label they refer to.
+	fixups: Vec<(usize, Label)>,
+	backend: core::marker::PhantomData<B>
+}
+
+impl<B: Backend> Assembler<B> {
+	/// Starts a function taking `params` arguments, which can be at most [REGS].
+	pub fn new(params: usize) -> Self {
+		assert!(params <= REGS, "Too many parameters");
+
+		let mut code = vec![];
+		B::prologue(&mut code, params);
+
+		Self {
+			code,
+			labels: vec![],
+			fixups: vec![],
+			backend: core::marker::PhantomData
+		}
+	}
+
+	pub fn mov(&mut self, dst: Reg, src: Reg) {
+		B::mov(&mut self.code, dst, src);
+	}
+
+	pub fn mov_imm(&mut self, dst: Reg, imm: u64) {
+		B::mov_imm(&mut self.code, dst, imm);
+	}
+
+	pub fn load(&mut self, dst: Reg, base: Reg, offset: i32) {
+		B::load(&mut self.code, dst, base, offset);
+	}
+
+	pub fn store(&mut self, base: Reg, offset: i32, src: Reg) {
+		B::store(&mut self.code, base, offset, src);
+	}
+
+	pub fn add(&mut self, dst: Reg, a: Reg, b: Reg) {
+		B::add(&mut self.code, dst, a, b);
+	}
+
+	pub fn sub(&mut self, dst: Reg, a: Reg, b: Reg) {
+		B::sub(&mut self.code, dst, a, b);
+	}
+
+	pub fn mul(&mut self, dst: Reg, a: Reg, b: Reg) {
+		B::mul(&mut self.code, dst, a, b);
+	}
+
+	/// Creates a label, to be bound with [Assembler::bind].
+	pub fn label(&mut self) -> Label {
+		self.labels.push(None);
+		Label(self.labels.len() - 1)
+	}
+
+	/// Binds `label` to the current position.
+	pub fn bind(&mut self, label: Label) {
+		assert!(self.labels[label.0].is_none(), "Label bound twice");
+		self.labels[label.0] = Some(self.code.len());
+	}
+
+	/// Branches to `label` if `a` compared with `b` satisfies `cond`.
+	pub fn branch(&mut self, cond: Cond, a: Reg, b: Reg, label: Label) {
+		let at = B::branch(&mut self.code, cond, a, b);
+		self.fixups.push((at, label));
+	}
+
+	pub fn jump(&mut self, label: Label) {
+		let at = B::jump(&mut self.code);
+		self.fixups.push((at, label));
+	}
+
+	/// Calls the function at `addr` with up to [REGS] arguments, storing the result in `ret` if given.
+	/// Every other register is preserved.
+	pub fn call(&mut self, addr: u64, args: &[Reg], ret: Option<Reg>) {
+		assert!(args.len() <= REGS, "Too many arguments");
+		B::call(&mut self.code, addr, args, ret);
+	}
+
+	pub fn ret(&mut self, value: Reg) {
+		B::ret(&mut self.code, value);
+	}
+
+	/// Current length of the code, as an offset from the start of the function.
+	pub fn len(&self) -> usize {
+		self.code.len()
+	}
+
+	pub fn is_empty(&self) -> bool {
+		self.code.is_empty()
+	}
+
+	/// Resolves every branch, returning the code.
+	pub fn finish(mut self) -> AsmResult<Vec<u8>> {
+		for &(at, label) in &self.fixups {
+			let target = self.labels[label.0].ok_or(AsmError::Undefined(label))?;
+
+			if !B::patch(&mut self.code, at, target) {
+				return Err(AsmError::OutOfRange(at));
+			}
+		}
+
+		Ok(self.code)
+	}
+}
+
+/// Lowers `dst = a op b` to a two operand `op`, going through `tmp` when `dst` is `b`.
+fn two_operand<M: AsRef<[u8]>, O: AsRef<[u8]>>(
+	out: &mut Vec<u8>,
+	(dst, a, b, tmp): (u8, u8, u8, u8),
+	mov: fn(u8, u8) -> M,
+	op: fn(u8, u8) -> O,
+	commutative: bool
+) {
+	if dst == b && dst != a {
+		if commutative {
+			out.extend_from_slice(op(dst, a).as_ref());
+		} else {
+			out.extend_from_slice(mov(tmp, b).as_ref());
+			out.extend_from_slice(mov(dst, a).as_ref());
+			out.extend_from_slice(op(dst, tmp).as_ref());
+		}
+
+		return;
+	}
+
+	if dst != a {
+		out.extend_from_slice(mov(dst, a).as_ref());
+	}
+
+	out.extend_from_slice(op(dst, b).as_ref());
+}
+
+/// Patches a 32 bit displacement relative to the end of the field, as used by x86 branches.
+fn patch_rel32(code: &mut [u8], at: usize, target: usize) -> bool {
+	let Ok(rel) = i32::try_from(target as i64 - (at as i64 + 4)) else {
+		return false;
+	};
+
+	code[at..at + 4].copy_from_slice(&rel.to_le_bytes());
+	true
+}
+
+/// Condition code for `jcc` on x86 and amd64.
+fn x86_cond(cond: Cond) -> u8 {
+	use crate::tier::raw::x86::*;
+
+	match cond {
+		Cond::Eq => CC_E,
+		Cond::Ne => CC_NE,
+		Cond::Lt => CC_L,
+		Cond::Le => CC_LE,
+		Cond::Gt => CC_G,
+		Cond::Ge => CC_GE,
+		Cond::Below => CC_B,
+		Cond::BelowEq => CC_BE,
+		Cond::Above => CC_A,
+		Cond::AboveEq => CC_AE
+	}
+}
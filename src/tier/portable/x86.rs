@@ -0,0 +1,119 @@
+use super::{patch_rel32, two_operand, x86_cond, Backend, Cond, Reg, REGS};
+use crate::tier::raw::x86::*;
+
+const MAP: [u8; REGS] = [EAX, ECX, EDX, EBX, ESI, EDI];
+
+/// Scratch register, never holding a value across instructions.
+const TMP: u8 = EBP;
+
+/// Callee saved registers pushed by the prologue, which all get used.
+const SAVED: [u8; 4] = [EBX, ESI, EDI, EBP];
+
+/// Where the first argument is relative to `esp`, past the saved registers and return address.
+const PARAMS: i32 = 4 * SAVED.len() as i32 + 4;
+
+fn phys(r: Reg) -> u8 {
+	MAP[r.0 as usize]
+}
+
+/// 32 bit x86 with the cdecl calling convention.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct X86;
+
+impl Backend for X86 {
+	fn prologue(out: &mut Vec<u8>, params: usize) {
+		for r in SAVED {
+			out.extend(push_r32(r));
+		}
+
+		for (i, &r) in MAP.iter().take(params).enumerate() {
+			out.extend(mov_r32_m32(r, ESP, PARAMS + 4 * i as i32));
+		}
+	}
+
+	fn mov(out: &mut Vec<u8>, dst: Reg, src: Reg) {
+		if dst != src {
+			out.extend(mov_r32_r32(phys(dst), phys(src)));
+		}
+	}
+
+	fn mov_imm(out: &mut Vec<u8>, dst: Reg, imm: u64) {
+		out.extend(mov_r32_i32(phys(dst), imm as u32));
+	}
+
+	fn load(out: &mut Vec<u8>, dst: Reg, base: Reg, offset: i32) {
+		out.extend(mov_r32_m32(phys(dst), phys(base), offset));
+	}
+
+	fn store(out: &mut Vec<u8>, base: Reg, offset: i32, src: Reg) {
+		out.extend(mov_m32_r32(phys(base), offset, phys(src)));
+	}
+
+	fn add(out: &mut Vec<u8>, dst: Reg, a: Reg, b: Reg) {
+		two_operand(out, (phys(dst), phys(a), phys(b), TMP), mov_r32_r32, add_r32_r32, true);
+	}
+
+	fn sub(out: &mut Vec<u8>, dst: Reg, a: Reg, b: Reg) {
+		two_operand(out, (phys(dst), phys(a), phys(b), TMP), mov_r32_r32, sub_r32_r32, false);
+	}
+
+	fn mul(out: &mut Vec<u8>, dst: Reg, a: Reg, b: Reg) {
+		two_operand(out, (phys(dst), phys(a), phys(b), TMP), mov_r32_r32, imul_r32_r32, true);
+	}
+
+	fn branch(out: &mut Vec<u8>, cond: Cond, a: Reg, b: Reg) -> usize {
+		out.extend(cmp_r32_r32(phys(a), phys(b)));
+		out.extend(jccnrd_i32(x86_cond(cond), 0));
+		out.len() - 4
+	}
+
+	fn jump(out: &mut Vec<u8>) -> usize {
+		out.extend(jmpnrd_i32(0));
+		out.len() - 4
+	}
+
+	fn patch(code: &mut [u8], at: usize, target: usize) -> bool {
+		patch_rel32(code, at, target)
+	}
+
+	fn call(out: &mut Vec<u8>, addr: u64, args: &[Reg], ret: Option<Reg>) {
+		// Arguments go below the saved registers. The prologue leaves `esp` 4 short of 16 byte alignment, so the frame pads to match.
+		let saved = 4 * REGS as i32;
+		let size = saved + 4 * args.len() as i32;
+		let size = size + (12 - size).rem_euclid(16);
+		let save = size - saved;
+
+		out.extend(sub_r32_i32(ESP, size as u32));
+		for (i, &r) in MAP.iter().enumerate() {
+			out.extend(mov_m32_r32(ESP, save + 4 * i as i32, r));
+		}
+
+		for (i, arg) in args.iter().enumerate() {
+			out.extend(mov_r32_m32(TMP, ESP, save + 4 * arg.0 as i32));
+			out.extend(mov_m32_r32(ESP, 4 * i as i32, TMP));
+		}
+
+		out.extend(mov_r32_i32(TMP, addr as u32));
+		out.extend(callnai_r32(TMP));
+		out.extend(mov_r32_r32(TMP, EAX));
+
+		for (i, &r) in MAP.iter().enumerate() {
+			out.extend(mov_r32_m32(r, ESP, save + 4 * i as i32));
+		}
+		out.extend(add_r32_i32(ESP, size as u32));
+
+		if let Some(ret) = ret {
+			out.extend(mov_r32_r32(phys(ret), TMP));
+		}
+	}
+
+	fn ret(out: &mut Vec<u8>, value: Reg) {
+		out.extend(mov_r32_r32(EAX, phys(value)));
+
+		for r in SAVED.iter().rev() {
+			out.extend(pop_r32(*r));
+		}
+
+		out.extend(crate::tier::raw::x86::ret());
+	}
+}
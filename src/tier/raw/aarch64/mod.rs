@@ -0,0 +1,161 @@
+//! AArch64, where every instruction is a single little endian 32 bit word.
+//!
+//! Register 31 means either [SP] or [XZR] depending on the instruction. Branch and literal offsets are in bytes, relative to the instruction itself.
+
+/// Stack pointer, for instructions that treat register 31 as `sp` like `add` with an immediate and loads / stores.
+pub const SP: u8 = 31;
+/// Zero register, for instructions that treat register 31 as `xzr`.
+pub const XZR: u8 = 31;
+/// Link register, written by `bl` / `blr` and read by `ret`.
+pub const LR: u8 = 30;
+
+pub const COND_EQ: u8 = 0b0000;
+pub const COND_NE: u8 = 0b0001;
+pub const COND_HS: u8 = 0b0010;
+pub const COND_LO: u8 = 0b0011;
+pub const COND_MI: u8 = 0b0100;
+pub const COND_PL: u8 = 0b0101;
+pub const COND_VS: u8 = 0b0110;
+pub const COND_VC: u8 = 0b0111;
+pub const COND_HI: u8 = 0b1000;
+pub const COND_LS: u8 = 0b1001;
+pub const COND_GE: u8 = 0b1010;
+pub const COND_LT: u8 = 0b1011;
+pub const COND_GT: u8 = 0b1100;
+pub const COND_LE: u8 = 0b1101;
+pub const COND_AL: u8 = 0b1110;
+
+pub const SHIFT_LSL: u8 = 0b00;
+pub const SHIFT_LSR: u8 = 0b01;
+pub const SHIFT_ASR: u8 = 0b10;
+/// Only valid for logical instructions, not `add` / `sub`.
+pub const SHIFT_ROR: u8 = 0b11;
+
+/// Extends for register offset loads and stores.
+pub const EXTEND_UXTW: u8 = 0b010;
+pub const EXTEND_LSL: u8 = 0b011;
+pub const EXTEND_SXTW: u8 = 0b110;
+pub const EXTEND_SXTX: u8 = 0b111;
+
+#[inline]
+const fn reg(r: u8) -> u32 {
+	(r & 0b1_1111) as u32
+}
+
+/// Truncates a signed value to a `bits` wide field.
+#[inline]
+const fn imm(v: i32, bits: u32) -> u32 {
+	(v as u32) & ((1 << bits) - 1)
+}
+
+/// Encodes `imm` as a logical immediate for a `size` bit register, returning the `N:immr:imms` fields.
+///
+/// Only values made of a repeating element, itself a rotated run of ones, can be expressed.
+/// All zeroes and all ones never can.
+pub const fn encode_bitmask(imm: u64, size: u32) -> Option<u32> {
+	let imm = if size == 32 {
+		(imm & 0xFFFF_FFFF) | (imm << 32)
+	} else {
+		imm
+	};
+
+	if imm == 0 || imm == u64::MAX {
+		return None;
+	}
+
+	// Shrink to the smallest repeating element.
+	let mut elt_size: u32 = 64;
+	while elt_size > 2 {
+		let half = elt_size / 2;
+		let mask = (1u64 << half) - 1;
+
+		if imm & mask != (imm >> half) & mask {
+			break;
+		}
+
+		elt_size = half;
+	}
+
+	let mask = if elt_size == 64 { u64::MAX } else { (1u64 << elt_size) - 1 };
+	let elt = imm & mask;
+	let ones = elt.count_ones();
+	let run = (1u64 << ones) - 1;
+
+	// Find how far the run of ones was rotated.
+	let mut rotation = 0;
+	while rotation < elt_size {
+		let rotated = if rotation == 0 {
+			elt
+		} else {
+			((elt >> rotation) | (elt << (elt_size - rotation))) & mask
+		};
+
+		if rotated == run {
+			let immr = (elt_size - rotation) % elt_size;
+			let imms = ((!(elt_size - 1) << 1) | (ones - 1)) & 0b11_1111;
+			let n = (elt_size == 64) as u32;
+			return Some((n << 12) | (immr << 6) | imms);
+		}
+
+		rotation += 1;
+	}
+
+	None
+}
+
+include!(concat!(env!("OUT_DIR"), "/aarch64.rs"));
+
+/// Copies `rm` into `rd`, as `orr rd, xzr, rm`. Register 31 is `xzr` here, use [add_r64_r64_i12] with 0 to copy `sp`.
+#[inline]
+pub const fn mov_r64_r64(rd: u8, rm: u8) -> [u8; 4] {
+	orr_r64_r64_r64(rd, XZR, rm)
+}
+
+#[inline]
+pub const fn mov_r32_r32(rd: u8, rm: u8) -> [u8; 4] {
+	orr_r32_r32_r32(rd, XZR, rm)
+}
+
+/// Loads any 64 bit value with `movz` and three `movk`, so the length never depends on `imm`.
+#[inline]
+pub const fn mov_r64_i64(rd: u8, imm: u64) -> [u8; 16] {
+	let [a0, a1, a2, a3] = movz_r64_i16(rd, imm as u16, 0);
+	let [b0, b1, b2, b3] = movk_r64_i16(rd, (imm >> 16) as u16, 1);
+	let [c0, c1, c2, c3] = movk_r64_i16(rd, (imm >> 32) as u16, 2);
+	let [d0, d1, d2, d3] = movk_r64_i16(rd, (imm >> 48) as u16, 3);
+	[a0, a1, a2, a3, b0, b1, b2, b3, c0, c1, c2, c3, d0, d1, d2, d3]
+}
+
+/// Compares `rn` with `rm`, as `subs xzr, rn, rm`.
+#[inline]
+pub const fn cmp_r64_r64(rn: u8, rm: u8) -> [u8; 4] {
+	subs_r64_r64_r64(XZR, rn, rm)
+}
+
+#[inline]
+pub const fn cmp_r32_r32(rn: u8, rm: u8) -> [u8; 4] {
+	subs_r32_r32_r32(XZR, rn, rm)
+}
+
+/// Compares `rn` with `imm`, as `subs xzr, rn, #imm`.
+#[inline]
+pub const fn cmp_r64_i12(rn: u8, imm: u16) -> [u8; 4] {
+	subs_r64_r64_i12(XZR, rn, imm)
+}
+
+#[inline]
+pub const fn cmp_r32_i12(rn: u8, imm: u16) -> [u8; 4] {
+	subs_r32_r32_i12(XZR, rn, imm)
+}
+
+/// Moves the bits of `rn` into the double precision register `rd`, as `fmov dN, xM`.
+#[inline]
+pub const fn fmov_d_r64(rd: u8, rn: u8) -> [u8; 4] {
+	(0x9E67_0000 | reg(rn) << 5 | reg(rd)).to_le_bytes()
+}
+
+/// Moves the bits of the double precision register `rn` into `rd`.
+#[inline]
+pub const fn fmov_r64_d(rd: u8, rn: u8) -> [u8; 4] {
+	(0x9E66_0000 | reg(rn) << 5 | reg(rd)).to_le_bytes()
+}
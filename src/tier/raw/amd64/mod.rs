@@ -1,6 +1,24 @@
 // amd64 should support all of these.
 pub use crate::tier::raw::x86::compatible::*;
 
+pub const RAX: u8 = 0;
+pub const RCX: u8 = 1;
+pub const RDX: u8 = 2;
+pub const RBX: u8 = 3;
+pub const RSP: u8 = 4;
+pub const RBP: u8 = 5;
+pub const RSI: u8 = 6;
+pub const RDI: u8 = 7;
+/// r8 through r15 need a REX prefix, which only instructions that always emit one can encode.
+pub const R8: u8 = 8;
+pub const R9: u8 = 9;
+pub const R10: u8 = 10;
+pub const R11: u8 = 11;
+pub const R12: u8 = 12;
+pub const R13: u8 = 13;
+pub const R14: u8 = 14;
+pub const R15: u8 = 15;
+
 pub const REX: u8 = 0b0100_0000;
 
 /// 0b0100_1000
@@ -9,4 +27,121 @@ pub const REX_W: u8 = REX + 0b1000;
 /// Override 32-bit default for compatibility with 16 bit functions on amd64.
 pub const COMPAT_16: u8 = 0x66;
 
+/// REX bit extending the ModRM reg field to r8 through r15.
+#[inline]
+pub(crate) const fn rex_r(reg: u8) -> u8 {
+	((reg >> 3) & 1) << 2
+}
+
+/// REX bit extending the ModRM rm field or SIB base to r8 through r15.
+#[inline]
+pub(crate) const fn rex_b(reg: u8) -> u8 {
+	(reg >> 3) & 1
+}
+
 include!(concat!(env!("OUT_DIR"), "/amd64.rs"));
+
+/// Length of the 64 bit instruction at the start of `code`, without disassembling it.
+/// [None] if the opcode doesn't exist in 64 bit mode, or `code` ends before the instruction does.
+pub fn insn_len(code: &[u8]) -> Option<usize> {
+	use crate::tier::raw::x86::decode::{decode, Mode};
+	decode(code, Mode::Long).map(|insn| insn.len)
+}
+
+/// Saves `rsp` into `save` and switches to the stack whose top is at `stack`.
+/// `stack` should be 16 byte aligned to keep calls made on the new stack aligned.
+#[inline]
+pub const fn switch_stack(save: u8, stack: u64) -> [u8; 13] {
+	let [a0, a1, a2] = mov_r64_r64(save, RSP);
+	let [b0, b1, b2, b3, b4, b5, b6, b7, b8, b9] = mov_r64_i64(RSP, stack);
+	[a0, a1, a2, b0, b1, b2, b3, b4, b5, b6, b7, b8, b9]
+}
+
+/// Switches back to the stack saved by [switch_stack].
+#[inline]
+pub const fn restore_stack(save: u8) -> [u8; 3] {
+	mov_r64_r64(RSP, save)
+}
+
+/// Moves the bits of `src` into the low half of `xmm`, like `movq xmm0, rax`.
+#[inline]
+pub const fn movq_x_r64(xmm: u8, src: u8) -> [u8; 5] {
+	[0x66, REX_W | rex_r(xmm) | rex_b(src), 0x0F, 0x6E, mod_rm(MODRM_DIRECT, xmm, src)]
+}
+
+/// Moves the low half of `xmm` into `dst`.
+#[inline]
+pub const fn movq_r64_x(dst: u8, xmm: u8) -> [u8; 5] {
+	[0x66, REX_W | rex_r(xmm) | rex_b(dst), 0x0F, 0x7E, mod_rm(MODRM_DIRECT, xmm, dst)]
+}
+
+/// Copies all of `src` into `dst`, like `movaps xmm1, xmm0`.
+#[inline]
+pub const fn movaps_x_x(dst: u8, src: u8) -> [u8; 4] {
+	[REX | rex_r(dst) | rex_b(src), 0x0F, 0x28, mod_rm(MODRM_DIRECT, dst, src)]
+}
+
+/// Stores all 16 bytes of `xmm` to `[base + disp]`, which needn't be aligned.
+#[inline]
+pub const fn movdqu_m128_x(base: u8, disp: i32, xmm: u8) -> [u8; 10] {
+	let [m, s, d0, d1, d2, d3] = mod_rm_sib(xmm, base, disp);
+	[0xF3, REX | rex_r(xmm) | rex_b(base), 0x0F, 0x7F, m, s, d0, d1, d2, d3]
+}
+
+/// Loads all 16 bytes of `xmm` from `[base + disp]`.
+#[inline]
+pub const fn movdqu_x_m128(xmm: u8, base: u8, disp: i32) -> [u8; 10] {
+	let [m, s, d0, d1, d2, d3] = mod_rm_sib(xmm, base, disp);
+	[0xF3, REX | rex_r(xmm) | rex_b(base), 0x0F, 0x6F, m, s, d0, d1, d2, d3]
+}
+
+/// Jumps to the address in `dst`, like `jmp r11`.
+#[inline]
+pub const fn jmpnai_r64(dst: u8) -> [u8; 3] {
+	[REX | rex_b(dst), 0xFF, mod_rm(MODRM_DIRECT, 4, dst)]
+}
+
+/// Jumps to the address stored at `disp` bytes past the end of this instruction, like `jmp [rip + disp]`.
+#[inline]
+pub const fn jmpnai_rip(disp: i32) -> [u8; 6] {
+	let [d0, d1, d2, d3] = disp.to_le_bytes();
+	[0xFF, mod_rm(0b00, 4, 0b101), d0, d1, d2, d3]
+}
+
+/// Stores `src` at `disp` bytes past the end of this instruction, like `mov [rip + disp], rax`.
+#[inline]
+pub const fn mov_rip_r64(disp: i32, src: u8) -> [u8; 7] {
+	let [d0, d1, d2, d3] = disp.to_le_bytes();
+	[REX_W | rex_r(src), 0x89, mod_rm(0b00, src, 0b101), d0, d1, d2, d3]
+}
+
+/// Moves `src` into control register `cr`, like `mov cr3, rax`.
+/// Control registers are always 64 bit here, the REX prefix is only there to reach `cr8` and r8 through r15.
+#[inline]
+pub const fn mov_cr_r64(cr: u8, src: u8) -> [u8; 4] {
+	[REX | rex_r(cr) | rex_b(src), 0x0F, 0x22, mod_rm(MODRM_DIRECT, cr, src)]
+}
+
+/// Moves control register `cr` into `dst`.
+#[inline]
+pub const fn mov_r64_cr(dst: u8, cr: u8) -> [u8; 4] {
+	[REX | rex_r(cr) | rex_b(dst), 0x0F, 0x20, mod_rm(MODRM_DIRECT, cr, dst)]
+}
+
+/// Moves `src` into debug register `dr`.
+#[inline]
+pub const fn mov_dr_r64(dr: u8, src: u8) -> [u8; 4] {
+	[REX | rex_b(src), 0x0F, 0x23, mod_rm(MODRM_DIRECT, dr, src)]
+}
+
+/// Moves debug register `dr` into `dst`.
+#[inline]
+pub const fn mov_r64_dr(dst: u8, dr: u8) -> [u8; 4] {
+	[REX | rex_b(dst), 0x0F, 0x21, mod_rm(MODRM_DIRECT, dr, dst)]
+}
+
+/// Loads the task register with the selector in `src`.
+#[inline]
+pub const fn ltr_r16(src: u8) -> [u8; 4] {
+	[REX | rex_b(src), 0x0F, 0x00, mod_rm(MODRM_DIRECT, 3, src)]
+}
@@ -0,0 +1,52 @@
+//! A32, the classic 32 bit ARM instruction set.
+//!
+//! Everything is emitted unconditionally, use [cond] to execute it only under a condition.
+
+pub use super::*;
+
+/// Encodes `imm` as an 8 bit value rotated right by an even amount, returning the 12 bit `rotate:imm8` field.
+pub const fn encode_imm(imm: u32) -> Option<u32> {
+	let mut rotate = 0;
+	while rotate < 16 {
+		let v = imm.rotate_left(rotate * 2);
+		if v <= 0xFF {
+			return Some(rotate << 8 | v);
+		}
+
+		rotate += 1;
+	}
+
+	None
+}
+
+/// Replaces the condition of an instruction, like `cond(COND_EQ, add_r32_r32_r32(0, 1, 2))` for `addeq r0, r1, r2`.
+///
+/// Don't use this with [blx_i24], where the condition field is part of the opcode.
+#[inline]
+pub const fn cond(cond: u8, inst: [u8; 4]) -> [u8; 4] {
+	let [a, b, c, d] = inst;
+	[a, b, c, (d & 0x0F) | (cond << 4)]
+}
+
+/// 12 bit offset with the add / subtract bit, for word and byte loads and stores.
+#[inline]
+const fn offset12(offset: i32) -> u32 {
+	((offset >= 0) as u32) << 23 | (offset.unsigned_abs() & 0xFFF)
+}
+
+/// 8 bit offset split into nibbles, with the add / subtract bit, for halfword loads and stores.
+#[inline]
+const fn offset8(offset: i32) -> u32 {
+	let abs = offset.unsigned_abs();
+	((offset >= 0) as u32) << 23 | (abs & 0xF0) << 4 | (abs & 0xF)
+}
+
+include!(concat!(env!("OUT_DIR"), "/arm32_a32.rs"));
+
+/// Loads any 32 bit value with `movw` and `movt`, so the length never depends on `imm`.
+#[inline]
+pub const fn movwt_r32_i32(rd: u8, imm: u32) -> [u8; 8] {
+	let [a0, a1, a2, a3] = movw_r32_i16(rd, imm as u16);
+	let [b0, b1, b2, b3] = movt_r32_i16(rd, (imm >> 16) as u16);
+	[a0, a1, a2, a3, b0, b1, b2, b3]
+}
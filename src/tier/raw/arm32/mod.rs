@@ -0,0 +1,45 @@
+//! 32 bit ARM, split into [a32] where every instruction is a 32 bit word, and [t32] (Thumb-2) mixing 16 and 32 bit instructions.
+//!
+//! Cortex-M cores only run [t32]. Register lists for `push` / `pop` are bitmasks, like `1 << 4 | 1 << LR`.
+//! Branch and literal offsets are in bytes, relative to the instruction itself rather than the pipelined `pc`.
+
+pub const SP: u8 = 13;
+/// Link register, written by `bl` / `blx`.
+pub const LR: u8 = 14;
+pub const PC: u8 = 15;
+
+pub const COND_EQ: u8 = 0b0000;
+pub const COND_NE: u8 = 0b0001;
+pub const COND_HS: u8 = 0b0010;
+pub const COND_LO: u8 = 0b0011;
+pub const COND_MI: u8 = 0b0100;
+pub const COND_PL: u8 = 0b0101;
+pub const COND_VS: u8 = 0b0110;
+pub const COND_VC: u8 = 0b0111;
+pub const COND_HI: u8 = 0b1000;
+pub const COND_LS: u8 = 0b1001;
+pub const COND_GE: u8 = 0b1010;
+pub const COND_LT: u8 = 0b1011;
+pub const COND_GT: u8 = 0b1100;
+pub const COND_LE: u8 = 0b1101;
+pub const COND_AL: u8 = 0b1110;
+
+pub const SHIFT_LSL: u8 = 0b00;
+pub const SHIFT_LSR: u8 = 0b01;
+pub const SHIFT_ASR: u8 = 0b10;
+/// Rotates right, or with an amount of 0 rotates right by one through the carry flag (`rrx`).
+pub const SHIFT_ROR: u8 = 0b11;
+
+#[inline]
+const fn reg(r: u8) -> u32 {
+	(r & 0b1111) as u32
+}
+
+/// Truncates a signed value to a `bits` wide field.
+#[inline]
+const fn imm(v: i32, bits: u32) -> u32 {
+	(v as u32) & ((1 << bits) - 1)
+}
+
+pub mod a32;
+pub mod t32;
@@ -0,0 +1,104 @@
+//! T32 (Thumb-2), mixing 16 bit instructions with 32 bit ones stored as two little endian halfwords.
+//!
+//! 16 bit data processing only reaches r0 to r7 and sets flags outside an IT block, hence names like `adds`.
+//! 32 bit forms of instructions that also have a 16 bit one carry `_w`, like `add_w_r32_r32_r32`.
+
+pub use super::*;
+
+/// Register in r0 to r7, for the 3 bit fields of 16 bit instructions.
+#[inline]
+const fn lo(r: u8) -> u32 {
+	(r & 0b111) as u32
+}
+
+/// Splits a 32 bit instruction into its halfwords, first halfword first.
+#[inline]
+const fn wide(word: u32) -> [u8; 4] {
+	let [a, b] = ((word >> 16) as u16).to_le_bytes();
+	let [c, d] = (word as u16).to_le_bytes();
+	[a, b, c, d]
+}
+
+/// Encodes `imm` as a modified immediate, returning the 12 bit `i:imm3:imm8` field.
+///
+/// These are a byte, a byte repeated in a pattern like `0x00XY00XY`, or a byte with its top bit set rotated anywhere.
+pub const fn encode_imm(imm: u32) -> Option<u32> {
+	if imm <= 0xFF {
+		return Some(imm);
+	}
+
+	let low = imm & 0xFF;
+	if imm == low * 0x0001_0001 {
+		return Some(0x100 | low);
+	}
+
+	let high = (imm >> 8) & 0xFF;
+	if imm == high * 0x0100_0100 {
+		return Some(0x200 | high);
+	}
+
+	if imm == low * 0x0101_0101 {
+		return Some(0x300 | low);
+	}
+
+	let mut rotate = 8;
+	while rotate < 32 {
+		let v = imm.rotate_left(rotate);
+		if v & !0xFF == 0 && v & 0x80 != 0 {
+			return Some(rotate << 7 | (v & 0x7F));
+		}
+
+		rotate += 1;
+	}
+
+	None
+}
+
+/// Places an `i:imm3:imm8` field from [encode_imm] in a 32 bit instruction.
+#[inline]
+const fn spread_imm(imm: u32) -> u32 {
+	(imm >> 11) << 26 | ((imm >> 8) & 0b111) << 12 | (imm & 0xFF)
+}
+
+/// 8 bit offset with the add / subtract bit, for wide loads and stores.
+#[inline]
+const fn imm8(offset: i16) -> u32 {
+	((offset >= 0) as u32) << 9 | (offset.unsigned_abs() as u32 & 0xFF)
+}
+
+/// Offset fields of `b.w` and `bl`, reaching 16 MiB either way.
+#[inline]
+const fn branch24(offset: i32) -> u32 {
+	let v = (offset - 4) >> 1;
+	let s = imm(v >> 23, 1);
+	let j1 = !(imm(v >> 22, 1) ^ s) & 1;
+	let j2 = !(imm(v >> 21, 1) ^ s) & 1;
+	s << 26 | imm(v >> 11, 10) << 16 | j1 << 13 | j2 << 11 | imm(v, 11)
+}
+
+/// Offset fields of a wide conditional branch, reaching 1 MiB either way.
+#[inline]
+const fn branch20(offset: i32) -> u32 {
+	let v = (offset - 4) >> 1;
+	imm(v >> 19, 1) << 26 | imm(v >> 11, 6) << 16 | imm(v >> 17, 1) << 13 | imm(v >> 18, 1) << 11 | imm(v, 11)
+}
+
+include!(concat!(env!("OUT_DIR"), "/arm32_t32.rs"));
+
+/// Compares any two registers, picking the 16 bit form for low registers.
+#[inline]
+pub const fn cmp_r32_r32(rn: u8, rm: u8) -> [u8; 2] {
+	if rn < 8 && rm < 8 {
+		((0x4280 | lo(rm) << 3 | lo(rn)) as u16).to_le_bytes()
+	} else {
+		((0x4500 | (((rn >> 3) & 1) as u32) << 7 | reg(rm) << 3 | lo(rn)) as u16).to_le_bytes()
+	}
+}
+
+/// Loads any 32 bit value with `movw` and `movt`, so the length never depends on `imm`.
+#[inline]
+pub const fn movwt_r32_i32(rd: u8, imm: u32) -> [u8; 8] {
+	let [a0, a1, a2, a3] = movw_r32_i16(rd, imm as u16);
+	let [b0, b1, b2, b3] = movt_r32_i16(rd, (imm >> 16) as u16);
+	[a0, a1, a2, a3, b0, b1, b2, b3]
+}
@@ -0,0 +1,178 @@
+//! RV64GC: the RV64I base with the M, A, F, D and C extensions.
+//!
+//! Names follow the assembly mnemonics, with `.` as `_` and compressed instructions prefixed with `c_`.
+//! Branch and jump offsets are in bytes, relative to the instruction itself.
+//! Compressed instructions with a 3 bit register field only reach x8 to x15.
+
+pub const ZERO: u8 = 0;
+/// Return address, written by `jal` / `jalr`.
+pub const RA: u8 = 1;
+pub const SP: u8 = 2;
+
+/// Rounding modes for floating point instructions.
+pub const RM_RNE: u8 = 0b000;
+pub const RM_RTZ: u8 = 0b001;
+pub const RM_RDN: u8 = 0b010;
+pub const RM_RUP: u8 = 0b011;
+pub const RM_RMM: u8 = 0b100;
+/// Use the mode in `frm`.
+pub const RM_DYN: u8 = 0b111;
+
+/// Memory ordering bits for atomics.
+pub const AQ: u8 = 0b10;
+pub const RL: u8 = 0b01;
+pub const AQRL: u8 = AQ | RL;
+
+/// Predecessor and successor sets for `fence`.
+pub const FENCE_I: u8 = 0b1000;
+pub const FENCE_O: u8 = 0b0100;
+pub const FENCE_R: u8 = 0b0010;
+pub const FENCE_W: u8 = 0b0001;
+pub const FENCE_RW: u8 = FENCE_R | FENCE_W;
+pub const FENCE_IORW: u8 = FENCE_I | FENCE_O | FENCE_RW;
+
+#[inline]
+const fn reg(r: u8) -> u32 {
+	(r & 0b1_1111) as u32
+}
+
+/// Register in x8 to x15, for the 3 bit fields of compressed instructions.
+#[inline]
+const fn creg(r: u8) -> u32 {
+	(r & 0b111) as u32
+}
+
+/// Bits `hi..=lo` of `v`, shifted down to bit 0.
+#[inline]
+const fn bits(v: i32, hi: u32, lo: u32) -> u32 {
+	((v as u32) >> lo) & ((1 << (hi - lo + 1)) - 1)
+}
+
+include!(concat!(env!("OUT_DIR"), "/riscv64.rs"));
+
+/// Longest sequence [li] produces: `lui`, `addiw` and three `slli` / `addi` pairs.
+const LI_MAX: usize = 8;
+
+/// Instructions emitted by [li], which vary in length with the constant.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Li {
+	bytes: [u8; LI_MAX * 4],
+	len: usize
+}
+
+impl Li {
+	const fn push(mut self, inst: [u8; 4]) -> Self {
+		let [b0, b1, b2, b3] = inst;
+		self.bytes[self.len] = b0;
+		self.bytes[self.len + 1] = b1;
+		self.bytes[self.len + 2] = b2;
+		self.bytes[self.len + 3] = b3;
+		self.len += 4;
+		self
+	}
+
+	pub const fn as_slice(&self) -> &[u8] {
+		self.bytes.split_at(self.len).0
+	}
+}
+
+impl core::ops::Deref for Li {
+	type Target = [u8];
+
+	fn deref(&self) -> &[u8] {
+		self.as_slice()
+	}
+}
+
+impl AsRef<[u8]> for Li {
+	fn as_ref(&self) -> &[u8] {
+		self.as_slice()
+	}
+}
+
+/// Sign extends the low 12 bits.
+#[inline]
+const fn lo12(v: i64) -> i64 {
+	(v << 52) >> 52
+}
+
+/// Builds `imm` from its upper bits, then shifts left and tops up with `addi` 12 bits at a time.
+const fn li_chain(rd: u8, imm: i64) -> Li {
+	// Peel off (shift, low 12 bits) steps until what's left fits in 32 bits.
+	let mut steps = [(0u32, 0i64); LI_MAX];
+	let mut depth = 0;
+	let mut val = imm;
+
+	while val != val as i32 as i64 {
+		let lo = lo12(val);
+		let hi = (val as u64).wrapping_add(0x800) >> 12;
+		let mut shift = 12 + hi.trailing_zeros();
+		val = (((hi >> (shift - 12)) << shift) as i64) >> shift;
+
+		// Shift 12 bits less if that lets `lui` supply the zeroes instead of needing an `addi`.
+		if shift > 12 && lo12(val) != val && (val << 12) == (val << 12) as i32 as i64 {
+			shift -= 12;
+			val <<= 12;
+		}
+
+		steps[depth] = (shift, lo);
+		depth += 1;
+	}
+
+	let mut out = Li {
+		bytes: [0; LI_MAX * 4],
+		len: 0
+	};
+
+	let lo = lo12(val);
+	let hi = (val.wrapping_add(0x800) >> 12) as u32;
+
+	if hi != 0 {
+		out = out.push(lui(rd, hi));
+		if lo != 0 {
+			out = out.push(addiw(rd, rd, lo as i32));
+		}
+	} else {
+		out = out.push(addi(rd, ZERO, lo as i32));
+	}
+
+	while depth > 0 {
+		depth -= 1;
+		let (shift, lo) = steps[depth];
+
+		out = out.push(slli(rd, rd, shift as u8));
+		if lo != 0 {
+			out = out.push(addi(rd, rd, lo as i32));
+		}
+	}
+
+	out
+}
+
+/// Loads any 64 bit constant into `rd` with the shortest sequence found.
+///
+/// Values fitting in 32 bits take `lui` and / or `addiw`, anything wider is built up with `slli` and `addi`.
+/// Positive values with leading zeroes may instead be built shifted up, then brought back down with `srli`.
+pub const fn li(rd: u8, imm: i64) -> Li {
+	let mut best = li_chain(rd, imm);
+
+	if best.len > 8 && imm > 0 {
+		let zeros = (imm as u64).leading_zeros();
+		let shifted = (imm as u64) << zeros;
+
+		// The vacated low bits can be filled with anything, ones often give a shorter chain.
+		let candidates = [shifted | ((1 << zeros) - 1), shifted];
+		let mut i = 0;
+
+		while i < candidates.len() {
+			let chain = li_chain(rd, candidates[i] as i64);
+			if chain.len + 4 < best.len {
+				best = chain.push(srli(rd, rd, zeros as u8));
+			}
+
+			i += 1;
+		}
+	}
+
+	best
+}
@@ -0,0 +1,190 @@
+//! WebAssembly bytecode, for the bodies of functions written out with `object::wasm`.
+//!
+//! Names follow the text format with `.` as `_`, and keywords like `if` and `loop` get a trailing `_`.
+//! Immediates are LEB128 encoded, so instructions taking them return an [Inst] whose length depends on the values.
+
+/// Value types, also usable as the result of a block.
+pub const I32: u8 = 0x7F;
+pub const I64: u8 = 0x7E;
+pub const F32: u8 = 0x7D;
+pub const F64: u8 = 0x7C;
+
+/// Block type of a block with no result.
+pub const BLOCK_EMPTY: u8 = 0x40;
+
+/// Longest LEB128 encoding of a 64 bit value.
+const LEB_MAX: usize = 10;
+
+/// Longest instruction here, an opcode followed by a full 64 bit immediate.
+const INST_MAX: usize = 1 + LEB_MAX;
+
+/// A LEB128 encoded integer.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Leb {
+	bytes: [u8; LEB_MAX],
+	len: usize
+}
+
+impl Leb {
+	pub const fn as_slice(&self) -> &[u8] {
+		self.bytes.split_at(self.len).0
+	}
+}
+
+impl core::ops::Deref for Leb {
+	type Target = [u8];
+
+	fn deref(&self) -> &[u8] {
+		self.as_slice()
+	}
+}
+
+impl AsRef<[u8]> for Leb {
+	fn as_ref(&self) -> &[u8] {
+		self.as_slice()
+	}
+}
+
+/// Encodes an unsigned integer, 7 bits at a time with the top bit marking that more follow.
+pub const fn uleb128(mut v: u64) -> Leb {
+	let mut out = Leb { bytes: [0; LEB_MAX], len: 0 };
+
+	loop {
+		let byte = (v & 0x7F) as u8;
+		v >>= 7;
+
+		if v == 0 {
+			out.bytes[out.len] = byte;
+			out.len += 1;
+			return out;
+		}
+
+		out.bytes[out.len] = byte | 0x80;
+		out.len += 1;
+	}
+}
+
+/// Encodes a signed integer, stopping once the sign bit of the last byte matches the rest of the value.
+pub const fn sleb128(mut v: i64) -> Leb {
+	let mut out = Leb { bytes: [0; LEB_MAX], len: 0 };
+
+	loop {
+		let byte = (v & 0x7F) as u8;
+		v >>= 7;
+
+		if (v == 0 && byte & 0x40 == 0) || (v == -1 && byte & 0x40 != 0) {
+			out.bytes[out.len] = byte;
+			out.len += 1;
+			return out;
+		}
+
+		out.bytes[out.len] = byte | 0x80;
+		out.len += 1;
+	}
+}
+
+/// An instruction with LEB128 immediates, which vary in length with their values.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Inst {
+	bytes: [u8; INST_MAX],
+	len: usize
+}
+
+impl Inst {
+	const fn new(opcode: u8) -> Self {
+		let mut bytes = [0; INST_MAX];
+		bytes[0] = opcode;
+		Self { bytes, len: 1 }
+	}
+
+	const fn leb(mut self, leb: Leb) -> Self {
+		let mut i = 0;
+		while i < leb.len {
+			self.bytes[self.len] = leb.bytes[i];
+			self.len += 1;
+			i += 1;
+		}
+
+		self
+	}
+
+	const fn uleb(self, v: u64) -> Self {
+		self.leb(uleb128(v))
+	}
+
+	const fn sleb(self, v: i64) -> Self {
+		self.leb(sleb128(v))
+	}
+
+	pub const fn as_slice(&self) -> &[u8] {
+		self.bytes.split_at(self.len).0
+	}
+}
+
+impl core::ops::Deref for Inst {
+	type Target = [u8];
+
+	fn deref(&self) -> &[u8] {
+		self.as_slice()
+	}
+}
+
+impl AsRef<[u8]> for Inst {
+	fn as_ref(&self) -> &[u8] {
+		self.as_slice()
+	}
+}
+
+include!(concat!(env!("OUT_DIR"), "/wasm.rs"));
+
+/// Calls through `table`, checking the callee against the signature at `ty` in the type section.
+#[inline]
+pub const fn call_indirect(ty: u32, table: u32) -> Inst {
+	Inst::new(0x11).uleb(ty as u64).uleb(table as u64)
+}
+
+#[inline]
+pub const fn i32_const(v: i32) -> Inst {
+	Inst::new(0x41).sleb(v as i64)
+}
+
+#[inline]
+pub const fn i64_const(v: i64) -> Inst {
+	Inst::new(0x42).sleb(v)
+}
+
+#[inline]
+pub const fn f32_const(v: f32) -> [u8; 5] {
+	let [a, b, c, d] = v.to_bits().to_le_bytes();
+	[0x43, a, b, c, d]
+}
+
+#[inline]
+pub const fn f64_const(v: f64) -> [u8; 9] {
+	let [a, b, c, d, e, f, g, h] = v.to_bits().to_le_bytes();
+	[0x44, a, b, c, d, e, f, g, h]
+}
+
+/// Size of memory 0 in 64 KiB pages.
+#[inline]
+pub const fn memory_size() -> [u8; 2] {
+	[0x3F, 0x00]
+}
+
+/// Grows memory 0 by a number of pages, returning the old size or -1.
+#[inline]
+pub const fn memory_grow() -> [u8; 2] {
+	[0x40, 0x00]
+}
+
+/// Copies between two ranges of memory 0, like `memmove`.
+#[inline]
+pub const fn memory_copy() -> [u8; 4] {
+	[0xFC, 0x0A, 0x00, 0x00]
+}
+
+/// Fills a range of memory 0 with a byte, like `memset`.
+#[inline]
+pub const fn memory_fill() -> [u8; 3] {
+	[0xFC, 0x0B, 0x00]
+}
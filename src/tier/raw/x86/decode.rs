@@ -0,0 +1,241 @@
+//! Instruction lengths, found by decoding just the prefixes, opcode and operand layout.
+
+/// Processor mode, deciding default operand and address sizes and which opcodes exist.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum Mode {
+	Real,
+	Protected,
+	Long
+}
+
+/// A relative operand, which moving the instruction invalidates.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum Rel {
+	/// A 32 bit displacement at this offset, from `[rip + disp]`, relative to the end of the instruction.
+	Disp(usize),
+	/// A `call`, `jmp`, `jcc` or `xbegin` with a 32 bit displacement at this offset, relative to the end of the instruction.
+	Branch(usize),
+	/// A branch with an 8 bit displacement in its last byte, given its opcode.
+	Short(u8)
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) struct Insn {
+	pub(crate) len: usize,
+	pub(crate) rel: Option<Rel>,
+	/// Whether execution never falls through to the next instruction, like after a `ret`.
+	pub(crate) ends: bool
+}
+
+/// Whether an opcode of the `0F` map takes an 8 bit immediate, under any prefix.
+fn imm8_0f(op: u8) -> bool {
+	matches!(op, 0x70..=0x73 | 0xA4 | 0xAC | 0xBA | 0xC2 | 0xC4..=0xC6)
+}
+
+/// Decodes the length and relative operand of the instruction at the start of `code`.
+/// Returns [None] for opcodes that don't exist in `mode`, or if `code` ends first.
+pub(crate) fn decode(code: &[u8], mode: Mode) -> Option<Insn> {
+	let long = mode == Mode::Long;
+
+	let mut i = 0;
+	let (mut opsize, mut addrsize, mut rex_w) = (false, false, false);
+
+	loop {
+		match *code.get(i)? {
+			0x66 => opsize = true,
+			0x67 => addrsize = true,
+			0x26 | 0x2E | 0x36 | 0x3E | 0x64 | 0x65 | 0xF0 | 0xF2 | 0xF3 => {}
+			// REX only counts right before the opcode, so any prefix after it cancels it.
+			b @ 0x40..=0x4F if long => {
+				rex_w = b & 0b1000 != 0;
+				i += 1;
+				continue;
+			}
+			_ => break
+		}
+
+		rex_w = false;
+		i += 1;
+	}
+
+	// Immediates sized by the operand size, which stay 32 bit with REX.W, even after a `66` prefix.
+	let z = if rex_w || opsize == (mode == Mode::Real) { 4 } else { 2 };
+	let addr16 = addrsize != (mode == Mode::Real) && !long;
+
+	let op = *code.get(i)?;
+	i += 1;
+
+	let mut rel = None;
+	let mut ends = false;
+
+	// Opcode map, with 0 for one byte opcodes and 1, 2 and 3 for `0F`, `0F 38` and `0F 3A`.
+	let mut map = 0;
+
+	// Outside of 64 bit mode, these only start a VEX or EVEX prefix when a ModRM byte couldn't, as they'd address a register.
+	let extended = match op {
+		0xC4 | 0xC5 | 0x62 => long || (mode == Mode::Protected && code.get(i)? >> 6 == 0b11),
+		// XOP, which otherwise is `pop` with a ModRM.
+		0x8F => code.get(i)? & 0x1F >= 8,
+		_ => false
+	};
+
+	let (modrm, mut imm) = if extended {
+		let (prefix, selected) = match op {
+			0xC5 => (1, 1),
+			0xC4 | 0x8F => (2, code[i] & 0x1F),
+			_ => (3, code[i] & 0b111)
+		};
+
+		i += prefix;
+		map = selected;
+
+		let op = *code.get(i)?;
+		i += 1;
+
+		match (map, op) {
+			// `vzeroupper` and `vzeroall`.
+			(1, 0x77) if prefix < 3 => (false, 0),
+			(1, _) => (true, if imm8_0f(op) { 1 } else { 0 }),
+			(2 | 5 | 6 | 9, _) => (true, 0),
+			(3 | 8, _) => (true, 1),
+			(10, _) => (true, 4),
+			_ => return None
+		}
+	} else if op == 0x0F {
+		let op = *code.get(i)?;
+		i += 1;
+		map = 1;
+
+		match op {
+			0x38 => {
+				code.get(i)?;
+				i += 1;
+				map = 2;
+				(true, 0)
+			}
+			0x3A => {
+				code.get(i)?;
+				i += 1;
+				map = 3;
+				(true, 1)
+			}
+			0x80..=0x8F => {
+				rel = long.then_some(Rel::Branch(i));
+				(false, if long { 4 } else { z })
+			}
+			0x0B => {
+				ends = true;
+				(false, 0)
+			}
+			0x05..=0x09 | 0x0E | 0x30..=0x37 | 0x77 | 0xA0..=0xA2 | 0xA8..=0xAA | 0xC8..=0xCF => (false, 0),
+			// 3DNow!, with its opcode in a trailing byte.
+			0x0F => (true, 1),
+			_ => (true, if imm8_0f(op) { 1 } else { 0 })
+		}
+	} else {
+		match op {
+			0x00..=0x3F => match op & 0b111 {
+				0..=3 => (true, 0),
+				4 => (false, 1),
+				5 => (false, z),
+				// Segment pushes and pops, and BCD adjustments, gone in 64 bit mode.
+				_ if long => return None,
+				_ => (false, 0)
+			},
+			// `inc` and `dec`, as these are REX prefixes in 64 bit mode.
+			0x40..=0x4F => (false, 0),
+			0x50..=0x5F | 0x6C..=0x6F | 0x90..=0x99 | 0x9B..=0x9F | 0xA4..=0xA7 | 0xAA..=0xAF => (false, 0),
+			0xC9 | 0xD7 | 0xEC..=0xEF | 0xF1 | 0xF4 | 0xF5 | 0xF8..=0xFD => (false, 0),
+			0xC3 | 0xCB | 0xCC | 0xCF => {
+				ends = true;
+				(false, 0)
+			}
+			0xC2 | 0xCA => {
+				ends = true;
+				(false, 2)
+			}
+			0x63 | 0x84..=0x8F | 0xD0..=0xD3 | 0xD8..=0xDF | 0xF6 | 0xF7 | 0xFE | 0xFF => (true, 0),
+			0x69 | 0x81 | 0xC7 => (true, z),
+			0x6B | 0x80 | 0x83 | 0xC0 | 0xC1 | 0xC6 => (true, 1),
+			0x68 | 0xA9 => (false, z),
+			0x6A | 0xA8 | 0xB0..=0xB7 | 0xCD | 0xE4..=0xE7 => (false, 1),
+			0xB8..=0xBF => (false, if rex_w { 8 } else { z }),
+			0xA0..=0xA3 => (false, if long { if addrsize { 4 } else { 8 } } else if addr16 { 2 } else { 4 }),
+			0xC8 => (false, 3),
+			0x70..=0x7F | 0xE0..=0xE3 | 0xEB => {
+				rel = Some(Rel::Short(op));
+				ends = op == 0xEB;
+				(false, 1)
+			}
+			0xE8 | 0xE9 => {
+				rel = long.then_some(Rel::Branch(i));
+				ends = op == 0xE9;
+				(false, if long { 4 } else { z })
+			}
+			// Opcodes 64 bit mode reuses or dropped.
+			0x60 | 0x61 | 0xCE | 0xD6 if !long => (false, 0),
+			0x62 | 0xC4 | 0xC5 if !long => (true, 0),
+			0x82 if !long => (true, 1),
+			0xD4 | 0xD5 if !long => (false, 1),
+			0x9A | 0xEA if !long => {
+				ends = op == 0xEA;
+				(false, z + 2)
+			}
+			_ => return None
+		}
+	};
+
+	if modrm {
+		let m = *code.get(i)?;
+		i += 1;
+
+		let (addressing, reg, rm) = (m >> 6, (m >> 3) & 0b111, m & 0b111);
+		if addr16 {
+			match addressing {
+				0b00 if rm == 0b110 => i += 2,
+				0b01 => i += 1,
+				0b10 => i += 2,
+				_ => {}
+			}
+		} else {
+			if addressing != 0b11 && rm == 0b100 {
+				let sib = *code.get(i)?;
+				i += 1;
+
+				if addressing == 0b00 && sib & 0b111 == 0b101 {
+					i += 4;
+				}
+			}
+
+			match addressing {
+				0b00 if rm == 0b101 => {
+					rel = long.then_some(Rel::Disp(i));
+					i += 4;
+				}
+				0b01 => i += 1,
+				0b10 => i += 4,
+				_ => {}
+			}
+		}
+
+		match (map, op, reg) {
+			// `test` is the only one of its group with an immediate.
+			(0, 0xF6, 0 | 1) => imm = 1,
+			(0, 0xF7, 0 | 1) => imm = z,
+			// `xbegin`, whose immediate is the displacement of its fallback.
+			(0, 0xC7, 7) if m == 0xF8 => rel = (long && imm == 4).then_some(Rel::Branch(i)),
+			// Indirect `jmp`.
+			(0, 0xFF, 4 | 5) => ends = true,
+			_ => {}
+		}
+	}
+
+	i += imm;
+	(i <= code.len() && i <= 15).then_some(Insn { len: i, rel, ends })
+}
+
+/// Length of the 32 bit instruction at the start of `code`, without disassembling it.
+/// [None] if the opcode doesn't exist in 32 bit mode, or `code` ends before the instruction does.
+pub fn insn_len(code: &[u8]) -> Option<usize> {
+	decode(code, Mode::Protected).map(|insn| insn.len)
+}
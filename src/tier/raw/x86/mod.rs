@@ -5,6 +5,60 @@ pub(crate) mod prelude {
 	pub(crate) const fn mod_rm(mode: u8, src: u8, dst: u8) -> u8 {
 		(mode << 6) | ((src & 0b111) << 3) | (dst & 0b111)
 	}
+
+	pub(crate) const MODRM_DISP32: u8 = 0b10;
+
+	/// ModRM, SIB and displacement for `[base + disp]`.
+	/// A SIB byte is always used so that every base, `esp` included, encodes to the same length.
+	#[inline]
+	pub(crate) const fn mod_rm_sib(reg: u8, base: u8, disp: i32) -> [u8; 6] {
+		let [d0, d1, d2, d3] = disp.to_le_bytes();
+		[mod_rm(MODRM_DISP32, reg, 0b100), mod_rm(0b00, 0b100, base), d0, d1, d2, d3]
+	}
 }
 
+pub(crate) mod decode;
+pub use decode::insn_len;
+
+pub mod real;
+
+pub const EAX: u8 = 0;
+pub const ECX: u8 = 1;
+pub const EDX: u8 = 2;
+pub const EBX: u8 = 3;
+pub const ESP: u8 = 4;
+pub const EBP: u8 = 5;
+pub const ESI: u8 = 6;
+pub const EDI: u8 = 7;
+
 include!(concat!(env!("OUT_DIR"), "/x86.rs"));
+
+/// Moves `src` into control register `cr`, like `mov cr3, eax`.
+#[inline]
+pub const fn mov_cr_r32(cr: u8, src: u8) -> [u8; 3] {
+	[0x0F, 0x22, mod_rm(MODRM_DIRECT, cr, src)]
+}
+
+/// Moves control register `cr` into `dst`.
+#[inline]
+pub const fn mov_r32_cr(dst: u8, cr: u8) -> [u8; 3] {
+	[0x0F, 0x20, mod_rm(MODRM_DIRECT, cr, dst)]
+}
+
+/// Moves `src` into debug register `dr`.
+#[inline]
+pub const fn mov_dr_r32(dr: u8, src: u8) -> [u8; 3] {
+	[0x0F, 0x23, mod_rm(MODRM_DIRECT, dr, src)]
+}
+
+/// Moves debug register `dr` into `dst`.
+#[inline]
+pub const fn mov_r32_dr(dst: u8, dr: u8) -> [u8; 3] {
+	[0x0F, 0x21, mod_rm(MODRM_DIRECT, dr, dst)]
+}
+
+/// Loads the task register with the selector in `src`.
+#[inline]
+pub const fn ltr_r16(src: u8) -> [u8; 3] {
+	[0x0F, 0x00, mod_rm(MODRM_DIRECT, 3, src)]
+}
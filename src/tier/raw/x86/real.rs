@@ -0,0 +1,96 @@
+//! 16 bit real mode, as used by boot sectors and other code running before protected mode is entered.
+//!
+//! Operands and addresses default to 16 bits here, so it's the 32 bit forms that carry the [OPSIZE] prefix.
+//!
+//! Memory operands are addressed by one of the `ADDR_*` bases plus a 16 bit displacement, like `[bx+si+disp]`.
+
+use super::prelude::*;
+
+/// Operand size override, switching between 16 and 32 bit operands.
+pub const OPSIZE: u8 = 0x66;
+
+// Segment override prefixes, placed before an instruction to address memory through another segment.
+pub const SEG_ES: u8 = 0x26;
+pub const SEG_CS: u8 = 0x2E;
+pub const SEG_SS: u8 = 0x36;
+pub const SEG_DS: u8 = 0x3E;
+pub const SEG_FS: u8 = 0x64;
+pub const SEG_GS: u8 = 0x65;
+
+pub const ADDR_BX_SI: u8 = 0;
+pub const ADDR_BX_DI: u8 = 1;
+pub const ADDR_BP_SI: u8 = 2;
+pub const ADDR_BP_DI: u8 = 3;
+pub const ADDR_SI: u8 = 4;
+pub const ADDR_DI: u8 = 5;
+/// Addresses through `ss` rather than `ds`, like the other `bp` bases.
+pub const ADDR_BP: u8 = 6;
+pub const ADDR_BX: u8 = 7;
+/// No base at all, the displacement is the address.
+pub const ADDR_ABSOLUTE: u8 = 8;
+
+const MODRM_DISP16: u8 = 0b10;
+
+/// ModRM byte and displacement for `[base + disp]`.
+/// Bases always take a 16 bit displacement so every memory form has the same length.
+#[inline]
+const fn mod_rm16(reg: u8, base: u8, disp: u16) -> [u8; 3] {
+	let [d0, d1] = disp.to_le_bytes();
+
+	if base == ADDR_ABSOLUTE {
+		[mod_rm(0b00, reg, 0b110), d0, d1]
+	} else {
+		[mod_rm(MODRM_DISP16, reg, base), d0, d1]
+	}
+}
+
+include!(concat!(env!("OUT_DIR"), "/x86_real.rs"));
+
+/// Loads segment register `dst` from `src`, like `mov ds, ax`.
+#[inline]
+pub const fn mov_s_r16(dst: u8, src: u8) -> [u8; 2] {
+	[0x8E, mod_rm(MODRM_DIRECT, dst, src)]
+}
+
+/// Stores segment register `src` into `dst`, like `mov ax, cs`.
+#[inline]
+pub const fn mov_r16_s(dst: u8, src: u8) -> [u8; 2] {
+	[0x8C, mod_rm(MODRM_DIRECT, src, dst)]
+}
+
+/// Far jump to `segment:offset`, reloading `cs`.
+#[inline]
+pub const fn jmpfad_i16_i16(segment: u16, offset: u16) -> [u8; 5] {
+	let [o0, o1] = offset.to_le_bytes();
+	let [s0, s1] = segment.to_le_bytes();
+	[0xEA, o0, o1, s0, s1]
+}
+
+/// Far jump with a 32 bit offset, as used to enter protected mode after setting `cr0.PE`.
+#[inline]
+pub const fn jmpfad_i16_i32(segment: u16, offset: u32) -> [u8; 8] {
+	let [o0, o1, o2, o3] = offset.to_le_bytes();
+	let [s0, s1] = segment.to_le_bytes();
+	[OPSIZE, 0xEA, o0, o1, o2, o3, s0, s1]
+}
+
+/// Far call to `segment:offset`, pushing `cs` and `ip`.
+#[inline]
+pub const fn callfad_i16_i16(segment: u16, offset: u16) -> [u8; 5] {
+	let [o0, o1] = offset.to_le_bytes();
+	let [s0, s1] = segment.to_le_bytes();
+	[0x9A, o0, o1, s0, s1]
+}
+
+/// Far call with a 32 bit offset.
+#[inline]
+pub const fn callfad_i16_i32(segment: u16, offset: u32) -> [u8; 8] {
+	let [o0, o1, o2, o3] = offset.to_le_bytes();
+	let [s0, s1] = segment.to_le_bytes();
+	[OPSIZE, 0x9A, o0, o1, o2, o3, s0, s1]
+}
+
+/// Length of the 16 bit instruction at the start of `code`, like [insn_len](super::insn_len) in 32 bit mode.
+pub fn insn_len(code: &[u8]) -> Option<usize> {
+	super::decode::decode(code, super::decode::Mode::Real).map(|insn| insn.len)
+}
@@ -0,0 +1,83 @@
+// Expected bytes come from `llvm-mc -triple=aarch64 -show-encoding`.
+
+use dasm::tier::raw::aarch64::*;
+
+const X0: u8 = 0;
+const X1: u8 = 1;
+const X2: u8 = 2;
+const X29: u8 = 29;
+
+#[test]
+fn test_data_processing() {
+	assert_eq!(add_r64_r64_r64(X0, X1, X2), [0x20, 0x00, 0x02, 0x8B]);
+	assert_eq!(add_r32_r32_r32_shift(3, 4, 5, SHIFT_LSL, 7), [0x83, 0x1C, 0x05, 0x0B]);
+	assert_eq!(subs_r64_r64_r64_shift(9, 10, 11, SHIFT_ASR, 63), [0x49, 0xFD, 0x8B, 0xEB]);
+	assert_eq!(and_r64_r64_r64_shift(X0, X1, X2, SHIFT_ROR, 4), [0x20, 0x10, 0xC2, 0x8A]);
+	assert_eq!(eor_r64_r64_r64_shift(X0, X1, X2, SHIFT_LSR, 1), [0x20, 0x04, 0x42, 0xCA]);
+	assert_eq!(add_r64_r64_i12(SP, SP, 16), [0xFF, 0x43, 0x00, 0x91]);
+	assert_eq!(sub_r64_r64_i12(X0, X1, 4095), [0x20, 0xFC, 0x3F, 0xD1]);
+	assert_eq!(cmp_r64_i12(X1, 3), [0x3F, 0x0C, 0x00, 0xF1]);
+	assert_eq!(mov_r64_r64(X0, X1), [0xE0, 0x03, 0x01, 0xAA]);
+	assert_eq!(mul_r64_r64_r64(X0, X1, X2), [0x20, 0x7C, 0x02, 0x9B]);
+	assert_eq!(udiv_r32_r32_r32(X0, X1, X2), [0x20, 0x08, 0xC2, 0x1A]);
+	assert_eq!(movz_r64_i16(X0, 0x1234, 3), [0x80, 0x46, 0xE2, 0xD2]);
+	assert_eq!(movk_r32_i16(X1, 0xFFFF, 1), [0xE1, 0xFF, 0xBF, 0x72]);
+	assert_eq!(movn_r64_i16(X2, 5, 0), [0xA2, 0x00, 0x80, 0x92]);
+}
+
+#[test]
+fn test_bitmask_immediates() {
+	assert_eq!(and_r64_r64_i64(X0, X1, 0xFF), Some([0x20, 0x1C, 0x40, 0x92]));
+	assert_eq!(orr_r64_r64_i64(X0, X1, 0x5555_5555_5555_5555), Some([0x20, 0xF0, 0x00, 0xB2]));
+	assert_eq!(eor_r32_r32_i32(X0, X1, 0xF0F0_F0F0), Some([0x20, 0xCC, 0x04, 0x52]));
+	assert_eq!(ands_r64_r64_i64(X2, 3, 0xFFFF_FFFF_FFFF_FFFE), Some([0x62, 0xF8, 0x7F, 0xF2]));
+	assert_eq!(and_r32_r32_i32(X0, X1, 0x8000_0001), Some([0x20, 0x04, 0x01, 0x12]));
+	assert_eq!(orr_r64_r64_i64(X0, X1, 0x7FFF_FFFE_0000_0000), Some([0x20, 0x74, 0x5F, 0xB2]));
+	assert_eq!(and_r64_r64_i64(X0, X1, 0x3C3C_3C3C_3C3C_3C3C), Some([0x20, 0xCC, 0x06, 0x92]));
+
+	assert_eq!(and_r64_r64_i64(X0, X1, 0), None);
+	assert_eq!(and_r64_r64_i64(X0, X1, u64::MAX), None);
+	assert_eq!(and_r32_r32_i32(X0, X1, u32::MAX), None);
+	assert_eq!(orr_r64_r64_i64(X0, X1, 0x1234), None);
+	assert_eq!(encode_bitmask(0b101, 64), None);
+}
+
+#[test]
+fn test_loads_and_stores() {
+	assert_eq!(ldr_r64_m(X0, X1, 8), [0x20, 0x04, 0x40, 0xF9]);
+	assert_eq!(ldr_r32_m(X0, SP, 16380), [0xE0, 0xFF, 0x7F, 0xB9]);
+	assert_eq!(strb_r32_m(X2, 3, 4095), [0x62, 0xFC, 0x3F, 0x39]);
+	assert_eq!(ldrsw_r64_m(X2, 3, 4), [0x62, 0x04, 0x80, 0xB9]);
+	assert_eq!(str_r64_pre(X0, SP, -16), [0xE0, 0x0F, 0x1F, 0xF8]);
+	assert_eq!(ldr_r64_post(X0, SP, 16), [0xE0, 0x07, 0x41, 0xF8]);
+	assert_eq!(ldur_r64_m(X0, X1, -1), [0x20, 0xF0, 0x5F, 0xF8]);
+	assert_eq!(ldr_r64_mr(X0, X1, X2, EXTEND_LSL, true), [0x20, 0x78, 0x62, 0xF8]);
+	assert_eq!(ldr_r32_mr(X0, X1, X2, EXTEND_SXTW, false), [0x20, 0xC8, 0x62, 0xB8]);
+	assert_eq!(ldr_r64_lit(X0, -8), [0xC0, 0xFF, 0xFF, 0x58]);
+	assert_eq!(stp_r64_pre(X29, LR, SP, -16), [0xFD, 0x7B, 0xBF, 0xA9]);
+	assert_eq!(ldp_r64_post(X29, LR, SP, 16), [0xFD, 0x7B, 0xC1, 0xA8]);
+	assert_eq!(ldp_r64_m(X0, X1, X2, -512), [0x40, 0x04, 0x60, 0xA9]);
+}
+
+#[test]
+fn test_branches() {
+	assert_eq!(b_i26(-4), [0xFF, 0xFF, 0xFF, 0x17]);
+	assert_eq!(bl_i26(0x100), [0x40, 0x00, 0x00, 0x94]);
+	assert_eq!(bcond_i19(COND_LE, -32), [0x0D, 0xFF, 0xFF, 0x54]);
+	assert_eq!(cbz_r64_i19(X0, 12), [0x60, 0x00, 0x00, 0xB4]);
+	assert_eq!(tbnz_i14(X1, 63, -8), [0xC1, 0xFF, 0xFF, 0xB7]);
+	assert_eq!(br_r64(16), [0x00, 0x02, 0x1F, 0xD6]);
+	assert_eq!(blr_r64(8), [0x00, 0x01, 0x3F, 0xD6]);
+	assert_eq!(ret(), [0xC0, 0x03, 0x5F, 0xD6]);
+	assert_eq!(svc_i16(0), [0x01, 0x00, 0x00, 0xD4]);
+	assert_eq!(nop(), [0x1F, 0x20, 0x03, 0xD5]);
+}
+
+#[test]
+fn test_mov_i64() {
+	let words = mov_r64_i64(X0, 0x1122_3344_5566_7788);
+	assert_eq!(words[..4], movz_r64_i16(X0, 0x7788, 0));
+	assert_eq!(words[4..8], movk_r64_i16(X0, 0x5566, 1));
+	assert_eq!(words[8..12], movk_r64_i16(X0, 0x3344, 2));
+	assert_eq!(words[12..], movk_r64_i16(X0, 0x1122, 3));
+}
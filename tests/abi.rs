@@ -0,0 +1,288 @@
+use dasm::abi::*;
+
+#[test]
+fn test_conventions() {
+	use dasm::tier::raw::amd64::*;
+
+	assert_eq!(SYSV_AMD64.args, [RDI, RSI, RDX, RCX, R8, R9]);
+	assert_eq!(WIN64.args, [RCX, RDX, R8, R9]);
+	assert_eq!((WIN64.shadow_space, SYSV_AMD64.red_zone), (32, 128));
+	assert_eq!(FASTCALL.args.len(), 2);
+	assert_eq!((STDCALL.callee_pops, CDECL.callee_pops), (true, false));
+	assert_eq!(AAPCS64.word(), 8);
+	assert_eq!(CDECL.word(), 4);
+}
+
+#[test]
+fn test_amd64_frames() {
+	use dasm::tier::raw::amd64::*;
+
+	// Return address and three registers make 32 bytes, so 20 bytes of locals round up to 32.
+	let frame = Frame::new(SYSV_AMD64).save(&[RBX, R12, R15]).locals(20);
+	assert_eq!(frame.reserved(), 32);
+
+	#[rustfmt::skip]
+	assert_eq!(frame.prologue(), [
+		0x40, 0x53,                               // push rbx
+		0x41, 0x54,                               // push r12
+		0x41, 0x57,                               // push r15
+		0x48, 0x81, 0xEC, 0x20, 0x00, 0x00, 0x00  // sub rsp, 32
+	]);
+
+	#[rustfmt::skip]
+	assert_eq!(frame.epilogue(), [
+		0x48, 0x81, 0xC4, 0x20, 0x00, 0x00, 0x00, // add rsp, 32
+		0x41, 0x5F,                               // pop r15
+		0x41, 0x5C,                               // pop r12
+		0x40, 0x5B,                               // pop rbx
+		0xC3                                      // ret
+	]);
+
+	// Shadow space goes below the locals.
+	let frame = Frame::new(WIN64).save(&[RSI, RDI]).locals(8);
+	assert_eq!((frame.reserved(), frame.locals_offset()), (40, 32));
+}
+
+#[test]
+fn test_x86_frames() {
+	use dasm::tier::raw::x86::*;
+
+	let frame = Frame::new(STDCALL).save(&[EBX, ESI]).locals(4).stack_args(12);
+	assert_eq!(frame.prologue(), [0x53, 0x56, 0x81, 0xEC, 0x04, 0x00, 0x00, 0x00]);
+	assert_eq!(frame.epilogue(), [0x81, 0xC4, 0x04, 0x00, 0x00, 0x00, 0x5E, 0x5B, 0xC2, 0x0C, 0x00]);
+
+	// cdecl leaves the arguments to the caller, and aligns to 16 bytes.
+	let frame = Frame::new(CDECL).save(&[EBX]).stack_args(12);
+	assert_eq!(frame.reserved(), 8);
+	assert!(frame.epilogue().ends_with(&[0x5B, 0xC3]));
+}
+
+#[test]
+fn test_aarch64_frames() {
+	let frame = Frame::new(AAPCS64).save(&[19, 20, 21]).locals(5000);
+	let words = |code: Vec<u8>| -> Vec<u32> {
+		code.chunks(4).map(|w| u32::from_le_bytes(w.try_into().unwrap())).collect()
+	};
+
+	#[rustfmt::skip]
+	assert_eq!(words(frame.prologue()), [
+		0xA9BF7BFD, // stp x29, x30, [sp, #-16]!
+		0x910003FD, // mov x29, sp
+		0xA9BF53F3, // stp x19, x20, [sp, #-16]!
+		0xF81F0FF5, // str x21, [sp, #-16]!
+		0xD13FC3FF, // sub sp, sp, #4080
+		0xD10E83FF  // sub sp, sp, #928
+	]);
+
+	#[rustfmt::skip]
+	assert_eq!(words(frame.epilogue()), [
+		0x913FC3FF, // add sp, sp, #4080
+		0x910E83FF, // add sp, sp, #928
+		0xF84107F5, // ldr x21, [sp], #16
+		0xA8C153F3, // ldp x19, x20, [sp], #16
+		0xA8C17BFD, // ldp x29, x30, [sp], #16
+		0xD65F03C0  // ret
+	]);
+}
+
+#[test]
+#[cfg(all(target_arch = "x86_64", feature = "mmap"))]
+fn test_host_frame() {
+	use dasm::tier::raw::amd64::*;
+
+	// Clobbers every callee saved register, and round trips the argument through a local.
+	let frame = Frame::new(HOST).save(HOST.callee_saved).locals(8);
+	let local = frame.locals_offset() as i32;
+
+	let mut code = frame.prologue();
+	for &r in HOST.callee_saved {
+		code.extend(mov_r64_i64(r, 0xDEAD));
+	}
+	code.extend(mov_m64_r64(RSP, local, HOST.args[0]));
+	code.extend(mov_r64_m64(HOST.ret, RSP, local));
+	code.extend(add_r64_r64(HOST.ret, HOST.args[1]));
+	code.extend(frame.epilogue());
+
+	let map = dasm::mmap::Mmap::exec(code).unwrap();
+	let f = unsafe { map.as_fn::<extern "C" fn(u64, u64) -> u64>() };
+
+	let kept = std::hint::black_box(1234u64);
+	assert_eq!(f.call(40, 2), 42);
+	assert_eq!(std::hint::black_box(kept), 1234);
+}
+
+#[cfg(all(target_arch = "x86_64", feature = "mmap"))]
+extern "C" fn mix(a: u64, b: f64, c: u64, d: f64, e: u64, f: u64, g: u64, h: u64, i: u64) -> f64 {
+	(a as f64) * b - (c as f64) / d + (e + 2 * f + 3 * g + 4 * h + 5 * i) as f64
+}
+
+#[test]
+#[cfg(all(target_arch = "x86_64", feature = "mmap"))]
+fn test_host_call() {
+	// Passes both parameters in the wrong order, with enough integers to spill onto the stack on either convention.
+	let [x, y] = [HOST.args[0], HOST.args[1]];
+	#[rustfmt::skip]
+	let args = [
+		Arg::Int(y), Arg::FloatImm(1.5), Arg::Int(x), Arg::FloatImm(4.0),
+		Arg::Imm(1), Arg::Imm(2), Arg::Imm(3), Arg::Imm(4), Arg::Int(y)
+	];
+
+	let frame = Frame::new(HOST);
+	let mut code = frame.prologue();
+	code.extend(Call::new(HOST, mix as *const () as u64).args(&args).ret(Ret::Float(HOST.ret)).emit());
+	code.extend(frame.epilogue());
+
+	let map = dasm::mmap::Mmap::exec(code).unwrap();
+	let f = unsafe { map.as_fn::<extern "C" fn(u64, u64) -> u64>() };
+
+	// 10 * 1.5 - 8 / 4 + (1 + 4 + 9 + 16 + 50)
+	assert_eq!(f64::from_bits(f.call(8, 10)), 93.0);
+}
+
+#[cfg(all(target_arch = "x86_64", feature = "mmap"))]
+extern "C" fn sub(a: u64, b: u64) -> u64 {
+	a - b
+}
+
+#[test]
+#[cfg(all(target_arch = "x86_64", feature = "mmap"))]
+fn test_preserve() {
+	use dasm::tier::raw::amd64::*;
+
+	let x = HOST.args[0];
+	let mut code = Frame::new(HOST).prologue();
+	code.extend(mov_r64_i64(R10, 1000));
+	code.extend(Call::new(HOST, sub as *const () as u64).args(&[Arg::Int(x), Arg::Imm(3)]).preserve(&[x, R10]).ret(Ret::Int(R11)).emit());
+
+	// (x - 3) + x + 1000
+	code.extend(mov_r64_r64(RAX, R11));
+	code.extend(add_r64_r64(RAX, x));
+	code.extend(add_r64_r64(RAX, R10));
+	code.extend(Frame::new(HOST).epilogue());
+
+	let map = dasm::mmap::Mmap::exec(code).unwrap();
+	let f = unsafe { map.as_fn::<extern "C" fn(u64) -> u64>() };
+	assert_eq!(f.call(10), 1017);
+}
+
+#[test]
+#[cfg(all(target_arch = "x86_64", target_os = "linux", feature = "mmap"))]
+fn test_variadic() {
+	extern "C" {
+		fn snprintf(buf: *mut u8, len: usize, fmt: *const u8, ...) -> i32;
+	}
+
+	// printf only reads vector registers when `al` says there are some.
+	let mut buf = [0u8; 32];
+	let args = [
+		Arg::Imm(buf.as_mut_ptr() as u64),
+		Arg::Imm(buf.len() as u64),
+		Arg::Imm(c"%d %.2f %.1f".as_ptr() as u64),
+		Arg::Imm(-5i64 as u64),
+		Arg::FloatImm(2.25),
+		Arg::Float(HOST.args[0])
+	];
+
+	let frame = Frame::new(HOST);
+	let mut code = frame.prologue();
+	code.extend(Call::new(HOST, snprintf as *const () as u64).args(&args).variadic().ret(Ret::Int(HOST.ret)).emit());
+	code.extend(frame.epilogue());
+
+	let map = dasm::mmap::Mmap::exec(code).unwrap();
+	let f = unsafe { map.as_fn::<extern "C" fn(u64) -> u64>() };
+	let len = f.call(0.5f64.to_bits()) as usize;
+	assert_eq!(&buf[..len], b"-5 2.25 0.5");
+}
+
+#[test]
+fn test_win64_variadic() {
+	use dasm::tier::raw::amd64::*;
+
+	// Variadic floats go in both the vector and the integer register of their position, above 32 bytes of shadow space.
+	let code = Call::new(WIN64, 0x1000).args(&[Arg::Imm(7), Arg::FloatImm(1.0)]).variadic().emit();
+
+	let mut expected = vec![0x48, 0x81, 0xEC, 0x30, 0x00, 0x00, 0x00];
+	expected.extend(mov_r64_i64(RCX, 7));
+	expected.extend(mov_r64_i64(RDX, 1.0f64.to_bits()));
+	expected.extend(movq_x_r64(1, RDX));
+	expected.extend(mov_r64_i64(R11, 0x1000));
+	expected.extend(callnai_r64(R11));
+	expected.extend([0x48, 0x81, 0xC4, 0x30, 0x00, 0x00, 0x00]);
+	assert_eq!(code, expected);
+}
+
+#[test]
+fn test_stdcall() {
+	use dasm::tier::raw::x86::*;
+
+	// Spills `ecx`, stages `edx` and puts back the 12 bytes the callee pops.
+	let code = Call::new(STDCALL, 0x401000)
+		.args(&[Arg::Int(EDX), Arg::FloatImm(-2.0)])
+		.preserve(&[ECX])
+		.ret(Ret::Int(EDX))
+		.emit();
+
+	#[rustfmt::skip]
+	assert_eq!(code, [
+		0x81, 0xEC, 0x18, 0x00, 0x00, 0x00,             // sub esp, 24
+		0x89, 0x8C, 0x24, 0x14, 0x00, 0x00, 0x00,       // mov [esp+20], ecx
+		0x89, 0x94, 0x24, 0x0C, 0x00, 0x00, 0x00,       // mov [esp+12], edx
+		0x8B, 0x84, 0x24, 0x0C, 0x00, 0x00, 0x00,       // mov eax, [esp+12]
+		0x89, 0x84, 0x24, 0x00, 0x00, 0x00, 0x00,       // mov [esp], eax
+		0xB8, 0x00, 0x00, 0x00, 0x00,                   // mov eax, 0
+		0x89, 0x84, 0x24, 0x04, 0x00, 0x00, 0x00,       // mov [esp+4], eax
+		0xB8, 0x00, 0x00, 0x00, 0xC0,                   // mov eax, 0xC0000000
+		0x89, 0x84, 0x24, 0x08, 0x00, 0x00, 0x00,       // mov [esp+8], eax
+		0xB8, 0x00, 0x10, 0x40, 0x00,                   // mov eax, 0x401000
+		0xFF, 0xD0,                                     // call eax
+		0x81, 0xEC, 0x0C, 0x00, 0x00, 0x00,             // sub esp, 12
+		0x89, 0x84, 0x24, 0x0C, 0x00, 0x00, 0x00,       // mov [esp+12], eax
+		0x8B, 0x8C, 0x24, 0x14, 0x00, 0x00, 0x00,       // mov ecx, [esp+20]
+		0x8B, 0x94, 0x24, 0x0C, 0x00, 0x00, 0x00,       // mov edx, [esp+12]
+		0x81, 0xC4, 0x18, 0x00, 0x00, 0x00              // add esp, 24
+	]);
+}
+
+#[test]
+fn test_aarch64_call() {
+	let words = |code: Vec<u8>| -> Vec<u32> {
+		code.chunks(4).map(|w| u32::from_le_bytes(w.try_into().unwrap())).collect()
+	};
+
+	// Swaps x0 and x1 through the staging slots, and turns the integer in x2 into d0.
+	let code = Call::new(AAPCS64, 0x1234)
+		.args(&[Arg::Int(1), Arg::Int(0), Arg::Float(2)])
+		.ret(Ret::Float(3))
+		.emit();
+
+	#[rustfmt::skip]
+	assert_eq!(words(code), [
+		0xD10083FF, // sub sp, sp, #32
+		0xF90003E1, // str x1, [sp]
+		0xF90007E0, // str x0, [sp, #8]
+		0xF9000BE2, // str x2, [sp, #16]
+		0xF94003E0, // ldr x0, [sp]
+		0xF94007E1, // ldr x1, [sp, #8]
+		0xF9400BF0, // ldr x16, [sp, #16]
+		0x9E670200, // fmov d0, x16
+		0xD2824690, // mov x16, #0x1234
+		0xF2A00010, // movk x16, #0, lsl #16
+		0xF2C00010, // movk x16, #0, lsl #32
+		0xF2E00010, // movk x16, #0, lsl #48
+		0xD63F0200, // blr x16
+		0x9E660000, // fmov x0, d0
+		0xF90003E0, // str x0, [sp]
+		0xF94003E3, // ldr x3, [sp]
+		0x910083FF  // add sp, sp, #32
+	]);
+}
+
+#[test]
+fn test_aarch64_call_limits() {
+	// 4096 stack arguments reach the last slot `ldr` and `str` can address, one more is out of reach.
+	let args = vec![Arg::Imm(0); 8 + 4097];
+	assert!(!Call::new(AAPCS64, 0).args(&args[..8 + 4096]).emit().is_empty());
+
+	let emit = std::panic::catch_unwind(|| Call::new(AAPCS64, 0).args(&args).emit());
+	assert!(emit.is_err());
+}
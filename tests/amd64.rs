@@ -1,17 +1,14 @@
 #![cfg(target_arch = "x86_64")]
 
-const RAX: u8 = 0;
-const RCX: u8 = 1;
-const RDX: u8 = 2;
-const RSI: u8 = 6; // Arg #2
-const RDI: u8 = 7; // Arg #1
+use dasm::abi::HOST;
+use dasm::tier::raw::amd64::{RAX, RCX, RDX, RDI, RSI};
 
 #[test]
 #[cfg(target_os = "linux")]
 fn test_print() {
 	let message = b"Hello, world!\n";
 
-	let map = dasm::mmap::Mmap::exec(&[
+	let map = dasm::mmap::Mmap::exec([
 		&dasm::tier::raw::amd64::mov_r64_i64(RDI, 1) as &[u8],
 		&dasm::tier::raw::amd64::mov_r64_i64(RAX, 1),
 		&dasm::tier::raw::amd64::mov_r64_i64(RSI, message.as_ptr() as _),
@@ -21,8 +18,8 @@ fn test_print() {
 		&dasm::tier::raw::amd64::ret()
 	].concat()).expect("Failed to mmap");
 
-	let f: extern "C" fn() = unsafe { std::mem::transmute(map.as_ptr()) };
-	f();
+	let f = unsafe { map.as_fn::<extern "C" fn()>() };
+	f.call();
 
 	// TODO: Verify output by intercepting it
 }
@@ -30,15 +27,94 @@ fn test_print() {
 #[test]
 fn test_adder() {
 	let adder = dasm::mmap::Mmap::exec([
-		&dasm::tier::raw::amd64::mov_r64_r64(RAX, RDI) as &[u8],
-		&dasm::tier::raw::amd64::mov_r64_r64(RCX, RSI),
-		&dasm::tier::raw::amd64::add_r64_r64(RAX, RCX),
+		&dasm::tier::raw::amd64::mov_r64_r64(HOST.ret, HOST.args[0]) as &[u8],
+		&dasm::tier::raw::amd64::mov_r64_r64(RCX, HOST.args[1]),
+		&dasm::tier::raw::amd64::add_r64_r64(HOST.ret, RCX),
 		&dasm::tier::raw::amd64::ret()
 	].concat()).unwrap();
 
-	let adder: extern "C" fn(u64, u64) -> u64 = unsafe { std::mem::transmute(adder.as_ptr()) };
+	let adder = unsafe { adder.as_fn::<extern "C" fn(u64, u64) -> u64>() };
 
-	assert_eq!(adder(64, 64), 128);
-	assert_eq!(adder(0, 0), 0);
-	assert_eq!(adder((-1i64) as u64, 2) as i64, 1);
+	assert_eq!(adder.call(64, 64), 128);
+	assert_eq!(adder.call(0, 0), 0);
+	assert_eq!(adder.call((-1i64) as u64, 2) as i64, 1);
+}
+
+#[test]
+fn test_extended_registers() {
+	use dasm::tier::raw::amd64::*;
+
+	assert_eq!(mov_r64_r64(R8, RAX), [0x4C, 0x8B, 0xC0]);
+	assert_eq!(add_r64_r64(RCX, R11), [0x49, 0x03, 0xCB]);
+	assert_eq!(imul_r64_r64(R11, R8), [0x4D, 0x0F, 0xAF, 0xD8]);
+	assert_eq!(mov_r64_i64(R13, 1), [0x49, 0xBD, 1, 0, 0, 0, 0, 0, 0, 0]);
+	assert_eq!(sub_r64_i32(R8, 16), [0x49, 0x81, 0xE8, 16, 0, 0, 0]);
+	assert_eq!(push_r64(R8), [0x41, 0x50]);
+	assert_eq!(pop_r64(R15), [0x41, 0x5F]);
+	assert_eq!(pop_r16(R9), [0x66, 0x41, 0x59]);
+
+	assert_eq!(mov_r64_m64(R11, RSP, 8), [0x4C, 0x8B, 0x9C, 0x24, 8, 0, 0, 0]);
+	assert_eq!(mov_m64_r64(R13, -8, RDX), [0x49, 0x89, 0x94, 0x25, 0xF8, 0xFF, 0xFF, 0xFF]);
+	assert_eq!(jccnrd_i32(CC_L, 0x10), [0x0F, 0x8C, 0x10, 0, 0, 0]);
+	assert_eq!(jccnrd_i8(CC_NE, 0xFE), [0x75, 0xFE]);
+}
+
+#[test]
+fn test_insn_len() {
+	use dasm::tier::raw::amd64::insn_len;
+
+	#[rustfmt::skip]
+	let cases: &[&[u8]] = &[
+		&[0x55],                                                 // push rbp
+		&[0x48, 0x83, 0xEC, 0x20],                               // sub rsp, 0x20
+		&[0x48, 0x8B, 0x05, 0x00, 0x10, 0x00, 0x00],             // mov rax, [rip + 0x1000]
+		&[0x48, 0xA1, 0, 0, 0, 0, 0, 0, 0, 0],                   // movabs rax, [0]
+		&[0x66, 0xF7, 0xC1, 0x01, 0x00],                         // test cx, 1
+		&[0x66, 0x48, 0x81, 0xC0, 0x01, 0x00, 0x00, 0x00],       // add rax, 1
+		&[0x66, 0x0F, 0x1F, 0x44, 0x00, 0x00],                   // nop word [rax + rax]
+		&[0xF3, 0x0F, 0x1E, 0xFA],                               // endbr64
+		&[0x0F, 0x0E],                                           // femms
+		&[0xC7, 0xF8, 0x00, 0x00, 0x00, 0x00],                   // xbegin rel32
+		&[0x66, 0x0F, 0x3A, 0x0F, 0xC1, 0x08],                   // palignr xmm0, xmm1, 8
+		&[0x0F, 0x84, 0x00, 0x00, 0x00, 0x00],                   // je rel32
+		&[0xC5, 0xF8, 0x77],                                     // vzeroupper
+		&[0xC5, 0xFD, 0x6F, 0x04, 0x24],                         // vmovdqa ymm0, [rsp]
+		&[0xC4, 0xE3, 0x7D, 0x18, 0xC1, 0x01],                   // vinsertf128 ymm0, ymm0, xmm1, 1
+		&[0x62, 0xF1, 0x7C, 0x48, 0x10, 0x44, 0x24, 0x01],       // vmovups zmm0, [rsp + 0x40]
+		&[0x62, 0xF3, 0x7D, 0x48, 0x19, 0xC1, 0x01],             // vextractf32x4 xmm1, zmm0, 1
+		&[0x8F, 0xEA, 0x78, 0x10, 0xC0, 0x01, 0x00, 0x00, 0x00], // bextr eax, eax, 1
+		&[0x8F, 0x00]                                            // pop [rax]
+	];
+
+	for code in cases {
+		assert_eq!(insn_len(code), Some(code.len()), "{code:02X?}");
+		assert_eq!(insn_len(&code[..code.len() - 1]), None, "{code:02X?}");
+	}
+
+	// Gone in 64 bit mode, and longer than any instruction can be.
+	assert_eq!(insn_len(&[0x06]), None);
+	assert_eq!(insn_len(&[0x66; 16]), None);
+}
+
+#[test]
+fn test_walk() {
+	use dasm::tier::raw::amd64::*;
+
+	let pieces: [&[u8]; 8] = [
+		&mov_r64_i64(R12, 0x1234),
+		&mov_m64_r64(RSP, 8, R9),
+		&movdqu_x_m128(15, R13, -16),
+		&movq_x_r64(3, RAX),
+		&jmpnai_rip(0),
+		&mov_rip_r64(8, R11),
+		&callnai_r64(R11),
+		&ret()
+	];
+
+	let code = pieces.concat();
+	let mut at = 0;
+	for piece in pieces {
+		assert_eq!(insn_len(&code[at..]), Some(piece.len()));
+		at += piece.len();
+	}
 }
@@ -0,0 +1,185 @@
+// Expected bytes come from `llvm-mc -triple=armv7ve -show-encoding` and `-triple=thumbv7m`.
+// Offsets there are relative to the pipelined pc, 8 bytes ahead in A32 and 4 in T32.
+
+use dasm::tier::raw::arm32::{a32, t32};
+
+#[test]
+fn test_a32_data_processing() {
+	use a32::*;
+
+	assert_eq!(add_r32_r32_r32(0, 1, 2), [0x02, 0x00, 0x81, 0xE0]);
+	assert_eq!(adds_r32_r32_r32_shift(3, 4, 5, SHIFT_LSL, 7), [0x85, 0x33, 0x94, 0xE0]);
+	assert_eq!(mvn_r32_r32_shift(0, 1, SHIFT_ASR, 3), [0xC1, 0x01, 0xE0, 0xE1]);
+	assert_eq!(rsc_r32_r32_r32_shift(0, 1, 2, SHIFT_ROR, 1), [0xE2, 0x00, 0xE1, 0xE0]);
+	assert_eq!(tst_r32_r32(1, 2), [0x02, 0x00, 0x11, 0xE1]);
+	assert_eq!(lsl_r32_r32_r32(0, 1, 2), [0x11, 0x02, 0xA0, 0xE1]);
+	assert_eq!(ror_r32_r32_r32(0, 1, 2), [0x71, 0x02, 0xA0, 0xE1]);
+	assert_eq!(mul_r32_r32_r32(0, 1, 2), [0x91, 0x02, 0x00, 0xE0]);
+	assert_eq!(sdiv_r32_r32_r32(0, 1, 2), [0x11, 0xF2, 0x10, 0xE7]);
+	assert_eq!(udiv_r32_r32_r32(3, 4, 5), [0x14, 0xF5, 0x33, 0xE7]);
+	assert_eq!(movw_r32_i16(0, 0x1234), [0x34, 0x02, 0x01, 0xE3]);
+	assert_eq!(movt_r32_i16(1, 0xABCD), [0xCD, 0x1B, 0x4A, 0xE3]);
+	assert_eq!(cond(COND_EQ, add_r32_r32_r32(0, 1, 2)), [0x02, 0x00, 0x81, 0x00]);
+}
+
+#[test]
+fn test_a32_immediates() {
+	use a32::*;
+
+	assert_eq!(sub_r32_r32_i32(0, 1, 0x3FC), Some([0xFF, 0x0F, 0x41, 0xE2]));
+	assert_eq!(mov_r32_i32(0, 0xFF00_0000), Some([0xFF, 0x04, 0xA0, 0xE3]));
+	assert_eq!(cmp_r32_i32(1, 4), Some([0x04, 0x00, 0x51, 0xE3]));
+	assert_eq!(orrs_r32_r32_i32(0, 1, 255), Some([0xFF, 0x00, 0x91, 0xE3]));
+
+	assert_eq!(add_r32_r32_i32(0, 1, 0x101), None);
+	assert_eq!(mov_r32_i32(0, 0x1FE0_0000 | 1), None);
+	assert_eq!(encode_imm(0xF000_000F), Some(0x2FF));
+}
+
+#[test]
+fn test_a32_loads_and_stores() {
+	use a32::*;
+
+	assert_eq!(ldr_r32_m(0, 1, 4), [0x04, 0x00, 0x91, 0xE5]);
+	assert_eq!(ldr_r32_m(0, 1, -4), [0x04, 0x00, 0x11, 0xE5]);
+	assert_eq!(str_r32_pre(0, SP, -4), [0x04, 0x00, 0x2D, 0xE5]);
+	assert_eq!(ldr_r32_post(0, SP, 4), [0x04, 0x00, 0x9D, 0xE4]);
+	assert_eq!(ldrb_r32_m(2, 3, 4095), [0xFF, 0x2F, 0xD3, 0xE5]);
+	assert_eq!(strb_r32_mr(2, 3, 4, SHIFT_LSL, 2), [0x04, 0x21, 0xC3, 0xE7]);
+	assert_eq!(ldr_r32_lit(0, 0), [0x08, 0x00, 0x1F, 0xE5]);
+	assert_eq!(ldr_r32_lit(0, 16), [0x08, 0x00, 0x9F, 0xE5]);
+	assert_eq!(ldrh_r32_m(0, 1, -4), [0xB4, 0x00, 0x51, 0xE1]);
+	assert_eq!(strh_r32_pre(0, 1, 0x3C), [0xBC, 0x03, 0xE1, 0xE1]);
+	assert_eq!(ldrsb_r32_post(0, 1, -255), [0xDF, 0x0F, 0x51, 0xE0]);
+	assert_eq!(ldrsh_r32_mr(0, 1, 2), [0xF2, 0x00, 0x91, 0xE1]);
+	assert_eq!(push(1 << 4 | 1 << LR), [0x10, 0x40, 0x2D, 0xE9]);
+	assert_eq!(pop(1 << 4 | 1 << 5 | 1 << PC), [0x30, 0x80, 0xBD, 0xE8]);
+}
+
+#[test]
+fn test_a32_branches() {
+	use a32::*;
+
+	assert_eq!(b_i24(0), [0xFE, 0xFF, 0xFF, 0xEA]);
+	assert_eq!(bl_i24(0x108), [0x40, 0x00, 0x00, 0xEB]);
+	assert_eq!(blx_i24(16), [0x02, 0x00, 0x00, 0xFA]);
+	assert_eq!(blx_i24(18), [0x02, 0x00, 0x00, 0xFB]);
+	assert_eq!(bx_r32(LR), [0x1E, 0xFF, 0x2F, 0xE1]);
+	assert_eq!(blx_r32(3), [0x33, 0xFF, 0x2F, 0xE1]);
+	assert_eq!(svc_i24(0x123456), [0x56, 0x34, 0x12, 0xEF]);
+	assert_eq!(bkpt_i16(0x1234), [0x74, 0x23, 0x21, 0xE1]);
+	assert_eq!(nop(), [0x00, 0xF0, 0x20, 0xE3]);
+}
+
+#[test]
+fn test_t32_narrow() {
+	use t32::*;
+
+	assert_eq!(lsls_r32_r32_i5(0, 1, 3), [0xC8, 0x00]);
+	assert_eq!(asrs_r32_r32_i5(0, 1, 31), [0xC8, 0x17]);
+	assert_eq!(adds_r32_r32_r32(0, 1, 2), [0x88, 0x18]);
+	assert_eq!(subs_r32_r32_i3(0, 1, 7), [0xC8, 0x1F]);
+	assert_eq!(movs_r32_i8(7, 200), [0xC8, 0x27]);
+	assert_eq!(cmp_r32_i8(3, 5), [0x05, 0x2B]);
+	assert_eq!(adds_r32_i8(2, 255), [0xFF, 0x32]);
+	assert_eq!(ands_r32_r32(0, 1), [0x08, 0x40]);
+	assert_eq!(muls_r32_r32(0, 1), [0x48, 0x43]);
+	assert_eq!(negs_r32_r32(0, 1), [0x48, 0x42]);
+	assert_eq!(add_r32_r32(8, 1), [0x88, 0x44]);
+	assert_eq!(mov_r32_r32(0, 12), [0x60, 0x46]);
+	assert_eq!(cmp_r32_r32(0, 1), [0x88, 0x42]);
+	assert_eq!(cmp_r32_r32(8, 1), [0x88, 0x45]);
+
+	assert_eq!(ldr_r32_m(0, 1, 124), [0xC8, 0x6F]);
+	assert_eq!(strb_r32_m(0, 1, 31), [0xC8, 0x77]);
+	assert_eq!(ldrh_r32_m(0, 1, 62), [0xC8, 0x8F]);
+	assert_eq!(ldrsh_r32_mr(0, 1, 2), [0x88, 0x5E]);
+	assert_eq!(str_r32_mr(3, 4, 5), [0x63, 0x51]);
+	assert_eq!(ldr_r32_sp(0, 1020), [0xFF, 0x98]);
+	assert_eq!(str_r32_sp(7, 4), [0x01, 0x97]);
+	assert_eq!(add_sp_i7(16), [0x04, 0xB0]);
+	assert_eq!(sub_sp_i7(508), [0xFF, 0xB0]);
+	assert_eq!(push(1 << 4 | 1 << 5 | 1 << LR), [0x30, 0xB5]);
+	assert_eq!(pop(1 << 4 | 1 << 5 | 1 << PC), [0x30, 0xBD]);
+
+	assert_eq!(it(COND_EQ, 0b1000), [0x08, 0xBF]);
+	assert_eq!(svc_i8(1), [0x01, 0xDF]);
+	assert_eq!(bkpt_i8(3), [0x03, 0xBE]);
+	assert_eq!(nop(), [0x00, 0xBF]);
+}
+
+#[test]
+fn test_t32_wide() {
+	use t32::*;
+
+	assert_eq!(add_w_r32_r32_r32(0, 1, 2), [0x01, 0xEB, 0x02, 0x00]);
+	assert_eq!(subs_w_r32_r32_r32_shift(8, 9, 10, SHIFT_LSR, 13), [0xB9, 0xEB, 0x5A, 0x38]);
+	assert_eq!(mvn_w_r32_r32(0, 1), [0x6F, 0xEA, 0x01, 0x00]);
+	assert_eq!(orn_w_r32_r32_r32(0, 1, 2), [0x61, 0xEA, 0x02, 0x00]);
+	assert_eq!(tst_w_r32_r32_shift(0, 1, SHIFT_LSL, 4), [0x10, 0xEA, 0x01, 0x1F]);
+	assert_eq!(cmn_w_r32_r32(5, 6), [0x15, 0xEB, 0x06, 0x0F]);
+	assert_eq!(lsl_w_r32_r32_r32(0, 1, 2), [0x01, 0xFA, 0x02, 0xF0]);
+	assert_eq!(mul_r32_r32_r32(0, 1, 2), [0x01, 0xFB, 0x02, 0xF0]);
+	assert_eq!(sdiv_r32_r32_r32(0, 1, 2), [0x91, 0xFB, 0xF2, 0xF0]);
+	assert_eq!(udiv_r32_r32_r32(0, 1, 2), [0xB1, 0xFB, 0xF2, 0xF0]);
+	assert_eq!(movw_r32_i16(0, 0xABCD), [0x4A, 0xF6, 0xCD, 0x30]);
+	assert_eq!(movt_r32_i16(9, 0x1234), [0xC1, 0xF2, 0x34, 0x29]);
+
+	assert_eq!(ldr_w_r32_m(0, 1, 4), [0xD1, 0xF8, 0x04, 0x00]);
+	assert_eq!(ldr_w_r32_m(0, 1, -4), [0x51, 0xF8, 0x04, 0x0C]);
+	assert_eq!(ldr_w_r32_pre(0, 1, 4), [0x51, 0xF8, 0x04, 0x0F]);
+	assert_eq!(str_w_r32_pre(0, SP, -4), [0x4D, 0xF8, 0x04, 0x0D]);
+	assert_eq!(ldr_w_r32_post(0, SP, 4), [0x5D, 0xF8, 0x04, 0x0B]);
+	assert_eq!(ldrsh_w_r32_mr(0, 1, 2, 1), [0x31, 0xF9, 0x12, 0x00]);
+	assert_eq!(strb_w_r32_m(8, 9, 4095), [0x89, 0xF8, 0xFF, 0x8F]);
+	assert_eq!(ldrsb_w_r32_m(0, 1, -255), [0x11, 0xF9, 0xFF, 0x0C]);
+	assert_eq!(push_w(1 << 4 | 1 << 8 | 1 << LR), [0x2D, 0xE9, 0x10, 0x41]);
+	assert_eq!(pop_w(1 << 4 | 1 << 8 | 1 << PC), [0xBD, 0xE8, 0x10, 0x81]);
+}
+
+#[test]
+fn test_t32_immediates() {
+	use t32::*;
+
+	assert_eq!(add_w_r32_r32_i32(0, 1, 0x00FF_00FF), Some([0x01, 0xF1, 0xFF, 0x10]));
+	assert_eq!(orr_w_r32_r32_i32(0, 1, 0xAB00_AB00), Some([0x41, 0xF0, 0xAB, 0x20]));
+	assert_eq!(and_w_r32_r32_i32(0, 1, 0x1212_1212), Some([0x01, 0xF0, 0x12, 0x30]));
+	assert_eq!(eor_w_r32_r32_i32(0, 1, 0x3FC00), Some([0x81, 0xF4, 0x7F, 0x30]));
+	assert_eq!(bic_w_r32_r32_i32(0, 1, 0x8000_0000), Some([0x21, 0xF0, 0x00, 0x40]));
+	assert_eq!(mov_w_r32_i32(0, 0xFF00_0000), Some([0x4F, 0xF0, 0x7F, 0x40]));
+	assert_eq!(cmp_w_r32_i32(0, 1000), Some([0xB0, 0xF5, 0x7A, 0x7F]));
+	assert_eq!(teq_w_r32_i32(5, 1), Some([0x95, 0xF0, 0x01, 0x0F]));
+
+	assert_eq!(add_w_r32_r32_i32(0, 1, 0x101), None);
+	assert_eq!(mov_w_r32_i32(0, 0x1200_0034), None);
+}
+
+#[test]
+fn test_t32_branches() {
+	use t32::*;
+
+	assert_eq!(bx_r32(LR), [0x70, 0x47]);
+	assert_eq!(blx_r32(9), [0xC8, 0x47]);
+	assert_eq!(b_i11(0), [0xFE, 0xE7]);
+	assert_eq!(b_i11(104), [0x32, 0xE0]);
+	assert_eq!(bcond_i8(COND_EQ, -16), [0xF6, 0xD0]);
+	assert_eq!(cbz_r32_i6(3, 130), [0xFB, 0xB3]);
+	assert_eq!(cbnz_r32_i6(0, 14), [0x28, 0xB9]);
+	assert_eq!(b_w_i24(104), [0x00, 0xF0, 0x32, 0xB8]);
+	assert_eq!(b_w_i24(-16777212), [0x00, 0xF4, 0x00, 0x90]);
+	assert_eq!(bl_i24(0), [0xFF, 0xF7, 0xFE, 0xFF]);
+	assert_eq!(bl_i24(0x12345A), [0x23, 0xF1, 0x2B, 0xFA]);
+	assert_eq!(bcond_w_i20(COND_EQ, 204), [0x00, 0xF0, 0x64, 0x80]);
+	assert_eq!(bcond_w_i20(COND_NE, -1048572), [0x40, 0xF4, 0x00, 0x80]);
+}
+
+#[test]
+fn test_movwt() {
+	let words = a32::movwt_r32_i32(0, 0xDEAD_BEEF);
+	assert_eq!(words[..4], a32::movw_r32_i16(0, 0xBEEF));
+	assert_eq!(words[4..], a32::movt_r32_i16(0, 0xDEAD));
+
+	let words = t32::movwt_r32_i32(0, 0xDEAD_BEEF);
+	assert_eq!(words[..4], t32::movw_r32_i16(0, 0xBEEF));
+	assert_eq!(words[4..], t32::movt_r32_i16(0, 0xDEAD));
+}
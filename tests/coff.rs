@@ -0,0 +1,136 @@
+use dasm::object::{coff, pe, Arch, Object, ObjectError, Relocation, RelocationKind, Section, Symbol};
+
+fn u16_at(b: &[u8], at: usize) -> u16 {
+	u16::from_le_bytes(b[at..at + 2].try_into().unwrap())
+}
+
+fn u32_at(b: &[u8], at: usize) -> u32 {
+	u32::from_le_bytes(b[at..at + 4].try_into().unwrap())
+}
+
+fn u64_at(b: &[u8], at: usize) -> u64 {
+	u64::from_le_bytes(b[at..at + 8].try_into().unwrap())
+}
+
+/// A symbol name, either inline or from the string table.
+fn symbol_name(b: &[u8], symbol: usize, strtab: usize) -> &str {
+	let raw = &b[symbol..symbol + 8];
+	let name = if u32_at(raw, 0) == 0 {
+		let at = strtab + u32_at(raw, 4) as usize;
+		let len = b[at..].iter().position(|&c| c == 0).unwrap();
+		&b[at..at + len]
+	} else {
+		let len = raw.iter().position(|&c| c == 0).unwrap_or(8);
+		&raw[..len]
+	};
+
+	std::str::from_utf8(name).unwrap()
+}
+
+#[test]
+fn test_coff_object() {
+	use dasm::tier::raw::amd64::*;
+
+	let text = [&callnrd_i32(0) as &[u8], &mov_r64_i64(0, 0), &ret()].concat();
+	let data = 42u64.to_le_bytes();
+
+	let object = Object::new(Arch::Amd64)
+		.text(&text)
+		.data(&data)
+		.symbol(Symbol::function("a_rather_long_function_name", 0, text.len() as u64))
+		.symbol(Symbol::data("answer", 0, 8).local())
+		.relocation(Relocation::new(Section::Text, 1, "helper", RelocationKind::Branch32, -4))
+		.relocation(Relocation::new(Section::Text, 7, "answer", RelocationKind::Absolute64, 8));
+
+	let b = coff::write(&object).unwrap();
+
+	assert_eq!(u16_at(&b, 0), 0x8664); // IMAGE_FILE_MACHINE_AMD64
+	assert_eq!(u16_at(&b, 2), 2); // Sections
+	assert_eq!(u16_at(&b, 16), 0); // No optional header
+
+	let symtab = u32_at(&b, 8) as usize;
+	let symbols = u32_at(&b, 12) as usize;
+	let strtab = symtab + symbols * 18;
+	assert_eq!(symbols, 3);
+	assert_eq!(u32_at(&b, strtab) as usize, b.len() - strtab);
+
+	let symbol = |i: usize| {
+		let s = symtab + i * 18;
+		(symbol_name(&b, s, strtab), u16_at(&b, s + 12), u16_at(&b, s + 14), b[s + 16])
+	};
+
+	assert_eq!(symbol(0), ("a_rather_long_function_name", 1, 0x20, 2));
+	assert_eq!(symbol(1), ("answer", 2, 0, 3));
+	assert_eq!(symbol(2), ("helper", 0, 0, 2));
+
+	// .text, with its relocations.
+	let text_header = 20;
+	assert_eq!(&b[text_header..text_header + 8], b".text\0\0\0");
+	assert_eq!(u32_at(&b, text_header + 36) & 0x6000_0020, 0x6000_0020); // Code, readable, executable
+
+	let raw = u32_at(&b, text_header + 20) as usize;
+	let relocs = u32_at(&b, text_header + 24) as usize;
+	assert_eq!(u16_at(&b, text_header + 32), 2);
+
+	// Addends live in the code. REL32 is already relative to the end of the field, so -4 becomes 0.
+	assert_eq!(&b[raw..raw + 5], &[0xE8, 0, 0, 0, 0]);
+	assert_eq!(u64_at(&b, raw + 7), 8);
+
+	assert_eq!((u32_at(&b, relocs), u32_at(&b, relocs + 4), u16_at(&b, relocs + 8)), (1, 2, 0x4)); // REL32 to helper
+	assert_eq!((u32_at(&b, relocs + 10), u32_at(&b, relocs + 14), u16_at(&b, relocs + 18)), (7, 1, 0x1)); // ADDR64 to answer
+
+	let x86 = Object::new(Arch::X86)
+		.text(&text)
+		.relocation(Relocation::new(Section::Text, 1, "x", RelocationKind::GotRelative32, 0));
+
+	assert_eq!(coff::write(&x86), Err(ObjectError::UnsupportedRelocation(Arch::X86, RelocationKind::GotRelative32)));
+}
+
+#[test]
+fn test_pe_image() {
+	let text = [0xB8, 7, 0, 0, 0, 0xC3]; // mov eax, 7; ret
+	let data = b"Hello";
+
+	for (arch, magic, optional_size) in [(Arch::Amd64, 0x20B, 240), (Arch::X86, 0x10B, 224)] {
+		let image = pe::Image::new(arch).text(&text).data(data);
+		let b = pe::write(&image).unwrap();
+
+		assert_eq!(&b[..2], b"MZ");
+		let pe = u32_at(&b, 0x3C) as usize;
+		assert_eq!(&b[pe..pe + 4], b"PE\0\0");
+
+		let file = pe + 4;
+		assert_eq!(u16_at(&b, file + 2), 2); // Sections
+		assert_eq!(u16_at(&b, file + 16), optional_size);
+		assert_eq!(u16_at(&b, file + 18) & 0x2, 0x2); // Executable
+
+		let optional = file + 20;
+		assert_eq!(u16_at(&b, optional), magic);
+		assert_eq!(u32_at(&b, optional + 16), 0x1000); // Entry point
+		assert_eq!(u16_at(&b, optional + 68), 3); // Console subsystem, same offset in both
+
+		let image_base = if magic == 0x20B { u64_at(&b, optional + 24) } else { u32_at(&b, optional + 28) as u64 };
+		assert_eq!(image_base, image.text_addr() - 0x1000);
+		assert_eq!(image.data_addr(), image.text_addr() + 0x1000);
+
+		// Section headers follow the optional header, pointing at file aligned raw data.
+		let sections = optional + optional_size as usize;
+		assert_eq!(&b[sections..sections + 8], b".text\0\0\0");
+		assert_eq!(u32_at(&b, sections + 12), 0x1000);
+
+		let raw = u32_at(&b, sections + 20) as usize;
+		assert_eq!(raw % 0x200, 0);
+		assert_eq!(&b[raw..raw + text.len()], &text);
+
+		let data_header = sections + 40;
+		assert_eq!(&b[data_header..data_header + 8], b".data\0\0\0");
+		assert_eq!(u32_at(&b, data_header + 12), 0x2000);
+
+		let raw = u32_at(&b, data_header + 20) as usize;
+		assert_eq!(&b[raw..raw + data.len()], data);
+
+		assert_eq!(b.len() % 0x200, 0);
+	}
+
+	assert_eq!(pe::write(&pe::Image::new(Arch::Amd64)), Err(ObjectError::OutOfBounds(0)));
+}
@@ -0,0 +1,211 @@
+use dasm::object::{elf, Arch, Object, ObjectError, Relocation, RelocationKind, Section, Symbol};
+
+fn u16_at(b: &[u8], at: usize) -> u16 {
+	u16::from_le_bytes(b[at..at + 2].try_into().unwrap())
+}
+
+fn u32_at(b: &[u8], at: usize) -> u32 {
+	u32::from_le_bytes(b[at..at + 4].try_into().unwrap())
+}
+
+fn u64_at(b: &[u8], at: usize) -> u64 {
+	u64::from_le_bytes(b[at..at + 8].try_into().unwrap())
+}
+
+fn cstr_at(b: &[u8], at: usize) -> &str {
+	let end = b[at..].iter().position(|&c| c == 0).unwrap();
+	std::str::from_utf8(&b[at..at + end]).unwrap()
+}
+
+/// (name, type, offset, size, link, info) of every ELF64 section.
+fn sections64(b: &[u8]) -> Vec<(&str, u32, usize, usize, u32, u32)> {
+	let shoff = u64_at(b, 0x28) as usize;
+	let shnum = u16_at(b, 0x3C) as usize;
+	let shstrndx = u16_at(b, 0x3E) as usize;
+
+	let header = |i: usize| shoff + i * 64;
+	let strtab = u64_at(b, header(shstrndx) + 0x18) as usize;
+
+	(0..shnum)
+		.map(|i| {
+			let h = header(i);
+			(
+				cstr_at(b, strtab + u32_at(b, h) as usize),
+				u32_at(b, h + 4),
+				u64_at(b, h + 0x18) as usize,
+				u64_at(b, h + 0x20) as usize,
+				u32_at(b, h + 0x28),
+				u32_at(b, h + 0x2C)
+			)
+		})
+		.collect()
+}
+
+#[test]
+fn test_elf64_relocatable() {
+	use dasm::tier::raw::amd64::*;
+
+	let text = [
+		&callnrd_i32(0) as &[u8],
+		&mov_r64_i64(0, 0),
+		&ret()
+	].concat();
+
+	let data = 42u64.to_le_bytes();
+
+	let object = Object::new(Arch::Amd64)
+		.text(&text)
+		.data(&data)
+		.symbol(Symbol::function("get_answer", 0, text.len() as u64))
+		.symbol(Symbol::data("answer", 0, 8).local())
+		.relocation(Relocation::new(Section::Text, 1, "helper", RelocationKind::Branch32, -4))
+		.relocation(Relocation::new(Section::Text, 7, "answer", RelocationKind::Absolute64, 0));
+
+	let b = elf::write(&object).unwrap();
+
+	assert_eq!(&b[..4], b"\x7FELF");
+	assert_eq!(b[4], 2); // ELFCLASS64
+	assert_eq!(u16_at(&b, 0x10), 1); // ET_REL
+	assert_eq!(u16_at(&b, 0x12), 62); // EM_X86_64
+
+	let sections = sections64(&b);
+	let names = sections.iter().map(|s| s.0).collect::<Vec<_>>();
+	assert_eq!(names, ["", ".text", ".data", ".symtab", ".strtab", ".note.GNU-stack", ".rela.text", ".shstrtab"]);
+
+	let (_, _, offset, size, ..) = sections[1];
+	assert_eq!(&b[offset..offset + size], &text);
+
+	// Symbols: null, two section symbols, the local, then globals and externs.
+	let (_, _, symtab, size, strtab, first_global) = sections[3];
+	let strtab = sections[strtab as usize].2;
+	let symbols = (0..size / 24)
+		.map(|i| {
+			let s = symtab + i * 24;
+			(cstr_at(&b, strtab + u32_at(&b, s) as usize), b[s + 4], u16_at(&b, s + 6))
+		})
+		.collect::<Vec<_>>();
+
+	assert_eq!(first_global, 4);
+	assert_eq!(symbols[3], ("answer", 0x01, 2)); // STB_LOCAL, STT_OBJECT, .data
+	assert_eq!(symbols[4], ("get_answer", 0x12, 1)); // STB_GLOBAL, STT_FUNC, .text
+	assert_eq!(symbols[5], ("helper", 0x10, 0)); // STB_GLOBAL, STT_NOTYPE, SHN_UNDEF
+
+	let (_, kind, rela, size, link, info) = sections[6];
+	assert_eq!((kind, link, info, size), (4, 3, 1, 48)); // SHT_RELA for .text, using .symtab
+
+	assert_eq!(u64_at(&b, rela), 1);
+	assert_eq!(u64_at(&b, rela + 8), (5 << 32) | 4); // helper, R_X86_64_PLT32
+	assert_eq!(u64_at(&b, rela + 16) as i64, -4);
+
+	assert_eq!(u64_at(&b, rela + 24), 7);
+	assert_eq!(u64_at(&b, rela + 32), (3 << 32) | 1); // answer, R_X86_64_64
+}
+
+#[test]
+fn test_elf32_relocatable() {
+	use dasm::tier::raw::x86::*;
+
+	let text = [&callnrd_i32(0) as &[u8], &mov_r32_i32(0, 0), &ret()].concat();
+
+	let object = Object::new(Arch::X86)
+		.text(&text)
+		.data(&[1, 2, 3, 4])
+		.symbol(Symbol::function("get_answer", 0, text.len() as u64))
+		.symbol(Symbol::data("answer", 0, 4))
+		.relocation(Relocation::new(Section::Text, 1, "helper", RelocationKind::Branch32, -4))
+		.relocation(Relocation::new(Section::Text, 6, "answer", RelocationKind::Absolute32, 0));
+
+	let b = elf::write(&object).unwrap();
+
+	assert_eq!(b[4], 1); // ELFCLASS32
+	assert_eq!(u16_at(&b, 0x12), 3); // EM_386
+	assert_eq!(u16_at(&b, 0x2E), 40); // Section header size
+
+	// REL has no addend field, so it's stored in the code instead.
+	let text_offset = 52usize.next_multiple_of(16);
+	assert_eq!(&b[text_offset..text_offset + 5], &[0xE8, 0xFC, 0xFF, 0xFF, 0xFF]);
+
+	assert_eq!(
+		elf::write(&object.clone().relocation(Relocation::new(Section::Text, 1, "x", RelocationKind::GotRelative32, 0))),
+		Err(ObjectError::UnsupportedRelocation(Arch::X86, RelocationKind::GotRelative32))
+	);
+
+	assert_eq!(
+		elf::write(&object.clone().relocation(Relocation::new(Section::Text, 10, "x", RelocationKind::Absolute32, 0))),
+		Err(ObjectError::OutOfBounds(10))
+	);
+
+	assert_eq!(
+		elf::write(&object.relocation(Relocation::new(Section::Text, u64::MAX - 1, "x", RelocationKind::Absolute32, 0))),
+		Err(ObjectError::OutOfBounds(u64::MAX - 1))
+	);
+}
+
+#[test]
+#[cfg(all(target_arch = "x86_64", target_os = "linux"))]
+fn test_elf64_executable() {
+	use dasm::tier::raw::amd64::*;
+
+	let message = b"Hello from a static executable!\n";
+	let exe = elf::Executable::new(Arch::Amd64).data(message);
+
+	let text = [
+		&mov_r64_i64(RAX, 1) as &[u8], // sys_write
+		&mov_r64_i64(RDI, 1),
+		&mov_r64_i64(RSI, exe.data_addr),
+		&mov_r64_i64(RDX, message.len() as u64),
+		&syscall(),
+
+		&mov_r64_i64(RAX, 60), // sys_exit
+		&mov_r64_i64(RDI, 7),
+		&syscall()
+	].concat();
+
+	let b = elf::write_executable(&exe.text(&text)).unwrap();
+
+	assert_eq!(u16_at(&b, 0x10), 2); // ET_EXEC
+	assert_eq!(u64_at(&b, 0x18), 0x40_1000); // Entry
+	assert_eq!(u16_at(&b, 0x38), 3); // Text, data and stack
+
+	let path = std::env::temp_dir().join(format!("dasm-elf-exec-{}", std::process::id()));
+	std::fs::write(&path, &b).unwrap();
+	std::fs::set_permissions(&path, std::os::unix::fs::PermissionsExt::from_mode(0o755)).unwrap();
+
+	let output = std::process::Command::new(&path).output().unwrap();
+	std::fs::remove_file(&path).unwrap();
+
+	assert_eq!(output.stdout, message);
+	assert_eq!(output.status.code(), Some(7));
+}
+
+#[test]
+fn test_elf32_executable() {
+	use dasm::tier::raw::x86::*;
+
+	let text = [&mov_r32_i32(0, 1) as &[u8], &int_i8(0x80)].concat();
+	let b = elf::write_executable(&elf::Executable::new(Arch::X86).text(&text).bss(64)).unwrap();
+
+	assert_eq!(b[4], 1); // ELFCLASS32
+	assert_eq!(u32_at(&b, 0x18), 0x0804_9000); // Entry
+	assert_eq!(u16_at(&b, 0x2C), 3); // Text, bss and stack
+
+	// Text segment is page aligned in the file, so it can be mapped directly.
+	let phoff = u32_at(&b, 0x1C) as usize;
+	assert_eq!(u32_at(&b, phoff), 1); // PT_LOAD
+	assert_eq!(u32_at(&b, phoff + 4), 0x1000);
+	assert_eq!(&b[0x1000..], &text);
+
+	// Bss takes up memory, but no file space.
+	assert_eq!(u32_at(&b, phoff + 32 + 16), 0);
+	assert_eq!(u32_at(&b, phoff + 32 + 20), 64);
+
+	let overlapping = elf::Executable::new(Arch::X86).text(&text).data(&[0]).data_addr(0x0804_9000);
+	assert_eq!(elf::write_executable(&overlapping), Err(ObjectError::Overlapping));
+
+	// Disjoint bytes, but the same page.
+	let sharing = elf::Executable::new(Arch::X86).text(&text).data(&[0]).text_addr(0x40_0000).data_addr(0x40_0800);
+	assert_eq!(elf::write_executable(&sharing), Err(ObjectError::Overlapping));
+
+	let adjacent = elf::Executable::new(Arch::X86).text(&text).data(&[0]).text_addr(0x40_0000).data_addr(0x40_1000);
+	assert!(elf::write_executable(&adjacent).is_ok());
+}
@@ -0,0 +1,71 @@
+use dasm::object::flat::{Fixup, Flat, BOOT_SECTOR_SIZE, BOOT_SIGNATURE};
+use dasm::object::ObjectError;
+use dasm::tier::raw::x86::real::*;
+
+const AX: u8 = 0;
+const SI: u8 = 6;
+
+#[test]
+fn test_boot_sector() {
+	let image = Flat::boot_sector()
+		.bytes(&cli())
+		.reference(&mov_r16_i16(SI, 0), "message", Fixup::Absolute16)
+		.label("print")
+		.bytes(&lodsb())
+		.bytes(&cmp_r8_i8(AX, 0))
+		.reference(&[0x74, 0], "halt", Fixup::Relative8) // je
+		.bytes(&mov_r8_i8(4, 0x0E))
+		.bytes(&int_i8(0x10))
+		.reference(&jmpnrd_i8(0), "print", Fixup::Relative8)
+		.label("halt")
+		.bytes(&hlt())
+		.reference(&jmpnrd_i16(0), "halt", Fixup::Relative16)
+		.align(8, 0x90)
+		.label("message")
+		.bytes(b"Hi\0")
+		.boot_signature();
+
+	assert_eq!(image.address("message"), Some(0x7C00 + 24));
+
+	let b = image.write().unwrap();
+	assert_eq!(b.len(), BOOT_SECTOR_SIZE);
+	assert_eq!(b[510..], BOOT_SIGNATURE);
+
+	assert_eq!(b[..24], [
+		0xFA, // cli
+		0xBE, 0x18, 0x7C, // mov si, message
+		0xAC, // lodsb
+		0x80, 0xF8, 0x00, // cmp al, 0
+		0x74, 0x06, // je halt
+		0xB4, 0x0E, // mov ah, 0x0E
+		0xCD, 0x10, // int 0x10
+		0xEB, 0xF4, // jmp print
+		0xF4, // hlt
+		0xE9, 0xFC, 0xFF, // jmp halt
+		0x90, 0x90, 0x90, 0x90
+	]);
+	assert_eq!(&b[24..27], b"Hi\0");
+	assert!(b[27..510].iter().all(|&b| b == 0));
+}
+
+#[test]
+fn test_flat_errors() {
+	let undefined = Flat::new(0).reference(&jmpnrd_i8(0), "missing", Fixup::Relative8);
+	assert_eq!(undefined.write(), Err(ObjectError::Undefined(1)));
+
+	let far = Flat::new(0)
+		.reference(&jmpnrd_i8(0), "far", Fixup::Relative8)
+		.bytes(&[0; 200])
+		.label("far");
+	assert_eq!(far.write(), Err(ObjectError::OutOfRange(1)));
+
+	let high = Flat::new(0x1_0000).label("high").reference(&mov_r16_i16(AX, 0), "high", Fixup::Absolute16);
+	assert_eq!(high.write(), Err(ObjectError::OutOfRange(1)));
+
+	let overflow = Flat::new(0).bytes(&[0; 4]).pad_to(2, 0);
+	assert_eq!(overflow.write(), Err(ObjectError::OutOfBounds(2)));
+
+	let padded = Flat::new(0x100).bytes(&[1]).pad_to(4, 0xFF).label("end");
+	assert_eq!(padded.address("end"), Some(0x104));
+	assert_eq!(padded.write().unwrap(), [1, 0xFF, 0xFF, 0xFF]);
+}
@@ -0,0 +1,126 @@
+#![cfg(all(target_arch = "x86_64", target_os = "linux"))]
+
+use dasm::mmap::{Hook, Mmap, MmapError, MmapOptions, Stack};
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+/// Permissions of the mapping holding `ptr`, like `r-xp`.
+fn permissions(ptr: *const u8) -> String {
+	let maps = std::fs::read_to_string("/proc/self/maps").unwrap();
+	let line = maps.lines()
+		.find(|l| {
+			let (start, end) = l.split_once(' ').unwrap().0.split_once('-').unwrap();
+			(usize::from_str_radix(start, 16).unwrap()..usize::from_str_radix(end, 16).unwrap()).contains(&(ptr as usize))
+		})
+		.unwrap();
+
+	line.split(' ').nth(1).unwrap().to_owned()
+}
+
+#[inline(never)]
+extern "C" fn mix(a: u64, b: u64) -> u64 {
+	a * 10 + b
+}
+
+static MIX: AtomicUsize = AtomicUsize::new(0);
+
+extern "C" fn mix_detour(a: u64, b: u64) -> u64 {
+	let original: extern "C" fn(u64, u64) -> u64 = unsafe { std::mem::transmute(MIX.load(Ordering::Relaxed)) };
+	original(a, b) + 1
+}
+
+#[test]
+fn test_hook() {
+	let mix = std::hint::black_box(mix as extern "C" fn(u64, u64) -> u64);
+	assert_eq!(mix(2, 5), 25);
+
+	let hook = unsafe { Hook::new(mix as *const u8, mix_detour as *const u8) }.unwrap();
+	MIX.store(hook.original() as usize, Ordering::Relaxed);
+
+	assert_eq!(mix(2, 5), 26);
+	assert_eq!(unsafe { hook.original_fn::<extern "C" fn(u64, u64) -> u64>() }.call(2, 5), 25);
+
+	hook.unhook().unwrap();
+	assert_eq!(mix(2, 5), 25);
+}
+
+static BRANCHY: AtomicUsize = AtomicUsize::new(0);
+
+extern "C" fn branchy_detour(x: u64) -> u64 {
+	let original: extern "C" fn(u64) -> u64 = unsafe { std::mem::transmute(BRANCHY.load(Ordering::Relaxed)) };
+	original(x) * 2
+}
+
+#[test]
+fn test_relocation() {
+	// A short branch and a rip relative load, both under the patch.
+	#[rustfmt::skip]
+	let mut map = Mmap::exec([
+		0x85, 0xFF,                               // test edi, edi
+		0x74, 0x0B,                               // jz 0x0F
+		0x48, 0x8D, 0x05, 0x0D, 0x00, 0x00, 0x00, // lea rax, [rip + 0x0D]
+		0x48, 0x8B, 0x00,                         // mov rax, [rax]
+		0xC3,                                     // ret
+		0xB8, 0x07, 0x00, 0x00, 0x00,             // mov eax, 7
+		0xC3,                                     // ret
+		0xCC, 0xCC, 0xCC,
+		0xD2, 0x04, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00
+	]).unwrap();
+
+	let f = unsafe { map.as_fn::<extern "C" fn(u64) -> u64>() };
+	assert_eq!((f.call(1), f.call(0)), (1234, 7));
+
+	let hook = unsafe { Hook::new(map.as_ptr(), branchy_detour as *const u8) }.unwrap();
+	BRANCHY.store(hook.original() as usize, Ordering::Relaxed);
+	assert_eq!((f.call(1), f.call(0)), (2468, 14));
+
+	drop(hook);
+	assert_eq!((f.call(1), f.call(0)), (1234, 7));
+
+	// The mapping keeps the protection it had, so it can still be written to.
+	assert_eq!(permissions(map.as_ptr()), "rwxp");
+	map.as_mut()[24] = 0xD3;
+}
+
+extern "C" fn two() -> u64 {
+	2
+}
+
+#[test]
+fn test_end_of_mapping() {
+	// A function right before an inaccessible page, which decoding mustn't read into.
+	let page = dasm::mmap::page_size();
+	let stack = Stack::new(page).unwrap();
+
+	let mut code = vec![0xCC; page];
+	code[page - 6..].copy_from_slice(&[0xB8, 0x01, 0x00, 0x00, 0x00, 0xC3]); // mov eax, 1; ret
+	let map = Mmap::exec_with(&code, &MmapOptions::new().fixed(stack.guard().wrapping_sub(page))).unwrap();
+
+	let target = map.as_ptr().wrapping_add(page - 6);
+	let f = unsafe { dasm::mmap::Func::<extern "C" fn() -> u64>::new(target) };
+
+	let hook = unsafe { Hook::new(target, two as *const u8) }.unwrap();
+	assert_eq!(f.call(), 2);
+	assert_eq!(unsafe { hook.original_fn::<extern "C" fn() -> u64>() }.call(), 1);
+
+	drop(hook);
+	assert_eq!(f.call(), 1);
+}
+
+#[test]
+fn test_unrelocatable() {
+	// Returning before the end of the patch, looping, an opcode amd64 doesn't have, and a `jz rel32` and `xbegin` back under the patch.
+	let codes = [
+		&[0x31, 0xC0, 0xC3][..],
+		&[0xE2, 0xFE, 0x90, 0x90, 0x90, 0xC3],
+		&[0x06, 0xC3],
+		&[0x0F, 0x84, 0xFB, 0xFF, 0xFF, 0xFF, 0xC3],
+		&[0xC7, 0xF8, 0xFB, 0xFF, 0xFF, 0xFF, 0xC3]
+	];
+
+	for code in codes {
+		let map = Mmap::exec(code).unwrap();
+		let hook = unsafe { Hook::new(map.as_ptr(), branchy_detour as *const u8) };
+		assert_eq!(hook.unwrap_err(), MmapError::Relocate);
+		assert_eq!(map.as_ref(), code);
+	}
+}
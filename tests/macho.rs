@@ -0,0 +1,103 @@
+use dasm::object::{macho, Arch, Object, ObjectError, Relocation, RelocationKind, Section, Symbol};
+
+fn u32_at(b: &[u8], at: usize) -> u32 {
+	u32::from_le_bytes(b[at..at + 4].try_into().unwrap())
+}
+
+fn u64_at(b: &[u8], at: usize) -> u64 {
+	u64::from_le_bytes(b[at..at + 8].try_into().unwrap())
+}
+
+fn cstr_at(b: &[u8], at: usize) -> &str {
+	let end = b[at..].iter().position(|&c| c == 0).unwrap();
+	std::str::from_utf8(&b[at..at + end]).unwrap()
+}
+
+/// Finds the first load command of the given type.
+fn command(b: &[u8], cmd: u32) -> usize {
+	let mut at = 32;
+	for _ in 0..u32_at(b, 16) {
+		if u32_at(b, at) == cmd {
+			return at;
+		}
+
+		at += u32_at(b, at + 4) as usize;
+	}
+
+	panic!("Missing load command {cmd:#x}");
+}
+
+#[test]
+fn test_macho_object() {
+	use dasm::tier::raw::amd64::*;
+
+	let text = [&callnrd_i32(0) as &[u8], &mov_r64_i64(0, 0), &ret()].concat();
+	let data = 42u64.to_le_bytes();
+
+	let object = Object::new(Arch::Amd64)
+		.text(&text)
+		.data(&data)
+		.symbol(Symbol::function("_get_answer", 0, text.len() as u64))
+		.symbol(Symbol::data("_answer", 0, 8).local())
+		.relocation(Relocation::new(Section::Text, 1, "_helper", RelocationKind::Branch32, -4))
+		.relocation(Relocation::new(Section::Text, 7, "_answer", RelocationKind::Absolute64, 0));
+
+	let b = macho::write(&object).unwrap();
+
+	assert_eq!(u32_at(&b, 0), 0xFEED_FACF); // MH_MAGIC_64
+	assert_eq!(u32_at(&b, 4), 0x0100_0007); // CPU_TYPE_X86_64
+	assert_eq!(u32_at(&b, 12), 1); // MH_OBJECT
+
+	// Segment with __TEXT,__text then __DATA,__data.
+	let segment = command(&b, 0x19);
+	assert_eq!(u32_at(&b, segment + 64), 2);
+
+	let text_section = segment + 72;
+	assert_eq!(cstr_at(&b, text_section), "__text");
+	assert_eq!(cstr_at(&b, text_section + 16), "__TEXT");
+	assert_eq!(u64_at(&b, text_section + 40), text.len() as u64);
+	assert_eq!(u32_at(&b, text_section + 64), 0x8000_0400); // Pure instructions
+
+	let offset = u32_at(&b, text_section + 48) as usize;
+	assert_eq!(&b[offset..offset + text.len()], &text);
+
+	let data_section = text_section + 80;
+	assert_eq!(cstr_at(&b, data_section), "__data");
+	assert_eq!(cstr_at(&b, data_section + 16), "__DATA");
+	let data_addr = u64_at(&b, data_section + 32);
+	assert_eq!(data_addr % 16, 0);
+
+	// Relocations are extern, referencing symbols by index.
+	let reloff = u32_at(&b, text_section + 56) as usize;
+	assert_eq!(u32_at(&b, text_section + 60), 2);
+
+	let info = |i: usize| {
+		let info = u32_at(&b, reloff + i * 8 + 4);
+		(u32_at(&b, reloff + i * 8), info & 0xFF_FFFF, (info >> 24) & 1, (info >> 25) & 3, (info >> 27) & 1, info >> 28)
+	};
+
+	assert_eq!(info(0), (1, 2, 1, 2, 1, 2)); // BRANCH to _helper, pc relative, 4 bytes
+	assert_eq!(info(1), (7, 0, 0, 3, 1, 0)); // UNSIGNED to _answer, 8 bytes
+
+	// Locals, then defined externals, then undefined.
+	let symtab = command(&b, 0x2);
+	let symoff = u32_at(&b, symtab + 8) as usize;
+	let stroff = u32_at(&b, symtab + 16) as usize;
+	assert_eq!(u32_at(&b, symtab + 12), 3);
+
+	let symbol = |i: usize| {
+		let s = symoff + i * 16;
+		(cstr_at(&b, stroff + u32_at(&b, s) as usize), b[s + 4], b[s + 5], u64_at(&b, s + 8))
+	};
+
+	assert_eq!(symbol(0), ("_answer", 0x0E, 2, data_addr));
+	assert_eq!(symbol(1), ("_get_answer", 0x0F, 1, 0));
+	assert_eq!(symbol(2), ("_helper", 0x01, 0, 0));
+
+	let dysymtab = command(&b, 0xB);
+	assert_eq!((u32_at(&b, dysymtab + 8), u32_at(&b, dysymtab + 12)), (0, 1));
+	assert_eq!((u32_at(&b, dysymtab + 16), u32_at(&b, dysymtab + 20)), (1, 1));
+	assert_eq!((u32_at(&b, dysymtab + 24), u32_at(&b, dysymtab + 28)), (2, 1));
+
+	assert_eq!(macho::write(&Object::new(Arch::X86)), Err(ObjectError::UnsupportedArch(Arch::X86)));
+}
@@ -0,0 +1,289 @@
+#![cfg(all(target_arch = "x86_64", target_os = "linux"))]
+
+use dasm::abi::HOST;
+
+#[test]
+fn test_wx_seal_unseal() {
+	let map = dasm::mmap::WxMmap::copy([
+		&dasm::tier::raw::amd64::mov_r64_r64(HOST.ret, HOST.args[0]) as &[u8],
+		&dasm::tier::raw::amd64::add_r64_r64(HOST.ret, HOST.args[1]),
+		&dasm::tier::raw::amd64::ret()
+	].concat()).unwrap();
+
+	let offset = dasm::tier::raw::amd64::mov_r64_r64(HOST.ret, HOST.args[0]).len();
+
+	let map = map.seal().unwrap();
+	let f: extern "C" fn(u64, u64) -> u64 = unsafe { std::mem::transmute(map.as_ptr()) };
+	assert_eq!(f(5, 200), 205);
+
+	let mut map = map.unseal().unwrap();
+	map.as_mut()[offset..offset + 3].copy_from_slice(&dasm::tier::raw::amd64::sub_r64_r64(HOST.ret, HOST.args[1]));
+
+	let map = map.seal().unwrap();
+	let f: extern "C" fn(u64, u64) -> u64 = unsafe { std::mem::transmute(map.as_ptr()) };
+	assert_eq!(f(200, 5), 195);
+}
+
+#[test]
+fn test_dual_views() {
+	let mut map = dasm::mmap::DualMmap::copy([
+		&dasm::tier::raw::amd64::mov_r64_r64(HOST.ret, HOST.args[0]) as &[u8],
+		&dasm::tier::raw::amd64::add_r64_r64(HOST.ret, HOST.args[1]),
+		&dasm::tier::raw::amd64::ret()
+	].concat()).unwrap();
+
+	assert_ne!(map.as_ptr(), map.as_mut_ptr() as *const u8);
+
+	let f: extern "C" fn(u64, u64) -> u64 = unsafe { std::mem::transmute(map.as_ptr()) };
+	assert_eq!(f(5, 200), 205);
+
+	// Patch through the writable view, visible through the executable one.
+	let offset = dasm::tier::raw::amd64::mov_r64_r64(HOST.ret, HOST.args[0]).len();
+	let exec = unsafe { map.as_ptr().add(offset) };
+	let write = map.to_write(exec).unwrap();
+	assert_eq!(map.to_exec(write), Some(exec));
+
+	let sub = dasm::tier::raw::amd64::sub_r64_r64(HOST.ret, HOST.args[1]);
+	unsafe { std::ptr::copy_nonoverlapping(sub.as_ptr(), write, sub.len()) };
+
+	assert_eq!(f(200, 5), 195);
+	assert_eq!(map.to_write(std::ptr::null()), None);
+}
+
+#[test]
+fn test_code_heap() {
+	let mut heap = dasm::mmap::CodeHeap::new().with_region_size(4096);
+
+	let slots = (0..1000u64)
+		.map(|i| {
+			heap.alloc([
+				&dasm::tier::raw::amd64::mov_r64_i64(HOST.ret, i) as &[u8],
+				&dasm::tier::raw::amd64::add_r64_r64(HOST.ret, HOST.args[0]),
+				&dasm::tier::raw::amd64::ret()
+			].concat()).unwrap()
+		})
+		.collect::<Vec<_>>();
+
+	let stats = heap.stats();
+	assert_eq!(stats.allocations, 1000);
+	assert_eq!(stats.used, 1000 * 16);
+	assert!(stats.regions < 10);
+
+	// Code runs from a shared read + execute view, never made writable.
+	let region = slots[0].as_ptr() as usize;
+	let maps = std::fs::read_to_string("/proc/self/maps").unwrap();
+	let line = maps.lines()
+		.find(|l| {
+			let (start, end) = l.split_once(' ').unwrap().0.split_once('-').unwrap();
+			(usize::from_str_radix(start, 16).unwrap()..usize::from_str_radix(end, 16).unwrap()).contains(&region)
+		})
+		.unwrap();
+	assert!(line.contains(" r-xs "), "{line}");
+
+	for (i, slot) in slots.iter().enumerate() {
+		assert_eq!(slot.as_ptr() as usize % 16, 0);
+
+		let f: extern "C" fn(u64) -> u64 = unsafe { std::mem::transmute(slot.as_ptr()) };
+		assert_eq!(f(1), i as u64 + 1);
+	}
+
+	for slot in slots {
+		heap.free(slot);
+	}
+
+	let stats = heap.stats();
+	assert_eq!(stats.allocations, 0);
+	assert_eq!(stats.used, 0);
+	assert_eq!(stats.free, stats.reserved);
+
+	// Freed memory is reused rather than mapping more.
+	let regions = stats.regions;
+	heap.alloc(vec![0xC3; 3000]).unwrap();
+	assert_eq!(heap.stats().regions, regions);
+
+	// Regions can still be placed, just not on explicit huge pages.
+	let target = test_code_heap as *const u8;
+	let mut heap = dasm::mmap::CodeHeap::new().with_options(dasm::mmap::MmapOptions::new().near(target));
+	let slot = heap.alloc([0xC3]).unwrap();
+	assert!((slot.as_ptr() as usize).abs_diff(target as usize) < i32::MAX as usize);
+
+	let huge = dasm::mmap::MmapOptions::new().huge_tlb();
+	assert_eq!(dasm::mmap::DualMmap::new_with(1, &huge).unwrap_err(), dasm::mmap::MmapError::Map(dasm::mmap::Errno::EINVAL));
+}
+
+#[test]
+fn test_as_fn_and_leak() {
+	let code = [
+		&dasm::tier::raw::amd64::mov_r64_r64(HOST.ret, HOST.args[0]) as &[u8],
+		&dasm::tier::raw::amd64::add_r64_r64(HOST.ret, HOST.args[1]),
+		&dasm::tier::raw::amd64::ret()
+	].concat();
+
+	let map = dasm::mmap::Mmap::exec(&code).unwrap();
+	let adder = unsafe { map.as_fn::<extern "C" fn(u64, u64) -> u64>() };
+	assert_eq!(adder.call(5, 200), 205);
+
+	let adder: extern "C" fn(u64, u64) -> u64 = unsafe { dasm::mmap::Mmap::exec(&code).unwrap().leak() };
+	assert_eq!(adder(5, 200), 205);
+
+	let map = dasm::mmap::WxMmap::copy(&code).unwrap().seal().unwrap();
+	let adder = unsafe { map.as_fn::<unsafe extern "C" fn(u64, u64) -> u64>() };
+	assert_eq!(unsafe { adder.call(1, 2) }, 3);
+}
+
+#[test]
+fn test_errors() {
+	use dasm::mmap::{DualMmap, Errno, Mmap, MmapError, WxMmap};
+
+	assert_eq!(Mmap::exec([]).unwrap_err(), MmapError::ZeroLength);
+	assert_eq!(WxMmap::new(0).unwrap_err(), MmapError::ZeroLength);
+	assert_eq!(DualMmap::new(0).unwrap_err(), MmapError::ZeroLength);
+	assert_eq!(WxMmap::new(usize::MAX / 2).unwrap_err(), MmapError::Map(Errno::ENOMEM));
+
+	assert_eq!(MmapError::Protect(Errno::EACCES).to_string(), "Failed to change mmap permissions: permission denied (EACCES)");
+
+	Mmap::exec([0xC3]).unwrap().unmap().unwrap();
+	WxMmap::new(1).unwrap().unmap().unwrap();
+	DualMmap::new(1).unwrap().unmap().unwrap();
+}
+
+#[test]
+fn test_patching() {
+	use dasm::tier::raw::amd64::*;
+
+	// 0: jmp 8, padded to 8 bytes
+	// 8: mov eax, 1; ret
+	// 16: mov eax, 2; ret
+	let mut code = vec![0x90; 24];
+	code[0..5].copy_from_slice(&jmpnrd_i32(3));
+	code[8..13].copy_from_slice(&mov_r32_i32(HOST.ret, 1));
+	code[13] = 0xC3;
+	code[16..21].copy_from_slice(&mov_r32_i32(HOST.ret, 2));
+	code[21] = 0xC3;
+
+	let map = dasm::mmap::Mmap::exec(&code).unwrap();
+	let f = unsafe { map.as_fn::<extern "C" fn() -> u32>() };
+	assert_eq!(f.call(), 1);
+
+	map.patch_jmp(0, unsafe { map.as_ptr().add(16) }).unwrap();
+	assert_eq!(f.call(), 2);
+
+	let mut direct = [0x90; 8];
+	direct[0..5].copy_from_slice(&mov_r32_i32(HOST.ret, 3));
+	direct[5] = 0xC3;
+
+	map.patch(0, direct).unwrap();
+	assert_eq!(f.call(), 3);
+
+	assert_eq!(map.patch(4, direct), Err(dasm::mmap::MmapError::Misaligned));
+	assert_eq!(map.patch_jmp(4, map.as_ptr()), Err(dasm::mmap::MmapError::Misaligned));
+	assert_eq!(map.patch(24, direct), Err(dasm::mmap::MmapError::OutOfRange));
+	assert_eq!(map.patch(usize::MAX - 7, direct), Err(dasm::mmap::MmapError::OutOfRange));
+	assert_eq!(map.patch_jmp(usize::MAX, map.as_ptr()), Err(dasm::mmap::MmapError::OutOfRange));
+
+	// Same thing, but through the writable view of a dual mapping.
+	let map = dasm::mmap::DualMmap::copy(&code).unwrap();
+	let f = unsafe { map.as_fn::<extern "C" fn() -> u32>() };
+	assert_eq!(f.call(), 1);
+
+	map.patch_jmp(0, unsafe { map.as_ptr().add(16) }).unwrap();
+	assert_eq!(f.call(), 2);
+
+	map.patch(0, direct).unwrap();
+	assert_eq!(f.call(), 3);
+
+	assert_eq!(map.patch(usize::MAX - 7, direct), Err(dasm::mmap::MmapError::OutOfRange));
+}
+
+extern "C" fn triple(x: u64) -> u64 {
+	x * 3
+}
+
+#[test]
+fn test_near_mapping() {
+	use dasm::tier::raw::amd64::*;
+
+	let target = triple as *const u8;
+	let options = dasm::mmap::MmapOptions::new().near(target);
+
+	// Call straight into the Rust function with a rel32 displacement, keeping the stack aligned.
+	let prologue = sub_r64_i32(4, 8);
+	let mut code = [
+		&prologue as &[u8],
+		&callnrd_i32(0),
+		&add_r64_i32(4, 8),
+		&ret()
+	].concat();
+
+	let mut map = dasm::mmap::WxMmap::new_with(code.len(), &options).unwrap();
+	assert!((map.as_ptr() as usize).abs_diff(target as usize) < i32::MAX as usize);
+
+	let call_end = map.as_ptr() as i64 + (prologue.len() + 5) as i64;
+	let rel = i32::try_from(target as i64 - call_end).unwrap();
+	code[prologue.len()..prologue.len() + 5].copy_from_slice(&callnrd_i32(rel as u32));
+
+	map.as_mut()[..code.len()].copy_from_slice(&code);
+
+	let map = map.seal().unwrap();
+	let f = unsafe { map.as_fn::<extern "C" fn(u64) -> u64>() };
+	assert_eq!(f.call(14), 42);
+}
+
+#[test]
+fn test_mapping_options() {
+	use dasm::mmap::{Errno, Mmap, MmapError, MmapOptions};
+
+	let map = Mmap::exec_with([0xC3], &MmapOptions::new().low_32bit()).unwrap();
+	assert!((map.as_ptr() as usize) < (1 << 32));
+
+	let map = Mmap::exec_with([0xC3], &MmapOptions::new().transparent_huge_pages()).unwrap();
+	let f = unsafe { map.as_fn::<extern "C" fn()>() };
+	f.call();
+
+	// Can't map over something that's already there.
+	let taken = MmapOptions::new().fixed(map.as_ptr());
+	assert_eq!(Mmap::exec_with([0xC3], &taken).unwrap_err(), MmapError::Map(Errno::EEXIST));
+}
+
+static mut STACK_LOCAL: usize = 0;
+
+extern "C" fn record_stack() -> u64 {
+	let local = 0u8;
+	unsafe { STACK_LOCAL = &local as *const u8 as usize };
+	7
+}
+
+#[test]
+fn test_guarded_stack() {
+	use dasm::tier::raw::amd64::*;
+
+	let stack = dasm::mmap::Stack::new(64 * 1024).unwrap();
+	assert!(stack.len() >= 64 * 1024);
+	assert_eq!(stack.top() as usize % 16, 0);
+	assert_eq!(stack.bottom() as usize - stack.guard() as usize, dasm::mmap::page_size());
+
+	// The guard page is mapped, but inaccessible.
+	let guard = format!("{:x}-", stack.guard() as usize);
+	let maps = std::fs::read_to_string("/proc/self/maps").unwrap();
+	let line = maps.lines().find(|l| l.starts_with(&guard)).expect("Guard page not mapped");
+	assert!(line.contains(" ---p "), "{line}");
+
+	// Run a Rust function on the new stack, then switch back.
+	let map = dasm::mmap::Mmap::exec([
+		&switch_stack(RDX, stack.top() as u64) as &[u8],
+		&push_r64(RDX),
+		&sub_r64_i32(RSP, 8),
+		&mov_r64_i64(RCX, record_stack as *const u8 as u64),
+		&callnai_r64(RCX),
+		&add_r64_i32(RSP, 8),
+		&pop_r64(RDX),
+		&restore_stack(RDX),
+		&ret()
+	].concat()).unwrap();
+
+	let f = unsafe { map.as_fn::<extern "C" fn() -> u64>() };
+	assert_eq!(f.call(), 7);
+
+	let local = unsafe { STACK_LOCAL };
+	assert!((stack.bottom() as usize..stack.top() as usize).contains(&local));
+}
@@ -0,0 +1,168 @@
+use dasm::tier::portable::*;
+
+/// Multiplies the numbers from 1 through the first argument.
+fn factorial<B: Backend>() -> Vec<u8> {
+	let mut asm = Assembler::<B>::new(1);
+	let (top, done) = (asm.label(), asm.label());
+
+	asm.mov_imm(R1, 1);
+	asm.mov_imm(R2, 1);
+	asm.bind(top);
+	asm.branch(Cond::Le, R0, R2, done);
+	asm.mul(R1, R1, R0);
+	asm.sub(R0, R0, R2);
+	asm.jump(top);
+	asm.bind(done);
+	asm.mul(R1, R0, R1);
+	asm.ret(R1);
+
+	asm.finish().unwrap()
+}
+
+#[test]
+#[cfg(all(target_arch = "x86_64", feature = "mmap"))]
+fn test_factorial() {
+	let map = dasm::mmap::Mmap::exec(factorial::<Amd64>()).unwrap();
+	let f = unsafe { map.as_fn::<extern "C" fn(u64) -> u64>() };
+
+	assert_eq!(f.call(1), 1);
+	assert_eq!(f.call(5), 120);
+	assert_eq!(f.call(20), 2432902008176640000);
+}
+
+#[test]
+#[cfg(all(target_arch = "x86_64", feature = "mmap"))]
+fn test_load_store() {
+	// Swaps two words of an array, and returns their difference.
+	let mut asm = Assembler::<Amd64>::new(1);
+	asm.load(R1, R0, 0);
+	asm.load(R2, R0, 1000 * 8);
+	asm.store(R0, 0, R2);
+	asm.store(R0, 1000 * 8, R1);
+	asm.sub(R1, R2, R1);
+	asm.ret(R1);
+
+	let map = dasm::mmap::Mmap::exec(asm.finish().unwrap()).unwrap();
+	let f = unsafe { map.as_fn::<extern "C" fn(*mut u64) -> u64>() };
+
+	let mut words = vec![0u64; 1001];
+	words[0] = 5;
+	words[1000] = 12;
+
+	assert_eq!(f.call(words.as_mut_ptr()), 7);
+	assert_eq!((words[0], words[1000]), (12, 5));
+}
+
+extern "C" fn sub3(a: u64, b: u64, c: u64) -> u64 {
+	a - b - c
+}
+
+#[test]
+#[cfg(all(target_arch = "x86_64", feature = "mmap"))]
+fn test_call() {
+	// Calls with the arguments rotated, then checks every register survived.
+	let mut asm = Assembler::<Amd64>::new(3);
+	asm.mov_imm(R3, 30);
+	asm.mov_imm(R4, 40);
+	asm.mov_imm(R5, 50);
+	asm.call(sub3 as *const () as u64, &[R2, R0, R1], Some(R3));
+
+	asm.add(R0, R0, R1);
+	asm.add(R0, R2, R0);
+	asm.add(R0, R0, R4);
+	asm.add(R0, R0, R5);
+	asm.mul(R0, R3, R0);
+	asm.ret(R0);
+
+	let map = dasm::mmap::Mmap::exec(asm.finish().unwrap()).unwrap();
+	let f = unsafe { map.as_fn::<extern "C" fn(u64, u64, u64) -> u64>() };
+
+	// (100 - 1 - 2) * (1 + 2 + 100 + 40 + 50)
+	assert_eq!(f.call(1, 2, 100), 97 * 193);
+}
+
+#[test]
+#[cfg(all(target_arch = "x86_64", feature = "mmap"))]
+fn test_conditions() {
+	let conds = [
+		(Cond::Eq, [false, true, false]),
+		(Cond::Ne, [true, false, true]),
+		(Cond::Lt, [false, false, true]),
+		(Cond::Le, [false, true, true]),
+		(Cond::Gt, [true, false, false]),
+		(Cond::Ge, [true, true, false]),
+		(Cond::Below, [true, false, false]),
+		(Cond::BelowEq, [true, true, false]),
+		(Cond::Above, [false, false, true]),
+		(Cond::AboveEq, [false, true, true])
+	];
+
+	for (cond, expected) in conds {
+		let mut asm = Assembler::<Amd64>::new(2);
+		let taken = asm.label();
+		asm.branch(cond, R0, R1, taken);
+		asm.mov_imm(R0, 0);
+		asm.ret(R0);
+		asm.bind(taken);
+		asm.mov_imm(R0, 1);
+		asm.ret(R0);
+
+		let map = dasm::mmap::Mmap::exec(asm.finish().unwrap()).unwrap();
+		let f = unsafe { map.as_fn::<extern "C" fn(i64, i64) -> u64>() };
+
+		// Compared with -1, which is the largest value when unsigned.
+		let results = [f.call(1, -1) == 1, f.call(-1, -1) == 1, f.call(-1, 1) == 1];
+		assert_eq!(results, expected, "{cond:?}");
+	}
+}
+
+#[test]
+fn test_x86() {
+	let code = factorial::<X86>();
+
+	#[rustfmt::skip]
+	assert_eq!(code[..29], [
+		0x53, 0x56, 0x57, 0x55,                     // push ebx; push esi; push edi; push ebp
+		0x8B, 0x84, 0x24, 0x14, 0x00, 0x00, 0x00,   // mov eax, [esp + 20]
+		0xB9, 0x01, 0x00, 0x00, 0x00,               // mov ecx, 1
+		0xBA, 0x01, 0x00, 0x00, 0x00,               // mov edx, 1
+		0x3B, 0xC2,                                 // cmp eax, edx
+		0x0F, 0x8E, 0x0A, 0x00, 0x00, 0x00          // jle done
+	]);
+
+	#[rustfmt::skip]
+	assert!(code.ends_with(&[
+		0x0F, 0xAF, 0xC8,                           // imul ecx, eax
+		0x8B, 0xC1,                                 // mov eax, ecx
+		0x5D, 0x5F, 0x5E, 0x5B,                     // pop ebp; pop edi; pop esi; pop ebx
+		0xC3                                        // ret
+	]));
+}
+
+#[test]
+fn test_aarch64() {
+	let code = factorial::<Aarch64>();
+	let words: Vec<u32> = code.chunks(4).map(|w| u32::from_le_bytes(w.try_into().unwrap())).collect();
+
+	assert_eq!(words[..2], [0xA9BF7BFD, 0x910003FD]); // stp x29, x30, [sp, #-16]!; mov x29, sp
+	assert_eq!(words[10..12], [0xEB02001F, 0x5400008D]); // cmp x0, x2; b.le done
+	assert_eq!(words[14], 0x17FFFFFC); // b top
+	assert_eq!(words[15..], [0x9B017C01, 0xAA0103E0, 0xA8C17BFD, 0xD65F03C0]);
+}
+
+#[test]
+fn test_errors() {
+	let mut asm = Assembler::<Amd64>::new(0);
+	let nowhere = asm.label();
+	asm.jump(nowhere);
+	assert_eq!(asm.finish(), Err(AsmError::Undefined(nowhere)));
+
+	let mut asm = Assembler::<Aarch64>::new(0);
+	let (far, start) = (asm.label(), asm.len());
+	asm.branch(Cond::Eq, R0, R1, far);
+	while asm.len() - start < 1 << 20 {
+		asm.mov_imm(R0, 0);
+	}
+	asm.bind(far);
+	assert_eq!(asm.finish(), Err(AsmError::OutOfRange(start + 4)));
+}
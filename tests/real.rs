@@ -0,0 +1,77 @@
+use dasm::object::flat::{Fixup, Flat};
+use dasm::tier::raw::x86::real::*;
+
+const AX: u8 = 0;
+const CX: u8 = 1;
+const DX: u8 = 2;
+const SI: u8 = 6;
+
+const DS: u8 = 3;
+
+#[test]
+fn test_operand_size() {
+	assert_eq!(mov_r16_i16(AX, 0x1234), [0xB8, 0x34, 0x12]);
+	assert_eq!(mov_r32_i32(AX, 0x1234), [OPSIZE, 0xB8, 0x34, 0x12, 0x00, 0x00]);
+	assert_eq!(add_r32_r32(AX, SI), [OPSIZE, 0x03, 0xC6]);
+	assert_eq!(push_r16(SI), [0x56]);
+	assert_eq!(callnrd_i16(0xFFFD), [0xE8, 0xFD, 0xFF]);
+	assert_eq!(in32_dx(), [OPSIZE, 0xED]);
+	assert_eq!(out8_i8(0x80), [0xE6, 0x80]);
+}
+
+#[test]
+fn test_memory_operands() {
+	assert_eq!(mov_r16_m16(AX, ADDR_BX_SI, 4), [0x8B, 0x80, 0x04, 0x00]);
+	assert_eq!(mov_m8_r8(ADDR_BP, 0xFFFE, CX), [0x88, 0x8E, 0xFE, 0xFF]);
+	assert_eq!(mov_r32_m32(DX, ADDR_ABSOLUTE, 0x7E00), [OPSIZE, 0x8B, 0x16, 0x00, 0x7E]);
+	assert_eq!(lea_r16_m16(SI, ADDR_BX, 0x10), [0x8D, 0xB7, 0x10, 0x00]);
+	assert_eq!(lgdt_m(ADDR_ABSOLUTE, 0x7C40), [0x0F, 0x01, 0x16, 0x40, 0x7C]);
+	assert_eq!(lidt_m(ADDR_BX, 0), [0x0F, 0x01, 0x9F, 0x00, 0x00]);
+	assert_eq!(jmpnai_m(ADDR_BX_DI, 0), [0xFF, 0xA1, 0x00, 0x00]);
+	assert_eq!(mov_s_r16(DS, AX), [0x8E, 0xD8]);
+	assert_eq!(mov_r16_s(AX, 1), [0x8C, 0xC8]);
+}
+
+#[test]
+fn test_far_branches() {
+	assert_eq!(jmpfad_i16_i16(0, 0x7C00), [0xEA, 0x00, 0x7C, 0x00, 0x00]);
+	assert_eq!(jmpfad_i16_i32(0x08, 0x1_0000), [OPSIZE, 0xEA, 0x00, 0x00, 0x01, 0x00, 0x08, 0x00]);
+	assert_eq!(callfad_i16_i16(0xF000, 0xFFF0), [0x9A, 0xF0, 0xFF, 0x00, 0xF0]);
+}
+
+#[test]
+fn test_mode_switch_stub() {
+	let image = Flat::boot_sector()
+		.bytes(&cli())
+		.bytes(&xor_r16_r16(AX, AX))
+		.bytes(&mov_s_r16(DS, AX))
+		.reference(&lgdt_m(ADDR_ABSOLUTE, 0), "gdtr", Fixup::Absolute16)
+		.reference_at(&jmpfad_i16_i16(0, 0), 1, "next", Fixup::Absolute16)
+		.label("next")
+		.bytes(&[SEG_DS])
+		.reference(&mov_r32_m32(AX, ADDR_ABSOLUTE, 0), "gdtr", Fixup::Absolute16)
+		.bytes(&hlt())
+		.align(8, 0)
+		.label("gdtr")
+		.bytes(&[0; 6]);
+
+	let b = image.write().unwrap();
+
+	// lgdt [gdtr]
+	assert_eq!(b[5..10], [0x0F, 0x01, 0x16, 0x18, 0x7C]);
+	// jmp 0:next, with the offset ahead of the segment.
+	assert_eq!(b[10..15], [0xEA, 0x0F, 0x7C, 0x00, 0x00]);
+	// mov eax, ds:[gdtr]
+	assert_eq!(b[15..21], [SEG_DS, OPSIZE, 0x8B, 0x06, 0x18, 0x7C]);
+}
+
+#[test]
+fn test_insn_len() {
+	// Operands and addresses are 16 bit unless overridden.
+	assert_eq!(insn_len(&[0xB8, 0x01, 0x00]), Some(3));
+	assert_eq!(insn_len(&[0x66, 0xB8, 0x01, 0x00, 0x00, 0x00]), Some(6));
+	assert_eq!(insn_len(&[0x8B, 0x46, 0x08]), Some(3));
+	assert_eq!(insn_len(&[0x67, 0x8B, 0x44, 0x24, 0x08]), Some(5));
+	assert_eq!(insn_len(&mov_r16_m16(AX, ADDR_BP, 8)), Some(4));
+	assert_eq!(insn_len(&mov_r32_m32(DX, ADDR_ABSOLUTE, 0x7E00)), Some(5));
+}
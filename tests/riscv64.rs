@@ -0,0 +1,128 @@
+// Expected bytes come from `llvm-mc -triple=riscv64 -mattr=+m,+a,+f,+d,+c -show-encoding`.
+
+use dasm::tier::raw::riscv64::*;
+
+const T0: u8 = 5;
+const T1: u8 = 6;
+const T2: u8 = 7;
+const S0: u8 = 8;
+const S1: u8 = 9;
+const A0: u8 = 10;
+const A1: u8 = 11;
+const A2: u8 = 12;
+const A3: u8 = 13;
+
+#[test]
+fn test_base() {
+	assert_eq!(sub(T0, T1, T2), [0xB3, 0x02, 0x73, 0x40]);
+	assert_eq!(sraw(A0, A1, A2), [0x3B, 0xD5, 0xC5, 0x40]);
+	assert_eq!(addi(A0, A1, -2048), [0x13, 0x85, 0x05, 0x80]);
+	assert_eq!(slli(A0, A1, 63), [0x13, 0x95, 0xF5, 0x03]);
+	assert_eq!(srai(A0, A1, 33), [0x13, 0xD5, 0x15, 0x42]);
+	assert_eq!(ld(RA, SP, 8), [0x83, 0x30, 0x81, 0x00]);
+	assert_eq!(sd(RA, SP, -8), [0x23, 0x3C, 0x11, 0xFE]);
+	assert_eq!(beq(A0, A1, -4), [0xE3, 0x0E, 0xB5, 0xFE]);
+	assert_eq!(bgeu(A0, A1, -4096), [0x63, 0x70, 0xB5, 0x80]);
+	assert_eq!(lui(A0, 0xF_FFFF), [0x37, 0xF5, 0xFF, 0xFF]);
+	assert_eq!(jal(RA, 2048), [0xEF, 0x00, 0x10, 0x00]);
+	assert_eq!(jal(ZERO, -1048576), [0x6F, 0x00, 0x00, 0x80]);
+	assert_eq!(jalr(ZERO, RA, 0), [0x67, 0x80, 0x00, 0x00]);
+	assert_eq!(ecall(), [0x73, 0x00, 0x00, 0x00]);
+	assert_eq!(fence(FENCE_RW, FENCE_W), [0x0F, 0x00, 0x10, 0x03]);
+}
+
+#[test]
+fn test_extensions() {
+	assert_eq!(mulhsu(A0, A1, A2), [0x33, 0xA5, 0xC5, 0x02]);
+	assert_eq!(remuw(A0, A1, A2), [0x3B, 0xF5, 0xC5, 0x02]);
+
+	assert_eq!(lr_d(A0, A1, AQ), [0x2F, 0xB5, 0x05, 0x14]);
+	assert_eq!(sc_w(A0, A2, A1, RL), [0x2F, 0xA5, 0xC5, 0x1A]);
+	assert_eq!(amoadd_d(A0, A2, A1, AQRL), [0x2F, 0xB5, 0xC5, 0x06]);
+
+	assert_eq!(fsd(A0, SP, -8), [0x27, 0x3C, 0xA1, 0xFE]);
+	assert_eq!(fadd_d(A0, A1, A2, RM_RNE), [0x53, 0x85, 0xC5, 0x02]);
+	assert_eq!(fsqrt_d(A0, A1, RM_RTZ), [0x53, 0x95, 0x05, 0x5A]);
+	assert_eq!(fcvt_l_d(A0, A1, RM_RTZ), [0x53, 0x95, 0x25, 0xC2]);
+	assert_eq!(fcvt_d_s(A0, A1), [0x53, 0x85, 0x05, 0x42]);
+	assert_eq!(fmv_x_d(A0, A1), [0x53, 0x85, 0x05, 0xE2]);
+	assert_eq!(fmadd_d(A0, A1, A2, A3, RM_DYN), [0x43, 0xF5, 0xC5, 0x6A]);
+}
+
+#[test]
+fn test_compressed() {
+	assert_eq!(c_addi4spn(A0, 1020), [0xE8, 0x1F]);
+	assert_eq!(c_lw(A0, A1, 124), [0xE8, 0x5D]);
+	assert_eq!(c_sd(A0, A1, 248), [0xE8, 0xFD]);
+	assert_eq!(c_addi(A0, -32), [0x01, 0x15]);
+	assert_eq!(c_li(A0, -1), [0x7D, 0x55]);
+	assert_eq!(c_addi16sp(-512), [0x01, 0x71]);
+	assert_eq!(c_lui(A0, 31), [0x7D, 0x65]);
+	assert_eq!(c_srai(A1, 1), [0x85, 0x85]);
+	assert_eq!(c_and(S0, S1), [0x65, 0x8C]);
+	assert_eq!(c_addw(A0, A1), [0x2D, 0x9D]);
+	assert_eq!(c_j(-2048), [0x01, 0xB0]);
+	assert_eq!(c_j(1234), [0xC9, 0xA9]);
+	assert_eq!(c_beqz(A0, -256), [0x01, 0xD1]);
+	assert_eq!(c_bnez(A0, 254), [0x7D, 0xED]);
+	assert_eq!(c_lwsp(A0, 252), [0x7E, 0x55]);
+	assert_eq!(c_ldsp(RA, 8), [0xA2, 0x60]);
+	assert_eq!(c_jr(RA), [0x82, 0x80]);
+	assert_eq!(c_mv(A0, A1), [0x2E, 0x85]);
+	assert_eq!(c_add(A0, A1), [0x2E, 0x95]);
+	assert_eq!(c_swsp(A0, 252), [0xAA, 0xDF]);
+	assert_eq!(c_sdsp(RA, 8), [0x06, 0xE4]);
+}
+
+/// Runs the `lui`, `addi`, `addiw`, `slli` and `srli` instructions [li] emits, returning the final register value.
+fn run(code: &[u8]) -> i64 {
+	let mut regs = [0i64; 32];
+
+	for word in code.chunks(4) {
+		let w = u32::from_le_bytes(word.try_into().unwrap());
+		let rd = (w >> 7 & 31) as usize;
+		let rs1 = regs[(w >> 15 & 31) as usize];
+		let imm = (w as i32 >> 20) as i64;
+
+		regs[rd] = match (w & 0x7F, w >> 12 & 7) {
+			(0x37, _) => (w & 0xFFFF_F000) as i32 as i64,
+			(0x13, 0) => rs1.wrapping_add(imm),
+			(0x1B, 0) => rs1.wrapping_add(imm) as i32 as i64,
+			(0x13, 1) => rs1 << (imm & 63),
+			(0x13, 5) => ((rs1 as u64) >> (imm & 63)) as i64,
+			_ => panic!("Unexpected instruction {w:08x}")
+		};
+	}
+
+	regs[A0 as usize]
+}
+
+#[test]
+fn test_li() {
+	assert_eq!(&*li(A0, 0), addi(A0, ZERO, 0));
+	assert_eq!(&*li(A0, -2048), addi(A0, ZERO, -2048));
+	assert_eq!(&*li(A0, 0x1000), lui(A0, 1));
+	assert_eq!(li(A0, 0x7FFF_FFFF).len(), 8);
+	assert_eq!(li(A0, 0xFFFF_FFFF).len(), 8);
+	assert_eq!(li(A0, 0xDEAD_BEEF).len(), 12);
+	assert_eq!(li(A0, 0x1234_5678_9ABC_DEF0).len(), 32);
+
+	let mut x: u64 = 0x9E37_79B9_7F4A_7C15;
+	let mut values = vec![0, 1, -1, 2047, 2048, -2049, i32::MAX as i64, i32::MIN as i64, i64::MAX, i64::MIN, 0x8000_0000, 0xFFFF_FFFF];
+
+	for i in 0..1000 {
+		x ^= x << 13;
+		x ^= x >> 7;
+		x ^= x << 17;
+
+		values.push(match i % 3 {
+			0 => x as i64,
+			1 => (x >> (x % 64)) as i64,
+			_ => ((x >> 20) << (x % 40)) as i64
+		});
+	}
+
+	for v in values {
+		assert_eq!(run(&li(A0, v)), v, "li {v:#x}");
+	}
+}
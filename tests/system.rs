@@ -0,0 +1,51 @@
+// Privileged instructions can't run in a test, so these only check encodings.
+
+#[test]
+fn test_amd64_system() {
+	use dasm::tier::raw::amd64::*;
+
+	const RAX: u8 = 0;
+	const RSP: u8 = 4;
+	const R9: u8 = 9;
+	const R12: u8 = 12;
+	const R13: u8 = 13;
+
+	assert_eq!(mov_cr_r64(3, RAX), [0x40, 0x0F, 0x22, 0xD8]);
+	assert_eq!(mov_r64_cr(R9, 8), [0x45, 0x0F, 0x20, 0xC1]);
+	assert_eq!(mov_cr_r64(4, R12), [0x41, 0x0F, 0x22, 0xE4]);
+	assert_eq!(mov_r64_dr(RAX, 6), [0x40, 0x0F, 0x21, 0xF0]);
+	assert_eq!(ltr_r16(R9), [0x41, 0x0F, 0x00, 0xD9]);
+
+	assert_eq!(lgdt_m(RSP, 0), [0x40, 0x0F, 0x01, 0x94, 0x24, 0, 0, 0, 0]);
+	assert_eq!(lidt_m(R13, -8), [0x41, 0x0F, 0x01, 0x9C, 0x25, 0xF8, 0xFF, 0xFF, 0xFF]);
+	assert_eq!(invlpg_m(R12, 0x100), [0x41, 0x0F, 0x01, 0xBC, 0x24, 0x00, 0x01, 0, 0]);
+	assert_eq!(xsave64_m(RSP, 0), [0x48, 0x0F, 0xAE, 0xA4, 0x24, 0, 0, 0, 0]);
+	assert_eq!(xrstor64_m(R9, 64), [0x49, 0x0F, 0xAE, 0xAC, 0x21, 0x40, 0, 0, 0]);
+
+	assert_eq!(rdmsr(), [0x0F, 0x32]);
+	assert_eq!(wrmsr(), [0x0F, 0x30]);
+	assert_eq!(swapgs(), [0x0F, 0x01, 0xF8]);
+	assert_eq!(sysretq(), [0x48, 0x0F, 0x07]);
+	assert_eq!(iretq(), [0x48, 0xCF]);
+	assert_eq!(wbinvd(), [0x0F, 0x09]);
+}
+
+#[test]
+fn test_x86_system() {
+	use dasm::tier::raw::x86::*;
+
+	const EAX: u8 = 0;
+	const EBX: u8 = 3;
+	const ESP: u8 = 4;
+	const EBP: u8 = 5;
+
+	assert_eq!(mov_cr_r32(0, EAX), [0x0F, 0x22, 0xC0]);
+	assert_eq!(mov_r32_cr(EBX, 2), [0x0F, 0x20, 0xD3]);
+	assert_eq!(mov_dr_r32(7, 1), [0x0F, 0x23, 0xF9]);
+	assert_eq!(ltr_r16(EAX), [0x0F, 0x00, 0xD8]);
+
+	assert_eq!(lgdt_m(ESP, 8), [0x0F, 0x01, 0x94, 0x24, 0x08, 0, 0, 0]);
+	assert_eq!(invlpg_m(EBP, 0), [0x0F, 0x01, 0xBC, 0x25, 0, 0, 0, 0]);
+	assert_eq!(xrstor_m(EAX, -4), [0x0F, 0xAE, 0xAC, 0x20, 0xFC, 0xFF, 0xFF, 0xFF]);
+	assert_eq!(iret(), [0xCF]);
+}
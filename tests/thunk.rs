@@ -0,0 +1,87 @@
+#![cfg(all(target_arch = "x86_64", target_os = "linux"))]
+
+use dasm::abi::{HOST, SYSV_AMD64, WIN64};
+use dasm::mmap::{LazyStub, Mmap, MmapError, Plt};
+
+extern "C" fn digits(ctx: u64, a: u64, b: u64) -> u64 {
+	ctx * 100 + a * 10 + b
+}
+
+#[test]
+fn test_closure() {
+	let map = Mmap::exec(dasm::abi::closure_thunk(HOST, 7, digits as *const () as u64)).unwrap();
+	let f = unsafe { map.as_fn::<extern "C" fn(u64, u64) -> u64>() };
+	assert_eq!(f.call(1, 2), 712);
+
+	// Win64 shifts the float positions along with the integer ones.
+	let code = dasm::abi::closure_thunk(WIN64, 7, 0x1000);
+	// mov r9, r8; movaps xmm3, xmm2
+	assert!(code.starts_with(&[0x4D, 0x8B, 0xC8, 0x40, 0x0F, 0x28, 0xDA]));
+}
+
+extern "win64" fn weigh(a: u64, b: u64, c: u64, d: u64, e: u64) -> u64 {
+	a + 2 * b + 3 * c + 4 * d + 5 * e
+}
+
+extern "sysv64" fn weigh_sysv(a: u64, b: u64, c: u64, d: u64) -> u64 {
+	a + 2 * b + 3 * c + 4 * d
+}
+
+#[test]
+fn test_adapters() {
+	// The fifth argument goes from a register onto the stack.
+	let map = Mmap::exec(dasm::abi::abi_adapter(SYSV_AMD64, WIN64, weigh as *const () as u64, 5)).unwrap();
+	let f = unsafe { map.as_fn::<extern "C" fn(u64, u64, u64, u64, u64) -> u64>() };
+	assert_eq!(f.call(1, 10, 100, 1000, 10000), 54321);
+
+	let map = Mmap::exec(dasm::abi::abi_adapter(WIN64, SYSV_AMD64, weigh_sysv as *const () as u64, 4)).unwrap();
+	let f: extern "win64" fn(u64, u64, u64, u64) -> u64 = unsafe { std::mem::transmute(map.as_ptr()) };
+	assert_eq!(f(1, 10, 100, 1000), 4321);
+}
+
+static RESOLVED: std::sync::atomic::AtomicU64 = std::sync::atomic::AtomicU64::new(0);
+
+extern "C" fn scale(a: u64, x: f64, b: u64, y: f64) -> f64 {
+	a as f64 * x + b as f64 * y
+}
+
+extern "C" fn resolve(ctx: u64) -> *const u8 {
+	RESOLVED.fetch_add(ctx, std::sync::atomic::Ordering::Relaxed);
+	scale as *const u8
+}
+
+#[test]
+fn test_lazy_stub() {
+	let stub = LazyStub::new(resolve, 1).unwrap();
+	assert_eq!(stub.resolved(), None);
+
+	// Integer and float arguments both survive the resolver.
+	let f = unsafe { stub.as_fn::<extern "C" fn(u64, f64, u64, f64) -> f64>() };
+	assert_eq!(f.call(2, 1.5, 3, 0.25), 3.75);
+	assert_eq!(f.call(4, 0.5, 1, 8.0), 10.0);
+
+	assert_eq!(RESOLVED.load(std::sync::atomic::Ordering::Relaxed), 1);
+	assert_eq!(stub.resolved(), Some(scale as *const u8));
+}
+
+extern "C" fn double(x: u64) -> u64 {
+	x * 2
+}
+
+extern "C" fn square(x: u64) -> u64 {
+	x * x
+}
+
+#[test]
+fn test_plt() {
+	let plt = Plt::new(&[double as *const u8, square as *const u8]).unwrap();
+	assert_eq!(plt.len(), 2);
+
+	let call = |i: usize, x: u64| unsafe { std::mem::transmute::<*const u8, extern "C" fn(u64) -> u64>(plt.entry(i))(x) };
+	assert_eq!((call(0, 5), call(1, 5)), (10, 25));
+
+	plt.set(0, square as *const u8).unwrap();
+	assert_eq!(call(0, 6), 36);
+
+	assert_eq!(Plt::new(&[]).unwrap_err(), MmapError::ZeroLength);
+}
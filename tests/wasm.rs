@@ -0,0 +1,135 @@
+use dasm::object::wasm::{self, Export, FuncType, Function, Module};
+use dasm::object::ObjectError;
+use dasm::tier::raw::wasm::*;
+
+#[test]
+fn test_leb128() {
+	assert_eq!(&*uleb128(0), [0x00]);
+	assert_eq!(&*uleb128(127), [0x7F]);
+	assert_eq!(&*uleb128(128), [0x80, 0x01]);
+	assert_eq!(&*uleb128(624485), [0xE5, 0x8E, 0x26]);
+	assert_eq!(uleb128(u64::MAX).len(), 10);
+
+	assert_eq!(&*sleb128(0), [0x00]);
+	assert_eq!(&*sleb128(-1), [0x7F]);
+	assert_eq!(&*sleb128(63), [0x3F]);
+	assert_eq!(&*sleb128(64), [0xC0, 0x00]);
+	assert_eq!(&*sleb128(-64), [0x40]);
+	assert_eq!(&*sleb128(-65), [0xBF, 0x7F]);
+	assert_eq!(&*sleb128(-123456), [0xC0, 0xBB, 0x78]);
+	assert_eq!(sleb128(i64::MIN).len(), 10);
+}
+
+#[test]
+fn test_instructions() {
+	assert_eq!(&*i32_const(624485), [0x41, 0xE5, 0x8E, 0x26]);
+	assert_eq!(&*i32_const(-64), [0x41, 0x40]);
+	assert_eq!(&*i64_const(-123456), [0x42, 0xC0, 0xBB, 0x78]);
+	assert_eq!(f32_const(1.5), [0x43, 0x00, 0x00, 0xC0, 0x3F]);
+	assert_eq!(f64_const(-2.0), [0x44, 0, 0, 0, 0, 0, 0, 0x00, 0xC0]);
+
+	assert_eq!(&*i64_load32_u(2, 8), [0x35, 0x02, 0x08]);
+	assert_eq!(&*i32_store(0, 0x10000), [0x36, 0x00, 0x80, 0x80, 0x04]);
+	assert_eq!(&*local_get(200), [0x20, 0xC8, 0x01]);
+	assert_eq!(&*global_set(1), [0x24, 0x01]);
+	assert_eq!(&*call(3), [0x10, 0x03]);
+	assert_eq!(&*call_indirect(1, 0), [0x11, 0x01, 0x00]);
+	assert_eq!(&*br_if(2), [0x0D, 0x02]);
+
+	assert_eq!(block(BLOCK_EMPTY), [0x02, 0x40]);
+	assert_eq!(loop_(I32), [0x03, 0x7F]);
+	assert_eq!(if_(BLOCK_EMPTY), [0x04, 0x40]);
+	assert_eq!(else_(), [0x05]);
+	assert_eq!(end(), [0x0B]);
+
+	assert_eq!(i32_eqz(), [0x45]);
+	assert_eq!(i32_add(), [0x6A]);
+	assert_eq!(i64_rotr(), [0x8A]);
+	assert_eq!(f64_copysign(), [0xA6]);
+	assert_eq!(i32_wrap_i64(), [0xA7]);
+	assert_eq!(f64_reinterpret_i64(), [0xBF]);
+	assert_eq!(i64_extend32_s(), [0xC4]);
+	assert_eq!(i32_trunc_sat_f64_u(), [0xFC, 0x03]);
+	assert_eq!(memory_copy(), [0xFC, 0x0A, 0x00, 0x00]);
+	assert_eq!(memory_fill(), [0xFC, 0x0B, 0x00]);
+}
+
+#[test]
+fn test_module() {
+	// (func (export "add") (param i32 i32) (result i32) local.get 0 local.get 1 i32.add)
+	let body = [&local_get(0) as &[u8], &local_get(1), &i32_add(), &end()].concat();
+
+	let module = Module::new()
+		.func_type(FuncType::new(&[I32, I32], &[I32]))
+		.function(Function::new(0, &[], &body))
+		.export(Export::function("add", 0));
+
+	#[rustfmt::skip]
+	let expected = [
+		0x00, 0x61, 0x73, 0x6D, 0x01, 0x00, 0x00, 0x00,
+		0x01, 0x07, 0x01, 0x60, 0x02, 0x7F, 0x7F, 0x01, 0x7F,
+		0x03, 0x02, 0x01, 0x00,
+		0x07, 0x07, 0x01, 0x03, b'a', b'd', b'd', 0x00, 0x00,
+		0x0A, 0x09, 0x01, 0x07, 0x00, 0x20, 0x00, 0x20, 0x01, 0x6A, 0x0B
+	];
+
+	assert_eq!(wasm::write(&module).unwrap(), expected);
+}
+
+#[test]
+fn test_module_memory_and_locals() {
+	// Sums the bytes in [0, len) into a local.
+	let body = [
+		&block(BLOCK_EMPTY) as &[u8],
+		&loop_(BLOCK_EMPTY),
+		&local_get(0),
+		&i32_eqz(),
+		&br_if(1),
+		&local_get(0),
+		&i32_const(1),
+		&i32_sub(),
+		&local_tee(0),
+		&i32_load8_u(0, 0),
+		&local_get(1),
+		&i32_add(),
+		&local_set(1),
+		&br(0),
+		&end(),
+		&end(),
+		&local_get(1),
+		&end()
+	]
+	.concat();
+
+	let module = Module::new()
+		.func_type(FuncType::new(&[I32], &[I32]))
+		.function(Function::new(0, &[(1, I32)], &body))
+		.memory(1, Some(2))
+		.export(Export::function("sum", 0))
+		.export(Export::memory("memory"));
+
+	let out = wasm::write(&module).unwrap();
+
+	// Memory section, with both limits.
+	let memory = [0x05, 0x04, 0x01, 0x01, 0x01, 0x02];
+	assert!(out.windows(memory.len()).any(|w| w == memory));
+
+	// Code section ends with the body, after its single run of locals.
+	let code = [&[0x01_u8, 0x01, 0x7F] as &[u8], &body].concat();
+	assert!(out.ends_with(&code));
+}
+
+#[test]
+fn test_module_errors() {
+	let module = Module::new().function(Function::new(0, &[], &[0x0B]));
+	assert_eq!(wasm::write(&module), Err(ObjectError::InvalidIndex(0)));
+
+	let module = Module::new().export(Export::memory("memory"));
+	assert_eq!(wasm::write(&module), Err(ObjectError::InvalidIndex(0)));
+
+	let module = Module::new()
+		.func_type(FuncType::new(&[], &[]))
+		.function(Function::new(0, &[], &[0x0B]))
+		.export(Export::function("missing", 1));
+	assert_eq!(wasm::write(&module), Err(ObjectError::InvalidIndex(1)));
+}
@@ -12,9 +12,46 @@ fn test_adder() {
 		&dasm::tier::raw::x86::ret()
 	].concat()).unwrap();
 
-	let adder: extern "C" fn(u64, u64) -> u64 = unsafe { std::mem::transmute(adder.as_ptr()) };
+	let adder = unsafe { adder.as_fn::<extern "C" fn(u64, u64) -> u64>() };
 
-	assert_eq!(adder(64, 64), 128);
-	assert_eq!(adder(0, 0), 0);
-	assert_eq!(adder((-1i64) as u64, 2) as i64, 1);
+	assert_eq!(adder.call(64, 64), 128);
+	assert_eq!(adder.call(0, 0), 0);
+	assert_eq!(adder.call((-1i64) as u64, 2) as i64, 1);
+}
+
+#[test]
+fn test_memory_and_branches() {
+	use dasm::tier::raw::x86::*;
+
+	assert_eq!(mov_r32_m32(EAX, ESP, 20), [0x8B, 0x84, 0x24, 20, 0, 0, 0]);
+	assert_eq!(mov_m32_r32(EDI, -4, ECX), [0x89, 0x8C, 0x27, 0xFC, 0xFF, 0xFF, 0xFF]);
+	assert_eq!(imul_r32_r32(ECX, EAX), [0x0F, 0xAF, 0xC8]);
+	assert_eq!(jccnrd_i32(CC_BE, 0), [0x0F, 0x86, 0, 0, 0, 0]);
+	assert_eq!(jccnrd_i8(CC_G, 0x7F), [0x7F, 0x7F]);
+}
+
+#[test]
+fn test_insn_len() {
+	use dasm::tier::raw::x86::insn_len;
+
+	#[rustfmt::skip]
+	let cases: &[&[u8]] = &[
+		&[0x40],                                          // inc eax
+		&[0x06],                                          // push es
+		&[0xC4, 0x00],                                    // les eax, [eax]
+		&[0x62, 0x00],                                    // bound eax, [eax]
+		&[0x62, 0xF1, 0x7C, 0x48, 0x10, 0x04, 0x24],      // vmovups zmm0, [esp]
+		&[0x8B, 0x05, 0x00, 0x00, 0x00, 0x00],            // mov eax, [0]
+		&[0x66, 0xE8, 0x00, 0x00],                        // call rel16
+		&[0x67, 0xA1, 0x00, 0x00],                        // mov eax, [0]
+		&[0x67, 0x8B, 0x46, 0x08],                        // mov eax, [bp + 8]
+		&[0x9A, 0x00, 0x00, 0x00, 0x00, 0x08, 0x00],      // call 0x8:0
+		&[0x8F, 0xE8, 0x78, 0xA2, 0xC1, 0x20]             // vpcmov xmm0, xmm0, xmm1, xmm2
+	];
+
+	for code in cases {
+		assert_eq!(insn_len(code), Some(code.len()), "{code:02X?}");
+	}
+
+	assert_eq!(insn_len(&[0xE8, 0x00]), None);
 }